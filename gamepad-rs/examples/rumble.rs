@@ -0,0 +1,29 @@
+extern crate gamepad_rs;
+
+use std::thread;
+use std::time::Duration;
+
+use gamepad_rs::*;
+
+// Rumbles every connected controller, first with the strong motor and then with the weak one.
+// On Linux, this works with any evdev device that supports FF_RUMBLE, including uinput devices
+pub fn main() {
+    let mut controller = ControllerContext::new().unwrap();
+
+    for i in 0..MAX_DEVICES {
+        controller.update(i);
+        if controller.state(i).status != ControllerStatus::Connected {
+            continue;
+        }
+        println!("[{}] {}", i, controller.info(i).name);
+        if !controller.rumble(i, 1.0, 0.0, Duration::from_millis(500)) {
+            println!("\trumble is not supported");
+            continue;
+        }
+        thread::sleep(Duration::from_millis(1000));
+        controller.rumble(i, 0.0, 1.0, Duration::from_millis(500));
+        thread::sleep(Duration::from_millis(1000));
+        // Stops the rumble on backends that have to do it in update
+        controller.update(i);
+    }
+}
@@ -11,6 +11,19 @@ pub fn main() {
     //for _ in 0..10 {
     loop {
         //println!("{} devices", controller.scan_controllers());
+        while let Some(event) = controller.poll_event() {
+            match event.kind {
+                ControllerEventKind::Connected => println!("[{}] connected", event.index),
+                ControllerEventKind::Disconnected => println!("[{}] disconnected", event.index),
+                ControllerEventKind::ButtonPressed { button } => {
+                    println!("[{}] button {} pressed", event.index, button)
+                }
+                ControllerEventKind::ButtonReleased { button } => {
+                    println!("[{}] button {} released", event.index, button)
+                }
+                ControllerEventKind::AxisMoved { .. } => (),
+            }
+        }
         for i in 0..MAX_DEVICES {
             controller.update(i);
             let status = controller.state(i).status;
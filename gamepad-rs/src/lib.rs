@@ -14,19 +14,46 @@ extern crate libc;
 #[macro_use]
 extern crate lazy_static;
 
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod mapping;
 mod platform;
 
+pub use self::mapping::*;
 pub use self::platform::*;
 
 pub const MAX_DEVICES: usize = 8;
 pub const MAX_DIGITAL: usize = 16;
 pub const MAX_ANALOG: usize = 8;
 
+/// Bus type, vendor, product and version of a device, as reported by EVIOCGID on Linux
+#[repr(C)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InputId {
+    pub bustype: u16,
+    pub vendor: u16,
+    pub product: u16,
+    pub version: u16,
+}
+
 #[derive(Debug)]
 pub struct ControllerInfo {
     pub name: String,
     pub digital_count: usize,
     pub analog_count: usize,
+    /// Number of hats, stored as x/y pairs at the end of the analog state
+    pub hat_count: usize,
+    pub id: InputId,
+    pub has_rumble: bool,
+    /// Best guess of the platform backend for the standard layout of the controller
+    pub mapping: Mapping,
+}
+
+impl Default for ControllerInfo {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ControllerInfo {
@@ -35,16 +62,16 @@ impl ControllerInfo {
             name: "null".to_owned(),
             digital_count: 0,
             analog_count: 0,
+            hat_count: 0,
+            id: InputId::default(),
+            has_rumble: false,
+            mapping: Mapping::new("null"),
         }
     }
 }
 
 lazy_static! {
-    static ref DEFAULT_CONTROLLER_INFO: ControllerInfo = ControllerInfo {
-        name: String::from("null"),
-        digital_count: 0,
-        analog_count: 0,
-    };
+    static ref DEFAULT_CONTROLLER_INFO: ControllerInfo = ControllerInfo::new();
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -53,7 +80,118 @@ pub enum ControllerStatus {
     Connected,
 }
 
-#[derive(Debug)]
+/// Buttons and axes index into `ControllerState::digital_state` and `ControllerState::analog_state`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControllerEventKind {
+    Connected,
+    Disconnected,
+    ButtonPressed { button: usize },
+    ButtonReleased { button: usize },
+    AxisMoved { axis: usize, value: f32 },
+}
+
+/// Events that are queued by `ControllerContext` and returned by `ControllerContext::poll_event`.
+/// The time is since the Unix epoch, and is taken from the device events when the platform has them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ControllerEvent {
+    pub index: usize,
+    pub time: Duration,
+    pub kind: ControllerEventKind,
+}
+
+// Events are dropped, oldest first, if the caller does not poll them
+const MAX_QUEUED_EVENTS: usize = 1024;
+
+/// The event queue of a `ControllerContext`, which also keeps the button edges of the
+/// last update of each controller, for `ControllerContext::is_pressed` and `is_released`
+struct EventQueue {
+    events: VecDeque<ControllerEvent>,
+    pressed: [[bool; MAX_DIGITAL]; MAX_DEVICES],
+    released: [[bool; MAX_DIGITAL]; MAX_DEVICES],
+}
+
+impl EventQueue {
+    fn new() -> Self {
+        EventQueue {
+            events: VecDeque::new(),
+            pressed: [[false; MAX_DIGITAL]; MAX_DEVICES],
+            released: [[false; MAX_DIGITAL]; MAX_DEVICES],
+        }
+    }
+
+    fn push(&mut self, index: usize, time: Duration, kind: ControllerEventKind) {
+        if index >= MAX_DEVICES {
+            return;
+        }
+        match kind {
+            ControllerEventKind::ButtonPressed { button } if button < MAX_DIGITAL => {
+                self.pressed[index][button] = true;
+            }
+            ControllerEventKind::ButtonReleased { button } if button < MAX_DIGITAL => {
+                self.released[index][button] = true;
+            }
+            _ => (),
+        }
+        if self.events.len() >= MAX_QUEUED_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(ControllerEvent { index, time, kind });
+    }
+
+    /// Pushes events for all the buttons and axes that differ between the two states
+    fn push_changes(
+        &mut self,
+        index: usize,
+        previous: &ControllerState,
+        current: &ControllerState,
+        time: Duration,
+    ) {
+        for button in 0..MAX_DIGITAL {
+            if previous.digital_state[button] != current.digital_state[button] {
+                let kind = if current.digital_state[button] {
+                    ControllerEventKind::ButtonPressed { button }
+                } else {
+                    ControllerEventKind::ButtonReleased { button }
+                };
+                self.push(index, time, kind);
+            }
+        }
+        for axis in 0..MAX_ANALOG {
+            if previous.analog_state[axis] != current.analog_state[axis] {
+                let value = current.analog_state[axis];
+                self.push(index, time, ControllerEventKind::AxisMoved { axis, value });
+            }
+        }
+    }
+
+    /// Called at the start of each update of a controller
+    fn clear_edges(&mut self, index: usize) {
+        if index < MAX_DEVICES {
+            self.pressed[index] = [false; MAX_DIGITAL];
+            self.released[index] = [false; MAX_DIGITAL];
+        }
+    }
+
+    fn pop(&mut self) -> Option<ControllerEvent> {
+        self.events.pop_front()
+    }
+
+    fn is_pressed(&self, index: usize, button: usize) -> bool {
+        index < MAX_DEVICES && button < MAX_DIGITAL && self.pressed[index][button]
+    }
+
+    fn is_released(&self, index: usize, button: usize) -> bool {
+        index < MAX_DEVICES && button < MAX_DIGITAL && self.released[index][button]
+    }
+}
+
+fn current_time() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+}
+
+#[derive(Debug, Clone)]
 pub struct ControllerState {
     pub status: ControllerStatus,
     pub sequence: usize,
@@ -61,6 +199,12 @@ pub struct ControllerState {
     pub analog_state: [f32; MAX_ANALOG],
 }
 
+impl Default for ControllerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ControllerState {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,650 @@
+// https://github.com/gabomdq/SDL_GameControllerDB
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{ControllerInfo, ControllerState, InputId, MAX_ANALOG, MAX_DIGITAL};
+
+pub const BUTTON_COUNT: usize = 15;
+pub const AXIS_COUNT: usize = 6;
+
+#[cfg(target_os = "linux")]
+const PLATFORM_NAME: &str = "Linux";
+#[cfg(target_os = "windows")]
+const PLATFORM_NAME: &str = "Windows";
+#[cfg(target_os = "macos")]
+const PLATFORM_NAME: &str = "Mac OS X";
+
+/// Buttons of the standard gamepad layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl Button {
+    pub const ALL: [Button; BUTTON_COUNT] = [
+        Button::A,
+        Button::B,
+        Button::X,
+        Button::Y,
+        Button::Back,
+        Button::Guide,
+        Button::Start,
+        Button::LeftStick,
+        Button::RightStick,
+        Button::LeftShoulder,
+        Button::RightShoulder,
+        Button::DPadUp,
+        Button::DPadDown,
+        Button::DPadLeft,
+        Button::DPadRight,
+    ];
+
+    /// The name used for the button in gamecontrollerdb mappings
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Button::A => "a",
+            Button::B => "b",
+            Button::X => "x",
+            Button::Y => "y",
+            Button::Back => "back",
+            Button::Guide => "guide",
+            Button::Start => "start",
+            Button::LeftStick => "leftstick",
+            Button::RightStick => "rightstick",
+            Button::LeftShoulder => "leftshoulder",
+            Button::RightShoulder => "rightshoulder",
+            Button::DPadUp => "dpup",
+            Button::DPadDown => "dpdown",
+            Button::DPadLeft => "dpleft",
+            Button::DPadRight => "dpright",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|button| button.name() == name).cloned()
+    }
+}
+
+/// Axes of the standard gamepad layout. Stick axes range from -1.0 to 1.0, with y positive
+/// downwards, and triggers range from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl Axis {
+    pub const ALL: [Axis; AXIS_COUNT] = [
+        Axis::LeftX,
+        Axis::LeftY,
+        Axis::RightX,
+        Axis::RightY,
+        Axis::LeftTrigger,
+        Axis::RightTrigger,
+    ];
+
+    /// The name used for the axis in gamecontrollerdb mappings
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Axis::LeftX => "leftx",
+            Axis::LeftY => "lefty",
+            Axis::RightX => "rightx",
+            Axis::RightY => "righty",
+            Axis::LeftTrigger => "lefttrigger",
+            Axis::RightTrigger => "righttrigger",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|axis| axis.name() == name).cloned()
+    }
+
+    pub fn is_trigger(&self) -> bool {
+        *self == Axis::LeftTrigger || *self == Axis::RightTrigger
+    }
+}
+
+/// The part of a raw axis that is used by a mapping
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisRange {
+    Full,
+    Positive,
+    Negative,
+}
+
+/// A raw input of a controller, indexing into `ControllerState`. Hats are stored as x/y pairs
+/// at the end of `analog_state`, and the mask is 1 for up, 2 for right, 4 for down and 8 for left
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MappingInput {
+    Button(usize),
+    Axis {
+        index: usize,
+        range: AxisRange,
+        is_inverted: bool,
+    },
+    Hat { index: usize, mask: u8 },
+}
+
+impl MappingInput {
+    /// Parses an input in gamecontrollerdb syntax, like `b0`, `-a1`, `a2~` or `h0.4`
+    pub fn parse(input: &str) -> Option<Self> {
+        let (range, input) = if let Some(input) = input.strip_prefix('+') {
+            (AxisRange::Positive, input)
+        } else if let Some(input) = input.strip_prefix('-') {
+            (AxisRange::Negative, input)
+        } else {
+            (AxisRange::Full, input)
+        };
+        let (is_inverted, input) = match input.strip_suffix('~') {
+            Some(input) => (true, input),
+            None => (false, input),
+        };
+        if let Some(index) = input.strip_prefix('b') {
+            index.parse().ok().map(MappingInput::Button)
+        } else if let Some(index) = input.strip_prefix('a') {
+            index.parse().ok().map(|index| MappingInput::Axis {
+                index,
+                range,
+                is_inverted,
+            })
+        } else if let Some(hat) = input.strip_prefix('h') {
+            let mut parts = hat.split('.');
+            let index = parts.next()?.parse().ok()?;
+            let mask = parts.next()?.parse().ok()?;
+            Some(MappingInput::Hat { index, mask })
+        } else {
+            None
+        }
+    }
+
+    fn read_hat(&self, info: &ControllerInfo, state: &ControllerState) -> bool {
+        if let MappingInput::Hat { index, mask } = *self {
+            if index >= info.hat_count {
+                return false;
+            }
+            let offset = info.analog_count - info.hat_count * 2 + index * 2;
+            if offset + 1 >= MAX_ANALOG {
+                return false;
+            }
+            let x = state.analog_state[offset];
+            let y = state.analog_state[offset + 1];
+            (mask & 1 != 0 && y < -0.5)
+                || (mask & 2 != 0 && x > 0.5)
+                || (mask & 4 != 0 && y > 0.5)
+                || (mask & 8 != 0 && x < -0.5)
+        } else {
+            false
+        }
+    }
+
+    // Full range axes return -1.0 to 1.0 and half range axes return 0.0 to 1.0
+    fn read_axis(index: usize, range: AxisRange, is_inverted: bool, state: &ControllerState) -> f32 {
+        let mut value = if index < MAX_ANALOG {
+            state.analog_state[index]
+        } else {
+            0.0
+        };
+        if is_inverted {
+            value = -value;
+        }
+        match range {
+            AxisRange::Full => value,
+            AxisRange::Positive => value.max(0.0),
+            AxisRange::Negative => (-value).max(0.0),
+        }
+    }
+
+    fn read_button(&self, info: &ControllerInfo, state: &ControllerState) -> bool {
+        match *self {
+            MappingInput::Button(index) => index < MAX_DIGITAL && state.digital_state[index],
+            MappingInput::Axis {
+                index,
+                range,
+                is_inverted,
+            } => {
+                let value = Self::read_axis(index, range, is_inverted, state);
+                match range {
+                    AxisRange::Full => value > 0.0,
+                    _ => value > 0.5,
+                }
+            }
+            MappingInput::Hat { .. } => self.read_hat(info, state),
+        }
+    }
+
+    fn read_value(&self, info: &ControllerInfo, state: &ControllerState, is_trigger: bool) -> f32 {
+        match *self {
+            MappingInput::Axis {
+                index,
+                range,
+                is_inverted,
+            } => {
+                let value = Self::read_axis(index, range, is_inverted, state);
+                if is_trigger && range == AxisRange::Full {
+                    (value + 1.0) / 2.0
+                } else {
+                    value
+                }
+            }
+            _ => {
+                if self.read_button(info, state) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Maps the raw inputs of a controller to the standard layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    pub name: String,
+    pub buttons: [Option<MappingInput>; BUTTON_COUNT],
+    pub axes: [Option<MappingInput>; AXIS_COUNT],
+}
+
+impl Mapping {
+    pub fn new(name: &str) -> Self {
+        Mapping {
+            name: name.to_owned(),
+            buttons: [None; BUTTON_COUNT],
+            axes: [None; AXIS_COUNT],
+        }
+    }
+
+    pub fn set_button(&mut self, button: Button, input: MappingInput) {
+        self.buttons[button as usize] = Some(input);
+    }
+
+    pub fn set_axis(&mut self, axis: Axis, input: MappingInput) {
+        self.axes[axis as usize] = Some(input);
+    }
+
+    /// Parses one line of a gamecontrollerdb file, returning the device id and the mapping.
+    /// Comments, invalid lines and mappings for other platforms return `None`.
+    /// Mappings to half axes, like `+leftx:b0`, are not supported and are ignored
+    pub fn parse(line: &str) -> Option<(InputId, Self)> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let mut fields = line.split(',');
+        let id = parse_guid(fields.next()?)?;
+        let mut mapping = Mapping::new(fields.next()?);
+        for field in fields {
+            let mut parts = field.splitn(2, ':');
+            let (target, input) = match (parts.next(), parts.next()) {
+                (Some(target), Some(input)) => (target, input),
+                _ => continue,
+            };
+            if target == "platform" {
+                if input != PLATFORM_NAME {
+                    return None;
+                }
+                continue;
+            }
+            let input = match MappingInput::parse(input) {
+                Some(input) => input,
+                None => continue,
+            };
+            if let Some(button) = Button::from_name(target) {
+                mapping.set_button(button, input);
+            } else if let Some(axis) = Axis::from_name(target) {
+                mapping.set_axis(axis, input);
+            }
+        }
+        Some((id, mapping))
+    }
+}
+
+// The GUID holds the little endian bus type, vendor, product and version, each followed by
+// two bytes that are ignored here
+fn parse_guid(guid: &str) -> Option<InputId> {
+    if guid.len() != 32 || !guid.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0u8; 16];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&guid[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    let word = |i: usize| bytes[i] as u16 | (bytes[i + 1] as u16) << 8;
+    Some(InputId {
+        bustype: word(0),
+        vendor: word(4),
+        product: word(8),
+        version: word(12),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseCurve {
+    Linear,
+    Quadratic,
+    Cubic,
+    Power(f32),
+}
+
+impl ResponseCurve {
+    /// Applies the curve to a value from 0.0 to 1.0
+    pub fn apply(&self, value: f32) -> f32 {
+        match *self {
+            ResponseCurve::Linear => value,
+            ResponseCurve::Quadratic => value * value,
+            ResponseCurve::Cubic => value * value * value,
+            ResponseCurve::Power(exponent) => value.powf(exponent),
+        }
+    }
+}
+
+/// Dead zones and response curve of the sticks or triggers. Stick dead zones are radial
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisSettings {
+    /// Magnitudes below this are read as 0.0
+    pub dead_zone: f32,
+    /// Magnitudes within this distance of the maximum are read as 1.0
+    pub outer_dead_zone: f32,
+    pub response_curve: ResponseCurve,
+}
+
+impl AxisSettings {
+    /// Rescales a magnitude from 0.0 to 1.0 to the range between the dead zones and applies the curve
+    pub fn apply(&self, magnitude: f32) -> f32 {
+        if magnitude <= self.dead_zone {
+            return 0.0;
+        }
+        let range = (1.0 - self.outer_dead_zone - self.dead_zone).max(f32::EPSILON);
+        let value = ((magnitude - self.dead_zone) / range).min(1.0);
+        self.response_curve.apply(value)
+    }
+}
+
+/// Controller state in the standard layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StandardState {
+    pub buttons: [bool; BUTTON_COUNT],
+    pub axes: [f32; AXIS_COUNT],
+}
+
+impl Default for StandardState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StandardState {
+    pub fn new() -> Self {
+        StandardState {
+            buttons: [false; BUTTON_COUNT],
+            axes: [0.0; AXIS_COUNT],
+        }
+    }
+
+    pub fn is_down(&self, button: Button) -> bool {
+        self.buttons[button as usize]
+    }
+
+    pub fn axis(&self, axis: Axis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    pub fn left_stick(&self) -> (f32, f32) {
+        (self.axis(Axis::LeftX), self.axis(Axis::LeftY))
+    }
+
+    pub fn right_stick(&self) -> (f32, f32) {
+        (self.axis(Axis::RightX), self.axis(Axis::RightY))
+    }
+}
+
+/// Reads controllers in the standard layout, using mappings from gamecontrollerdb files
+/// that are matched by vendor and product id. Controllers without a mapping use the
+/// best guess of the platform backend, from `ControllerInfo::mapping`
+pub struct StandardLayout {
+    mappings: HashMap<(u16, u16), Mapping>,
+    pub stick_settings: AxisSettings,
+    pub trigger_settings: AxisSettings,
+}
+
+impl Default for StandardLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StandardLayout {
+    pub fn new() -> Self {
+        StandardLayout {
+            mappings: HashMap::new(),
+            stick_settings: AxisSettings {
+                dead_zone: 0.2,
+                outer_dead_zone: 0.05,
+                response_curve: ResponseCurve::Linear,
+            },
+            trigger_settings: AxisSettings {
+                dead_zone: 0.1,
+                outer_dead_zone: 0.05,
+                response_curve: ResponseCurve::Linear,
+            },
+        }
+    }
+
+    pub fn add_mapping(&mut self, id: InputId, mapping: Mapping) {
+        self.mappings.insert((id.vendor, id.product), mapping);
+    }
+
+    /// Adds the mappings for this platform from the contents of a gamecontrollerdb file,
+    /// and returns the number of mappings that were added
+    pub fn add_mappings(&mut self, db: &str) -> usize {
+        let mut count = 0;
+        for (id, mapping) in db.lines().filter_map(Mapping::parse) {
+            // Mappings without a vendor id are keyed by name, which is not supported
+            if id.vendor != 0 {
+                self.add_mapping(id, mapping);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    pub fn load_mappings<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize> {
+        let db = fs::read_to_string(path)?;
+        Ok(self.add_mappings(&db))
+    }
+
+    pub fn get_mapping<'a>(&'a self, info: &'a ControllerInfo) -> &'a Mapping {
+        self.mappings
+            .get(&(info.id.vendor, info.id.product))
+            .unwrap_or(&info.mapping)
+    }
+
+    pub fn read(&self, info: &ControllerInfo, state: &ControllerState) -> StandardState {
+        let mapping = self.get_mapping(info);
+        let mut standard = StandardState::new();
+        for button in Button::ALL.iter() {
+            if let Some(input) = mapping.buttons[*button as usize] {
+                standard.buttons[*button as usize] = input.read_button(info, state);
+            }
+        }
+        for axis in Axis::ALL.iter() {
+            if let Some(input) = mapping.axes[*axis as usize] {
+                standard.axes[*axis as usize] = input.read_value(info, state, axis.is_trigger());
+            }
+        }
+        self.apply_stick_settings(&mut standard, Axis::LeftX, Axis::LeftY);
+        self.apply_stick_settings(&mut standard, Axis::RightX, Axis::RightY);
+        for axis in &[Axis::LeftTrigger, Axis::RightTrigger] {
+            let value = standard.axes[*axis as usize].clamp(0.0, 1.0);
+            standard.axes[*axis as usize] = self.trigger_settings.apply(value);
+        }
+        standard
+    }
+
+    fn apply_stick_settings(&self, standard: &mut StandardState, x_axis: Axis, y_axis: Axis) {
+        let x = standard.axes[x_axis as usize];
+        let y = standard.axes[y_axis as usize];
+        let magnitude = (x * x + y * y).sqrt();
+        if magnitude == 0.0 {
+            return;
+        }
+        let scale = self.stick_settings.apply(magnitude.min(1.0)) / magnitude;
+        standard.axes[x_axis as usize] = x * scale;
+        standard.axes[y_axis as usize] = y * scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XBOX_360_GUID: &str = "030000005e0400008e02000014010000";
+
+    fn xbox_360_line(platform: &str) -> String {
+        format!(
+            "{},Xbox 360 Controller,a:b0,b:b1,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,\
+             guide:b8,leftshoulder:b4,leftstick:b9,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,\
+             rightstick:b10,righttrigger:a5,rightx:a3,righty:a4,start:b7,x:b2,y:b3,platform:{},",
+            XBOX_360_GUID, platform
+        )
+    }
+
+    fn assert_close(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-5, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn parse_guid_reads_little_endian_words() {
+        let id = parse_guid(XBOX_360_GUID).unwrap();
+        assert_eq!(
+            id,
+            InputId {
+                bustype: 0x0003,
+                vendor: 0x045e,
+                product: 0x028e,
+                version: 0x0114,
+            }
+        );
+        assert_eq!(parse_guid("030000005e0400008e020000"), None);
+        assert_eq!(parse_guid("030000005e0400008e0200001401000g"), None);
+    }
+
+    #[test]
+    fn parse_mapping_inputs() {
+        assert_eq!(MappingInput::parse("b10"), Some(MappingInput::Button(10)));
+        assert_eq!(
+            MappingInput::parse("a2"),
+            Some(MappingInput::Axis {
+                index: 2,
+                range: AxisRange::Full,
+                is_inverted: false,
+            })
+        );
+        assert_eq!(
+            MappingInput::parse("-a1"),
+            Some(MappingInput::Axis {
+                index: 1,
+                range: AxisRange::Negative,
+                is_inverted: false,
+            })
+        );
+        assert_eq!(
+            MappingInput::parse("+a3~"),
+            Some(MappingInput::Axis {
+                index: 3,
+                range: AxisRange::Positive,
+                is_inverted: true,
+            })
+        );
+        assert_eq!(
+            MappingInput::parse("h0.4"),
+            Some(MappingInput::Hat { index: 0, mask: 4 })
+        );
+        assert_eq!(MappingInput::parse("h0"), None);
+        assert_eq!(MappingInput::parse("x1"), None);
+    }
+
+    #[test]
+    fn parse_mapping_line() {
+        let (id, mapping) = Mapping::parse(&xbox_360_line(PLATFORM_NAME)).unwrap();
+        assert_eq!(id.vendor, 0x045e);
+        assert_eq!(id.product, 0x028e);
+        assert_eq!(mapping.name, "Xbox 360 Controller");
+        assert_eq!(
+            mapping.buttons[Button::A as usize],
+            Some(MappingInput::Button(0))
+        );
+        assert_eq!(
+            mapping.buttons[Button::DPadDown as usize],
+            Some(MappingInput::Hat { index: 0, mask: 4 })
+        );
+        assert_eq!(
+            mapping.axes[Axis::RightTrigger as usize],
+            Some(MappingInput::Axis {
+                index: 5,
+                range: AxisRange::Full,
+                is_inverted: false,
+            })
+        );
+        assert!(mapping.buttons.iter().all(|input| input.is_some()));
+        assert!(mapping.axes.iter().all(|input| input.is_some()));
+    }
+
+    #[test]
+    fn parse_mapping_line_skips_comments_and_other_platforms() {
+        assert_eq!(Mapping::parse("# Linux"), None);
+        assert_eq!(Mapping::parse("   "), None);
+        assert_eq!(Mapping::parse(&xbox_360_line("Android")), None);
+    }
+
+    #[test]
+    fn axis_settings_apply_dead_zones() {
+        let settings = AxisSettings {
+            dead_zone: 0.2,
+            outer_dead_zone: 0.05,
+            response_curve: ResponseCurve::Linear,
+        };
+        assert_close(settings.apply(0.0), 0.0);
+        assert_close(settings.apply(0.2), 0.0);
+        assert_close(settings.apply(0.575), 0.5);
+        assert_close(settings.apply(0.95), 1.0);
+        assert_close(settings.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn axis_settings_apply_response_curve() {
+        let settings = AxisSettings {
+            dead_zone: 0.2,
+            outer_dead_zone: 0.05,
+            response_curve: ResponseCurve::Quadratic,
+        };
+        assert_close(settings.apply(0.575), 0.25);
+        let settings = AxisSettings {
+            response_curve: ResponseCurve::Power(3.0),
+            ..settings
+        };
+        assert_close(settings.apply(0.575), 0.125);
+        assert_close(settings.apply(1.0), 1.0);
+    }
+}
@@ -71,9 +71,9 @@ pub use self::consts::*;
 #[doc(hidden)]
 pub const NRSHIFT: u32 = 0;
 #[doc(hidden)]
-pub const TYPESHIFT: u32 = NRSHIFT + NRBITS as u32;
+pub const TYPESHIFT: u32 = NRSHIFT + NRBITS;
 #[doc(hidden)]
-pub const SIZESHIFT: u32 = TYPESHIFT + TYPEBITS as u32;
+pub const SIZESHIFT: u32 = TYPESHIFT + TYPEBITS;
 #[doc(hidden)]
 pub const DIRSHIFT: u32 = SIZESHIFT + SIZEBITS as u32;
 
@@ -133,3 +133,15 @@ impl ::std::default::Default for input_absinfo {
 pub const fn eviocgabs(abs: u32) -> ::libc::c_ulong {
     ior(b'E' as _, (0x40 + abs) as _, std::mem::size_of::<input_absinfo>() as _) as ::libc::c_ulong
 }
+
+pub const fn eviocgkey(len: u32) -> ::libc::c_ulong {
+    ior(b'E' as _, 0x18, len as _) as ::libc::c_ulong
+}
+
+pub const fn eviocgbit(ev: u32, len: u32) -> ::libc::c_ulong {
+    ior(b'E' as _, (0x20 + ev) as _, len as _) as ::libc::c_ulong
+}
+
+pub const fn eviocsff(size: usize) -> ::libc::c_ulong {
+    iow(b'E' as _, 0x80, size as _) as ::libc::c_ulong
+}
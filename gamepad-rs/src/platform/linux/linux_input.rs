@@ -18,6 +18,16 @@ pub const EV_FF_STATUS: c_int = 0x17;
 pub const EV_MAX: c_int = 0x1f;
 pub const EV_CNT: c_int = EV_MAX + 1;
 
+pub const FF_RUMBLE: c_int = 0x50;
+pub const FF_PERIODIC: c_int = 0x51;
+pub const FF_MAX: c_int = 0x7f;
+pub const FF_CNT: c_int = FF_MAX + 1;
+
+pub const SYN_REPORT: c_int = 0;
+pub const SYN_CONFIG: c_int = 1;
+pub const SYN_MT_REPORT: c_int = 2;
+pub const SYN_DROPPED: c_int = 3;
+
 pub const KEY_MAX: c_int = 0x2ff;
 pub const KEY_CNT: c_int = KEY_MAX + 1;
 
@@ -100,6 +110,11 @@ pub const BTN_TOOL_DOUBLETAP: c_int = 0x14d;
 pub const BTN_TOOL_TRIPLETAP: c_int = 0x14e;
 pub const BTN_TOOL_QUADTAP: c_int = 0x14f; /* Four fingers on trackpad */
 
+pub const BTN_DPAD_UP: c_int = 0x220;
+pub const BTN_DPAD_DOWN: c_int = 0x221;
+pub const BTN_DPAD_LEFT: c_int = 0x222;
+pub const BTN_DPAD_RIGHT: c_int = 0x223;
+
 pub const BTN_WHEEL: c_int = 0x150;
 pub const BTN_GEAR_DOWN: c_int = 0x150;
 pub const BTN_GEAR_UP: c_int = 0x151;
@@ -1,29 +1,22 @@
 // https://github.com/glfw/glfw/blob/master/src/linux_joystick.c
 
 use super::super::{
-    ControllerInfo, ControllerState, ControllerStatus, DEFAULT_CONTROLLER_INFO,
-    DEFAULT_CONTROLLER_STATE,
+    current_time, Axis, AxisRange, Button, ControllerEvent, ControllerEventKind, ControllerInfo,
+    ControllerState, ControllerStatus, EventQueue, InputId, Mapping, MappingInput,
+    DEFAULT_CONTROLLER_INFO, DEFAULT_CONTROLLER_STATE, MAX_ANALOG, MAX_DEVICES, MAX_DIGITAL,
 };
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 mod ioctl;
 mod linux_input;
 
-use self::ioctl::eviocgabs;
+use self::ioctl::{eviocgabs, eviocgbit, eviocgkey, eviocsff};
 use self::linux_input::*;
 
 fn is_bit_set(bit: usize, arr: &[u8]) -> bool {
-    return (arr[bit / 8] & (1 << ((bit as usize) % 8))) != 0;
-}
-
-#[repr(C)]
-#[derive(Default, Debug)]
-struct InputId {
-    bustype: u16,
-    vendor: u16,
-    product: u16,
-    version: u16,
+    (arr[bit / 8] & (1 << (bit % 8))) != 0
 }
 
 #[repr(C)]
@@ -53,59 +46,243 @@ pub struct InputEvent {
     pub value: i32,
 }
 
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone)]
+struct FfTrigger {
+    button: u16,
+    interval: u16,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone)]
+struct FfReplay {
+    length: u16,
+    delay: u16,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone)]
+struct FfRumbleEffect {
+    strong_magnitude: u16,
+    weak_magnitude: u16,
+}
+
+// Only used for the size and alignment of the effect union
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct FfPeriodicEffect {
+    waveform: u16,
+    period: u16,
+    magnitude: i16,
+    offset: i16,
+    phase: u16,
+    envelope: [u16; 4],
+    custom_len: u32,
+    custom_data: *mut i16,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+union FfEffectData {
+    rumble: FfRumbleEffect,
+    periodic: FfPeriodicEffect,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct FfEffect {
+    type_: u16,
+    id: i16,
+    direction: u16,
+    trigger: FfTrigger,
+    replay: FfReplay,
+    data: FfEffectData,
+}
+
+const INPUT_DIRECTORY: &str = "/dev/input";
+
+// Used when inotify is not available
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
 struct GamePad {
     fd: libc::c_int,
+    path: PathBuf,
     info: ControllerInfo,
     state: ControllerState,
     axis_map: [i32; ABS_CNT as usize],
     axis_info: [InputAbsinfo; ABS_CNT as usize],
-    buttons_map: [usize; (KEY_CNT - BTN_MISC) as usize],
+    buttons_map: [i32; (KEY_CNT - BTN_MISC) as usize],
+    // Set when the kernel reports SYN_DROPPED, until the next SYN_REPORT
+    is_dropping: bool,
+    // The uploaded rumble effect is reused, as devices only hold a few effects
+    rumble_effect_id: Option<i16>,
 }
 
 impl GamePad {
+    /// Queries the current value and range of all axes
     unsafe fn poll_abs_info(&mut self) {
-        for code in &self.axis_map {
-            if *code != -1 {
-                if libc::ioctl(
-                    self.fd,
-                    eviocgabs(*code as _),
-                    &mut self.axis_info[*code as usize],
-                ) < 0
-                {
-                    continue;
-                }
+        for code in 0..ABS_CNT as usize {
+            // An axis that can not be queried keeps its last known value
+            if self.axis_map[code] != -1 {
+                libc::ioctl(self.fd, eviocgabs(code as _), &mut self.axis_info[code]);
             }
         }
     }
 
-    unsafe fn poll(&mut self) {
-        let mut e = InputEvent::default();
+    /// Returns the index of the button, if it is mapped
+    fn set_button(&mut self, code: usize, value: bool) -> Option<usize> {
+        if code < BTN_MISC as usize || code >= KEY_CNT as usize {
+            return None;
+        }
+        let index = self.buttons_map[code - BTN_MISC as usize];
+        if index == -1 {
+            return None;
+        }
+        self.state.digital_state[index as usize] = value;
+        Some(index as usize)
+    }
+
+    /// Returns the index of the axis and its new value, if it is mapped
+    fn set_axis(&mut self, code: usize, value: i32) -> Option<(usize, f32)> {
+        if code >= ABS_CNT as usize || self.axis_map[code] == -1 {
+            return None;
+        }
+        let info = self.axis_info[code];
+        let value = if code >= ABS_HAT0X as usize && code <= ABS_HAT3Y as usize {
+            value as f32
+        } else {
+            ((value as f32 - info.minimum as f32) / (info.maximum as f32 - info.minimum as f32)
+                - 0.5)
+                * 2.
+        };
+        let index = self.axis_map[code] as usize;
+        self.state.analog_state[index] = value;
+        Some((index, value))
+    }
 
-        if libc::read(
+    /// Re-queries the state of all buttons and axes, after the kernel has dropped events
+    unsafe fn resync(&mut self) {
+        let mut key_state = [0u8; (KEY_CNT as usize).div_ceil(8)];
+        if libc::ioctl(
             self.fd,
-            &mut e as *mut _ as *mut _,
-            std::mem::size_of_val(&e),
-        ) < 0
+            eviocgkey(key_state.len() as _),
+            key_state.as_mut_ptr(),
+        ) >= 0
         {
-            // handle disconnect
-            return;
+            for code in BTN_MISC as usize..KEY_CNT as usize {
+                let _ = self.set_button(code, is_bit_set(code, &key_state));
+            }
         }
 
-        if e.type_ == EV_KEY as _ {
-            let code = e.code as usize - BTN_MISC as usize;
-            self.state.digital_state[self.buttons_map[code]] = e.value != 0;
+        self.poll_abs_info();
+        for code in 0..ABS_CNT as usize {
+            let value = self.axis_info[code].value;
+            let _ = self.set_axis(code, value);
         }
-        if e.type_ == EV_ABS as _ {
-            let info = self.axis_info[e.code as usize];
-            let value = if e.code >= ABS_HAT0X as _ && e.code <= ABS_HAT3Y as _ {
-                e.value as f32
-            } else {
-                ((e.value as f32 - info.minimum as f32)
-                    / (info.maximum as f32 - info.minimum as f32)
-                    - 0.5)
-                    * 2.
-            };
-            self.state.analog_state[self.axis_map[e.code as usize] as usize] = value;
+    }
+
+    /// Reads all pending events and queues them as controller events with the given index.
+    /// Returns false if the device has been disconnected
+    unsafe fn poll(&mut self, index: usize, events: &mut EventQueue) -> bool {
+        let mut has_changed = false;
+        loop {
+            let mut e = InputEvent::default();
+
+            let length = libc::read(
+                self.fd,
+                &mut e as *mut _ as *mut _,
+                std::mem::size_of_val(&e),
+            );
+            // A read of zero bytes has nothing more to return, so it ends the loop like EAGAIN
+            if length <= 0 {
+                let error = if length < 0 {
+                    std::io::Error::last_os_error().raw_os_error()
+                } else {
+                    None
+                };
+                if has_changed {
+                    self.state.sequence = self.state.sequence.wrapping_add(1);
+                }
+                return error != Some(libc::ENODEV);
+            }
+            if (length as usize) < std::mem::size_of::<InputEvent>() {
+                continue;
+            }
+
+            let time = Duration::new(e.time.tv_sec as u64, e.time.tv_usec as u32 * 1000);
+            if e.type_ == EV_SYN as _ {
+                if e.code == SYN_DROPPED as _ {
+                    self.is_dropping = true;
+                } else if e.code == SYN_REPORT as _ && self.is_dropping {
+                    self.is_dropping = false;
+                    let previous = self.state.clone();
+                    self.resync();
+                    events.push_changes(index, &previous, &self.state, time);
+                    has_changed = true;
+                }
+            } else if self.is_dropping {
+                // Events are incomplete until the next SYN_REPORT, and the state is queried then
+                continue;
+            } else if e.type_ == EV_KEY as _ {
+                // A value of 2 is a key repeat, which does not change the state
+                if e.value == 2 {
+                    continue;
+                }
+                if let Some(button) = self.set_button(e.code as usize, e.value != 0) {
+                    let kind = if e.value != 0 {
+                        ControllerEventKind::ButtonPressed { button }
+                    } else {
+                        ControllerEventKind::ButtonReleased { button }
+                    };
+                    events.push(index, time, kind);
+                    has_changed = true;
+                }
+            } else if e.type_ == EV_ABS as _ {
+                if let Some((axis, value)) = self.set_axis(e.code as usize, e.value) {
+                    events.push(index, time, ControllerEventKind::AxisMoved { axis, value });
+                    has_changed = true;
+                }
+            }
+        }
+    }
+
+    /// Uploads and plays a rumble effect. Returns false if it is not supported
+    unsafe fn rumble(&mut self, strong_magnitude: u16, weak_magnitude: u16, length: u16) -> bool {
+        if !self.info.has_rumble {
+            return false;
+        }
+
+        let mut effect: FfEffect = std::mem::zeroed();
+        effect.type_ = FF_RUMBLE as _;
+        effect.id = self.rumble_effect_id.unwrap_or(-1);
+        effect.replay.length = length;
+        effect.data.rumble = FfRumbleEffect {
+            strong_magnitude,
+            weak_magnitude,
+        };
+        if libc::ioctl(self.fd, eviocsff(std::mem::size_of::<FfEffect>()), &mut effect) < 0 {
+            return false;
+        }
+        self.rumble_effect_id = Some(effect.id);
+
+        let event = InputEvent {
+            time: TimeVal::default(),
+            type_: EV_FF as _,
+            code: effect.id as _,
+            value: 1,
+        };
+        libc::write(
+            self.fd,
+            &event as *const _ as *const _,
+            std::mem::size_of_val(&event),
+        ) == std::mem::size_of_val(&event) as isize
+    }
+}
+
+impl Drop for GamePad {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
         }
     }
 }
@@ -113,17 +290,23 @@ impl GamePad {
 unsafe fn open_joystick_device(path: PathBuf) -> Option<GamePad> {
     use std::os::unix::ffi::OsStrExt;
 
-    let fd = libc::open(
-        path.as_os_str().as_bytes().as_ptr() as _,
-        libc::O_RDONLY | libc::O_NONBLOCK,
-    );
+    let c_path = match std::ffi::CString::new(path.as_os_str().as_bytes()) {
+        Ok(c_path) => c_path,
+        Err(_) => return None,
+    };
+    // Writing is needed for force feedback, but reading is enough for input
+    let mut fd = libc::open(c_path.as_ptr(), libc::O_RDWR | libc::O_NONBLOCK);
+    let is_writable = fd != -1;
+    if !is_writable {
+        fd = libc::open(c_path.as_ptr(), libc::O_RDONLY | libc::O_NONBLOCK);
+    }
     if fd == -1 {
         return None;
     }
 
-    let mut ev_bits: [u8; (EV_CNT as usize + 7) / 8] = [0; (EV_CNT as usize + 7) / 8];
-    let mut key_bits: [u8; (KEY_CNT as usize + 7) / 8] = [0; (KEY_CNT as usize + 7) / 8];
-    let mut abs_bits: [u8; (ABS_CNT as usize + 7) / 8] = [0; (ABS_CNT as usize + 7) / 8];
+    let mut ev_bits: [u8; (EV_CNT as usize).div_ceil(8)] = [0; (EV_CNT as usize).div_ceil(8)];
+    let mut key_bits: [u8; (KEY_CNT as usize).div_ceil(8)] = [0; (KEY_CNT as usize).div_ceil(8)];
+    let mut abs_bits: [u8; (ABS_CNT as usize).div_ceil(8)] = [0; (ABS_CNT as usize).div_ceil(8)];
 
     // EVIOCGBIT(0, sizeof(evBits))
     let eviocgbit_0: u64 = 2147763488;
@@ -155,6 +338,12 @@ unsafe fn open_joystick_device(path: PathBuf) -> Option<GamePad> {
         return None;
     }
 
+    let mut ff_bits: [u8; (FF_CNT as usize).div_ceil(8)] = [0; (FF_CNT as usize).div_ceil(8)];
+    let has_rumble = is_writable
+        && is_bit_set(EV_FF as _, &ev_bits)
+        && libc::ioctl(fd, eviocgbit(EV_FF as _, ff_bits.len() as _), ff_bits.as_mut_ptr()) >= 0
+        && is_bit_set(FF_RUMBLE as _, &ff_bits);
+
     // Retrieve joystick name
     let mut name: [u8; 256] = [0; 256];
     // EVIOCGNAME(256)
@@ -169,14 +358,15 @@ unsafe fn open_joystick_device(path: PathBuf) -> Option<GamePad> {
     println!("Found gamepad {:?}: {:?}", path, name);
     println!("input_id: {:?}", id);
 
+    // Buttons and axes are numbered like SDL does, so that gamecontrollerdb mappings apply
     let mut digital_count = 0;
-    let mut buttons_map = [0; (KEY_CNT - BTN_MISC) as usize];
-    for code in BTN_MISC..KEY_CNT {
-        if !is_bit_set(code as _, &key_bits) {
+    let mut buttons_map = [-1; (KEY_CNT - BTN_MISC) as usize];
+    for code in (BTN_JOYSTICK..KEY_CNT).chain(BTN_MISC..BTN_JOYSTICK) {
+        if !is_bit_set(code as _, &key_bits) || digital_count >= MAX_DIGITAL {
             continue;
         }
 
-        buttons_map[(code - BTN_MISC) as usize] = digital_count;
+        buttons_map[(code - BTN_MISC) as usize] = digital_count as i32;
         digital_count += 1;
     }
 
@@ -185,90 +375,544 @@ unsafe fn open_joystick_device(path: PathBuf) -> Option<GamePad> {
     let mut axis_info = [InputAbsinfo::default(); ABS_CNT as usize];
 
     for code in 0..ABS_CNT {
-        if !is_bit_set(code as _, &abs_bits) {
+        if !is_bit_set(code as _, &abs_bits)
+            || (ABS_HAT0X..=ABS_HAT3Y).contains(&code)
+            || analog_count >= MAX_ANALOG
+        {
             continue;
         }
 
-        if code >= ABS_HAT0X && code <= ABS_HAT3Y {
-            axis_map[code as usize] = analog_count as i32;
-            analog_count += 1;
-        } else {
-            if libc::ioctl(fd, eviocgabs(code as _), &mut axis_info[code as usize]) < 0 {
-                continue;
-            }
-            axis_map[code as usize] = analog_count as i32;
-            analog_count += 1;
+        if libc::ioctl(fd, eviocgabs(code as _), &mut axis_info[code as usize]) < 0 {
+            continue;
         }
+        axis_map[code as usize] = analog_count as i32;
+        analog_count += 1;
     }
 
+    // Hats are stored as x/y pairs after the other axes
+    let mut hat_count = 0;
+    for hat in 0..4 {
+        let x = ABS_HAT0X + hat * 2;
+        let y = x + 1;
+        if (!is_bit_set(x as _, &abs_bits) && !is_bit_set(y as _, &abs_bits))
+            || analog_count + 2 > MAX_ANALOG
+        {
+            continue;
+        }
+
+        axis_map[x as usize] = analog_count as i32;
+        axis_map[y as usize] = analog_count as i32 + 1;
+        analog_count += 2;
+        hat_count += 1;
+    }
+
+    let mapping = guess_mapping(&name, &buttons_map, &axis_map, hat_count);
+
     let mut gamepad = GamePad {
         fd,
+        path,
         info: ControllerInfo {
             name,
             digital_count,
             analog_count,
+            hat_count,
+            id,
+            has_rumble,
+            mapping,
         },
         state: ControllerState::new(),
         axis_info,
         axis_map,
         buttons_map,
+        is_dropping: false,
+        rumble_effect_id: None,
     };
     gamepad.state.status = ControllerStatus::Connected;
-    gamepad.poll_abs_info();
+    gamepad.resync();
 
     Some(gamepad)
 }
 
-unsafe fn platform_init_joysticks() -> Vec<GamePad> {
-    let dirname = "/dev/input";
+// Follows the evdev gamepad conventions, for controllers without a gamecontrollerdb mapping
+fn guess_mapping(
+    name: &str,
+    buttons_map: &[i32; (KEY_CNT - BTN_MISC) as usize],
+    axis_map: &[i32; ABS_CNT as usize],
+    hat_count: usize,
+) -> Mapping {
+    let mut mapping = Mapping::new(name);
+
+    let buttons = [
+        (BTN_A, Button::A),
+        (BTN_B, Button::B),
+        (BTN_X, Button::X),
+        (BTN_Y, Button::Y),
+        (BTN_SELECT, Button::Back),
+        (BTN_MODE, Button::Guide),
+        (BTN_START, Button::Start),
+        (BTN_THUMBL, Button::LeftStick),
+        (BTN_THUMBR, Button::RightStick),
+        (BTN_TL, Button::LeftShoulder),
+        (BTN_TR, Button::RightShoulder),
+        (BTN_DPAD_UP, Button::DPadUp),
+        (BTN_DPAD_DOWN, Button::DPadDown),
+        (BTN_DPAD_LEFT, Button::DPadLeft),
+        (BTN_DPAD_RIGHT, Button::DPadRight),
+    ];
+    for &(code, button) in buttons.iter() {
+        let index = buttons_map[(code - BTN_MISC) as usize];
+        if index != -1 {
+            mapping.set_button(button, MappingInput::Button(index as usize));
+        }
+    }
+
+    if hat_count > 0 && mapping.buttons[Button::DPadUp as usize].is_none() {
+        mapping.set_button(Button::DPadUp, MappingInput::Hat { index: 0, mask: 1 });
+        mapping.set_button(Button::DPadRight, MappingInput::Hat { index: 0, mask: 2 });
+        mapping.set_button(Button::DPadDown, MappingInput::Hat { index: 0, mask: 4 });
+        mapping.set_button(Button::DPadLeft, MappingInput::Hat { index: 0, mask: 8 });
+    }
+
+    let axes = [
+        (ABS_X, Axis::LeftX),
+        (ABS_Y, Axis::LeftY),
+        (ABS_RX, Axis::RightX),
+        (ABS_RY, Axis::RightY),
+        (ABS_Z, Axis::LeftTrigger),
+        (ABS_RZ, Axis::RightTrigger),
+    ];
+    for &(code, axis) in axes.iter() {
+        let index = axis_map[code as usize];
+        if index != -1 {
+            mapping.set_axis(
+                axis,
+                MappingInput::Axis {
+                    index: index as usize,
+                    range: AxisRange::Full,
+                    is_inverted: false,
+                },
+            );
+        }
+    }
+
+    let triggers = [(BTN_TL2, Axis::LeftTrigger), (BTN_TR2, Axis::RightTrigger)];
+    for &(code, axis) in triggers.iter() {
+        let index = buttons_map[(code - BTN_MISC) as usize];
+        if index != -1 && mapping.axes[axis as usize].is_none() {
+            mapping.set_axis(axis, MappingInput::Button(index as usize));
+        }
+    }
+
+    mapping
+}
+
+fn to_magnitude(value: f32) -> u16 {
+    (value.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+}
 
-    let mut res = vec![];
+fn is_event_device(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|file_name| file_name.to_str())
+        .map(|file_name| file_name.starts_with("event"))
+        .unwrap_or(false)
+}
 
-    for entry in std::fs::read_dir(dirname).unwrap() {
-        let path = entry.unwrap().path();
-        let file_name = path.file_name().unwrap().to_str().unwrap();
+unsafe fn init_inotify() -> Option<libc::c_int> {
+    let fd = libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC);
+    if fd == -1 {
+        return None;
+    }
+    let directory = std::ffi::CString::new(INPUT_DIRECTORY).unwrap();
+    if libc::inotify_add_watch(
+        fd,
+        directory.as_ptr(),
+        libc::IN_CREATE | libc::IN_ATTRIB | libc::IN_DELETE,
+    ) == -1
+    {
+        libc::close(fd);
+        return None;
+    }
+    Some(fd)
+}
 
-        if file_name.starts_with("event") {
-            if let Some(gamepad) = open_joystick_device(path) {
-                res.push(gamepad);
+/// Reads all pending inotify events and returns the paths of the event devices that changed
+unsafe fn read_inotify_events(fd: libc::c_int) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let len = libc::read(fd, buffer.as_mut_ptr() as *mut _, buffer.len());
+        if len <= 0 {
+            break;
+        }
+        let mut offset = 0;
+        while offset + std::mem::size_of::<libc::inotify_event>() <= len as usize {
+            let event = std::ptr::read_unaligned(buffer.as_ptr().add(offset) as *const libc::inotify_event);
+            let name_offset = offset + std::mem::size_of::<libc::inotify_event>();
+            let name = &buffer[name_offset..name_offset + event.len as usize];
+            let name_len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+            if let Ok(name) = std::str::from_utf8(&name[..name_len]) {
+                let path = Path::new(INPUT_DIRECTORY).join(name);
+                if is_event_device(&path) && !paths.contains(&path) {
+                    paths.push(path);
+                }
             }
+            offset = name_offset + event.len as usize;
         }
     }
-    res
+    paths
 }
 
 pub struct ControllerContext {
-    gamepads: Vec<GamePad>,
+    gamepads: Vec<Option<GamePad>>,
+    events: EventQueue,
+    inotify_fd: Option<libc::c_int>,
+    last_scan: Instant,
 }
 
 impl ControllerContext {
     pub fn new() -> Option<Self> {
-        Some(ControllerContext {
-            gamepads: unsafe { platform_init_joysticks() },
-        })
+        let mut context = ControllerContext {
+            gamepads: (0..MAX_DEVICES).map(|_| None).collect(),
+            events: EventQueue::new(),
+            inotify_fd: unsafe { init_inotify() },
+            last_scan: Instant::now(),
+        };
+        context.scan_controllers();
+        Some(context)
+    }
+
+    /// Scan all devices, open any new ones in free slots, and return number of connected controllers
+    pub fn scan_controllers(&mut self) -> usize {
+        self.last_scan = Instant::now();
+        if let Ok(entries) = std::fs::read_dir(INPUT_DIRECTORY) {
+            let mut paths: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| is_event_device(path))
+                .collect();
+            paths.sort();
+            for path in paths {
+                self.try_open(path);
+            }
+        }
+        self.gamepads.iter().filter(|gamepad| gamepad.is_some()).count()
+    }
+
+    fn try_open(&mut self, path: PathBuf) {
+        if self.gamepads.iter().flatten().any(|gamepad| gamepad.path == path) {
+            return;
+        }
+        if let Some(index) = self.gamepads.iter().position(|gamepad| gamepad.is_none()) {
+            if let Some(gamepad) = unsafe { open_joystick_device(path) } {
+                self.gamepads[index] = Some(gamepad);
+                self.events.push(index, current_time(), ControllerEventKind::Connected);
+            }
+        }
+    }
+
+    fn disconnect(&mut self, index: usize) {
+        if self.gamepads[index].take().is_some() {
+            self.events.push(index, current_time(), ControllerEventKind::Disconnected);
+        }
+    }
+
+    fn check_hotplug(&mut self) {
+        match self.inotify_fd {
+            Some(fd) => {
+                for path in unsafe { read_inotify_events(fd) } {
+                    if path.exists() {
+                        self.try_open(path);
+                    } else if let Some(index) = self.gamepads.iter().position(|gamepad| {
+                        gamepad.as_ref().map(|gamepad| gamepad.path == path).unwrap_or(false)
+                    }) {
+                        self.disconnect(index);
+                    }
+                }
+            }
+            None => {
+                if self.last_scan.elapsed() >= RESCAN_INTERVAL {
+                    self.scan_controllers();
+                }
+            }
+        }
     }
 
     /// Update controller state by index
     pub fn update(&mut self, index: usize) {
-        if let Some(ref mut gamepad) = self.gamepads.get_mut(index) {
-            unsafe {
-                gamepad.poll();
-            }
+        self.check_hotplug();
+        self.events.clear_edges(index);
+        let is_connected = match self.gamepads.get_mut(index) {
+            Some(Some(gamepad)) => unsafe { gamepad.poll(index, &mut self.events) },
+            _ => return,
+        };
+        if !is_connected {
+            self.disconnect(index);
         }
     }
 
+    /// Rumbles the controller for the given duration. Magnitudes range from 0.0 to 1.0,
+    /// and the strong motor is the low frequency one. Returns false if rumble is not supported
+    pub fn rumble(
+        &mut self,
+        index: usize,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+        duration: Duration,
+    ) -> bool {
+        let length = duration.as_millis().min(u16::MAX as u128) as u16;
+        match self.gamepads.get_mut(index) {
+            Some(Some(gamepad)) => unsafe {
+                gamepad.rumble(to_magnitude(strong_magnitude), to_magnitude(weak_magnitude), length)
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns true if the button was pressed during the last update of the controller
+    pub fn is_pressed(&self, index: usize, button: usize) -> bool {
+        self.events.is_pressed(index, button)
+    }
+
+    /// Returns true if the button was released during the last update of the controller
+    pub fn is_released(&self, index: usize, button: usize) -> bool {
+        self.events.is_released(index, button)
+    }
+
+    /// Returns the next queued event, if any. Button and axis events are queued by `update`
+    pub fn poll_event(&mut self) -> Option<ControllerEvent> {
+        self.check_hotplug();
+        self.events.pop()
+    }
+
     pub fn info(&self, index: usize) -> &ControllerInfo {
-        if let Some(ref gamepad) = self.gamepads.get(index) {
+        if let Some(Some(ref gamepad)) = self.gamepads.get(index) {
             &gamepad.info
         } else {
-            &*DEFAULT_CONTROLLER_INFO
+            &DEFAULT_CONTROLLER_INFO
         }
     }
     pub fn state(&self, index: usize) -> &ControllerState {
-        if let Some(ref gamepad) = self.gamepads.get(index) {
+        if let Some(Some(ref gamepad)) = self.gamepads.get(index) {
             &gamepad.state
         } else {
             &DEFAULT_CONTROLLER_STATE
         }
     }
 }
+
+impl Drop for ControllerContext {
+    fn drop(&mut self) {
+        if let Some(fd) = self.inotify_fd {
+            unsafe {
+                libc::close(fd);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ioctl::{io, ior, iorw, iow};
+    use super::*;
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    const EV_UINPUT: u16 = 0x0101;
+    const UI_FF_UPLOAD: u16 = 1;
+    const UI_FF_ERASE: u16 = 2;
+
+    #[repr(C)]
+    struct UinputUserDev {
+        name: [u8; 80],
+        id: InputId,
+        ff_effects_max: u32,
+        absmax: [i32; ABS_CNT as usize],
+        absmin: [i32; ABS_CNT as usize],
+        absfuzz: [i32; ABS_CNT as usize],
+        absflat: [i32; ABS_CNT as usize],
+    }
+
+    #[repr(C)]
+    struct UinputFfUpload {
+        request_id: u32,
+        retval: i32,
+        effect: FfEffect,
+        old: FfEffect,
+    }
+
+    #[repr(C)]
+    struct UinputFfErase {
+        request_id: u32,
+        retval: i32,
+        effect_id: u32,
+    }
+
+    fn ui_ioctl(nr: u64) -> libc::c_ulong {
+        io(b'U' as _, nr) as _
+    }
+
+    fn ui_ioctl_write(nr: u64, size: usize) -> libc::c_ulong {
+        iow(b'U' as _, nr, size as _) as _
+    }
+
+    /// A virtual gamepad with one button, one axis and rumble support, that accepts effect
+    /// uploads and records played effects on a background thread until it is dropped
+    struct VirtualGamePad {
+        fd: libc::c_int,
+        is_stopped: Arc<AtomicBool>,
+        has_played: Arc<AtomicBool>,
+        handler: Option<thread::JoinHandle<()>>,
+    }
+
+    impl VirtualGamePad {
+        unsafe fn create() -> Option<Self> {
+            let path = std::ffi::CString::new("/dev/uinput").unwrap();
+            let fd = libc::open(path.as_ptr(), libc::O_RDWR | libc::O_NONBLOCK);
+            if fd == -1 {
+                return None;
+            }
+            let set_bit = |nr: u64, bit: libc::c_int| libc::ioctl(fd, ui_ioctl_write(nr, 4), bit) >= 0;
+            let mut device: UinputUserDev = std::mem::zeroed();
+            device.name[..16].copy_from_slice(b"gamepad-rs-test\0");
+            device.id = InputId {
+                bustype: 0x06,
+                vendor: 0x1234,
+                product: 0x5678,
+                version: 1,
+            };
+            device.ff_effects_max = 1;
+            device.absmin[ABS_X as usize] = -32768;
+            device.absmax[ABS_X as usize] = 32767;
+            let is_created = set_bit(100, EV_KEY)
+                && set_bit(100, EV_ABS)
+                && set_bit(100, EV_FF)
+                && set_bit(101, BTN_SOUTH)
+                && set_bit(103, ABS_X)
+                && set_bit(107, FF_RUMBLE)
+                && libc::write(
+                    fd,
+                    &device as *const _ as *const _,
+                    std::mem::size_of::<UinputUserDev>(),
+                ) == std::mem::size_of::<UinputUserDev>() as isize
+                && libc::ioctl(fd, ui_ioctl(1)) >= 0;
+            if !is_created {
+                libc::close(fd);
+                return None;
+            }
+            let is_stopped = Arc::new(AtomicBool::new(false));
+            let has_played = Arc::new(AtomicBool::new(false));
+            let handler = {
+                let is_stopped = is_stopped.clone();
+                let has_played = has_played.clone();
+                thread::spawn(move || Self::handle_requests(fd, &is_stopped, &has_played))
+            };
+            Some(VirtualGamePad {
+                fd,
+                is_stopped,
+                has_played,
+                handler: Some(handler),
+            })
+        }
+
+        // Uploads and erases block the client until they are answered
+        fn handle_requests(fd: libc::c_int, is_stopped: &AtomicBool, has_played: &AtomicBool) {
+            while !is_stopped.load(Ordering::SeqCst) {
+                let mut e = InputEvent::default();
+                let length = unsafe {
+                    libc::read(fd, &mut e as *mut _ as *mut _, std::mem::size_of_val(&e))
+                };
+                if length < std::mem::size_of_val(&e) as isize {
+                    thread::sleep(Duration::from_millis(1));
+                    continue;
+                }
+                if e.type_ == EV_UINPUT && e.code == UI_FF_UPLOAD {
+                    unsafe {
+                        let mut upload: UinputFfUpload = std::mem::zeroed();
+                        upload.request_id = e.value as u32;
+                        let size = std::mem::size_of::<UinputFfUpload>();
+                        if libc::ioctl(fd, iorw(b'U' as _, 200, size as _) as _, &mut upload) >= 0 {
+                            upload.retval = 0;
+                            libc::ioctl(fd, ui_ioctl_write(201, size), &upload);
+                        }
+                    }
+                } else if e.type_ == EV_UINPUT && e.code == UI_FF_ERASE {
+                    unsafe {
+                        let mut erase: UinputFfErase = std::mem::zeroed();
+                        erase.request_id = e.value as u32;
+                        let size = std::mem::size_of::<UinputFfErase>();
+                        if libc::ioctl(fd, iorw(b'U' as _, 202, size as _) as _, &mut erase) >= 0 {
+                            erase.retval = 0;
+                            libc::ioctl(fd, ui_ioctl_write(203, size), &erase);
+                        }
+                    }
+                } else if e.type_ == EV_FF as u16 && e.value == 1 {
+                    has_played.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+
+        /// Returns the event device of the virtual gamepad, once udev has created it
+        unsafe fn get_event_path(&self) -> Option<PathBuf> {
+            let mut sysname = [0u8; 64];
+            let request = ior(b'U' as _, 44, sysname.len() as _) as libc::c_ulong;
+            if libc::ioctl(self.fd, request, sysname.as_mut_ptr()) < 0 {
+                return None;
+            }
+            let len = sysname.iter().position(|c| *c == 0).unwrap_or(sysname.len());
+            let sysname = std::str::from_utf8(&sysname[..len]).ok()?;
+            let directory = Path::new("/sys/class/input").join(sysname);
+            for _ in 0..200 {
+                let path = std::fs::read_dir(&directory)
+                    .ok()?
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| Path::new(INPUT_DIRECTORY).join(entry.file_name()))
+                    .find(|path| is_event_device(path) && path.exists());
+                if path.is_some() {
+                    return path;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            None
+        }
+    }
+
+    impl Drop for VirtualGamePad {
+        fn drop(&mut self) {
+            self.is_stopped.store(true, Ordering::SeqCst);
+            if let Some(handler) = self.handler.take() {
+                let _ = handler.join();
+            }
+            unsafe {
+                libc::ioctl(self.fd, ui_ioctl(2));
+                libc::close(self.fd);
+            }
+        }
+    }
+
+    // Needs write access to /dev/uinput and the event devices, so it is not run by default
+    #[test]
+    #[ignore]
+    fn rumble_virtual_gamepad() {
+        unsafe {
+            let device = VirtualGamePad::create().expect("Unable to create uinput device");
+            let path = device.get_event_path().expect("Unable to find event device of uinput device");
+            let mut gamepad = open_joystick_device(path).expect("Unable to open uinput device");
+            assert!(gamepad.info.has_rumble);
+            assert!(gamepad.rumble(u16::MAX, u16::MAX / 2, 100));
+            assert!(gamepad.rumble_effect_id.is_some());
+            // Uploading again reuses the effect
+            assert!(gamepad.rumble(u16::MAX / 2, 0, 100));
+            for _ in 0..100 {
+                if device.has_played.load(Ordering::SeqCst) {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            assert!(device.has_played.load(Ordering::SeqCst));
+            // The gamepad has to be closed while the requests are handled, as it erases its effects
+            drop(gamepad);
+        }
+    }
+}
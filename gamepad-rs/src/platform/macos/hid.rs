@@ -53,6 +53,16 @@ mod io_kit_const {
         "Manufacturer"
     }
 
+    pub fn kIOHIDVendorIDKey() -> &'static str {
+        "VendorID"
+    }
+    pub fn kIOHIDProductIDKey() -> &'static str {
+        "ProductID"
+    }
+    pub fn kIOHIDVersionNumberKey() -> &'static str {
+        "VersionNumber"
+    }
+
     pub fn kIOHIDDeviceUsageKey() -> &'static str {
         "DeviceUsage"
     }
@@ -205,6 +215,9 @@ pub struct Device {
     device: IOHIDDeviceRef,
 
     pub product: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub version: u16,
     pub axes: Vec<HIDElement>,
     pub hats: Vec<HIDElement>,
     pub buttons: Vec<HIDElement>,
@@ -435,6 +448,9 @@ impl Device {
                 usage,
                 page,
                 product,
+                vendor_id: get_property_i32(dev, kIOHIDVendorIDKey()).unwrap_or(0) as u16,
+                product_id: get_property_i32(dev, kIOHIDProductIDKey()).unwrap_or(0) as u16,
+                version: get_property_i32(dev, kIOHIDVersionNumberKey()).unwrap_or(0) as u16,
                 device: dev,
                 hats: Vec::new(),
                 axes: Vec::new(),
@@ -1,12 +1,17 @@
 mod hid;
 
-use super::super::{ControllerInfo, ControllerState, ControllerStatus, DEFAULT_CONTROLLER_INFO,
-                   DEFAULT_CONTROLLER_STATE, MAX_ANALOG, MAX_DEVICES, MAX_DIGITAL};
+use std::time::Duration;
+
+use super::super::{current_time, Axis, AxisRange, Button, ControllerEvent, ControllerEventKind,
+                   ControllerInfo, ControllerState, ControllerStatus, EventQueue, InputId, Mapping,
+                   MappingInput, DEFAULT_CONTROLLER_INFO, DEFAULT_CONTROLLER_STATE, MAX_ANALOG,
+                   MAX_DEVICES, MAX_DIGITAL};
 
 pub struct ControllerContext {
     info: Vec<ControllerInfo>,
     state: Vec<ControllerState>,
     hid: hid::HID,
+    events: EventQueue,
 }
 
 // Helper function for convert Vec to array
@@ -38,7 +43,12 @@ impl ControllerContext {
         let hid = hid::HID::new();
 
         match hid {
-            Ok(hid) => Some(Self { info, state, hid }),
+            Ok(hid) => Some(Self {
+                info,
+                state,
+                hid,
+                events: EventQueue::new(),
+            }),
             Err(err) => {
                 println!("Error on create HID. reason: {:?}", err);
                 None
@@ -49,54 +59,111 @@ impl ControllerContext {
         self.hid.detect_devices();
 
         let ndev = self.hid.num_devices();
-        let state = self.hid.hid_state();
-        let devices = state.devices.borrow();
+        let mut statuses = Vec::new();
+        {
+            let state = self.hid.hid_state();
+            let devices = state.devices.borrow();
 
-        for (i, dev) in devices.iter().enumerate() {
-            if i >= MAX_DEVICES {
-                break;
-            }
+            for (i, dev) in devices.iter().enumerate() {
+                if i >= MAX_DEVICES {
+                    break;
+                }
 
-            if let Some(d) = dev.upgrade() {
-                let d = d.borrow();
+                if let Some(d) = dev.upgrade() {
+                    let d = d.borrow();
 
-                self.info[i] = ControllerInfo {
-                    name: d.product.clone(),
-                    analog_count: d.axes.len(),
-                    digital_count: d.buttons.len(),
-                };
-                self.state[i].status = ControllerStatus::Connected;
-            } else {
-                self.info[i] = ControllerInfo::new();
-                self.state[i].status = ControllerStatus::Disconnected;
+                    self.info[i] = ControllerInfo {
+                        name: d.product.clone(),
+                        analog_count: d.axes.len(),
+                        digital_count: d.buttons.len(),
+                        hat_count: 0,
+                        // Bus type is assumed to be USB
+                        id: InputId {
+                            bustype: 0x03,
+                            vendor: d.vendor_id,
+                            product: d.product_id,
+                            version: d.version,
+                        },
+                        has_rumble: false,
+                        mapping: guess_mapping(&d.product),
+                    };
+                    statuses.push(ControllerStatus::Connected);
+                } else {
+                    self.info[i] = ControllerInfo::new();
+                    statuses.push(ControllerStatus::Disconnected);
+                }
             }
         }
+        for (i, status) in statuses.into_iter().enumerate() {
+            self.set_status(i, status);
+        }
 
         ndev
     }
+    /// Force feedback is not implemented for the HID backend, so this always returns false
+    pub fn rumble(
+        &mut self,
+        _index: usize,
+        _strong_magnitude: f32,
+        _weak_magnitude: f32,
+        _duration: Duration,
+    ) -> bool {
+        false
+    }
+    /// Returns true if the button was pressed during the last update of the controller
+    pub fn is_pressed(&self, index: usize, button: usize) -> bool {
+        self.events.is_pressed(index, button)
+    }
+    /// Returns true if the button was released during the last update of the controller
+    pub fn is_released(&self, index: usize, button: usize) -> bool {
+        self.events.is_released(index, button)
+    }
+    /// Returns the next queued event, if any. Button and axis events are queued by `update`
+    pub fn poll_event(&mut self) -> Option<ControllerEvent> {
+        self.events.pop()
+    }
+    fn set_status(&mut self, index: usize, status: ControllerStatus) {
+        if self.state[index].status != status {
+            self.state[index].status = status;
+            let kind = match status {
+                ControllerStatus::Connected => ControllerEventKind::Connected,
+                ControllerStatus::Disconnected => ControllerEventKind::Disconnected,
+            };
+            self.events.push(index, current_time(), kind);
+        }
+    }
     /// Update controller state by index
     pub fn update(&mut self, index: usize) {
+        self.events.clear_edges(index);
         self.hid.update(index);
-        let state = self.hid.hid_state();
-        let devices = state.devices.borrow();
+        let status = {
+            let state = self.hid.hid_state();
+            let devices = state.devices.borrow();
 
-        if index >= devices.len() || index >= MAX_DEVICES {
-            return;
-        }
+            if index >= devices.len() || index >= MAX_DEVICES {
+                return;
+            }
 
-        let dev = &devices[index];
+            let dev = &devices[index];
 
-        if let Some(d) = dev.upgrade() {
-            let dev_bor = d.borrow();
-            self.state[index] = ControllerState {
-                status: ControllerStatus::Connected,
-                sequence: dev_bor.state.sequence,
-                analog_state: to_analog_state_array(&dev_bor.state.analog_state),
-                digital_state: to_digital_state_array(&dev_bor.state.digital_state),
+            if let Some(d) = dev.upgrade() {
+                let dev_bor = d.borrow();
+                let previous = self.state[index].clone();
+                self.state[index] = ControllerState {
+                    status: self.state[index].status,
+                    sequence: dev_bor.state.sequence,
+                    analog_state: to_analog_state_array(&dev_bor.state.analog_state),
+                    digital_state: to_digital_state_array(&dev_bor.state.digital_state),
+                };
+                // HID values are read without timestamps, so changes are queued with the time of the update
+                self.events
+                    .push_changes(index, &previous, &self.state[index], current_time());
+                ControllerStatus::Connected
+            } else {
+                ControllerStatus::Disconnected
             }
-        } else {
-            self.state[index].status = ControllerStatus::Disconnected;
-        }
+        };
+        self.set_status(index, status);
     }
     /// Get current information of Controller
     pub fn info(&self, index: usize) -> &ControllerInfo {
@@ -115,3 +182,47 @@ impl ControllerContext {
         }
     }
 }
+
+// Elements are sorted by usage, which gives this layout for Xbox 360 style controllers
+fn guess_mapping(name: &str) -> Mapping {
+    let mut mapping = Mapping::new(name);
+    let buttons = [
+        Button::A,
+        Button::B,
+        Button::X,
+        Button::Y,
+        Button::LeftShoulder,
+        Button::RightShoulder,
+        Button::LeftStick,
+        Button::RightStick,
+        Button::Start,
+        Button::Back,
+        Button::Guide,
+        Button::DPadUp,
+        Button::DPadDown,
+        Button::DPadLeft,
+        Button::DPadRight,
+    ];
+    for (index, button) in buttons.iter().enumerate() {
+        mapping.set_button(*button, MappingInput::Button(index));
+    }
+    let axes = [
+        Axis::LeftX,
+        Axis::LeftY,
+        Axis::LeftTrigger,
+        Axis::RightX,
+        Axis::RightY,
+        Axis::RightTrigger,
+    ];
+    for (index, axis) in axes.iter().enumerate() {
+        mapping.set_axis(
+            *axis,
+            MappingInput::Axis {
+                index,
+                range: AxisRange::Full,
+                is_inverted: false,
+            },
+        );
+    }
+    mapping
+}
@@ -1,13 +1,13 @@
-pub use self::platform::*;
+pub use self::os::*;
 
 #[cfg(target_os = "linux")]
 #[path = "linux/mod.rs"]
-mod platform;
+mod os;
 
 #[cfg(target_os = "windows")]
 #[path = "windows/mod.rs"]
-mod platform;
+mod os;
 
 #[cfg(target_os = "macos")]
 #[path = "macos/mod.rs"]
-mod platform;
+mod os;
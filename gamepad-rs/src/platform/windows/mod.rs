@@ -2,6 +2,7 @@ use std::mem;
 
 use winapi::shared::winerror::ERROR_SUCCESS;
 use winapi::um::xinput::{self, XINPUT_CAPABILITIES as XCapabilities, XINPUT_STATE as XState,
+                         XINPUT_VIBRATION as XVibration,
                          XINPUT_FLAG_GAMEPAD, XINPUT_GAMEPAD_A, XINPUT_GAMEPAD_B,
                          XINPUT_GAMEPAD_BACK, XINPUT_GAMEPAD_DPAD_DOWN, XINPUT_GAMEPAD_DPAD_LEFT,
                          XINPUT_GAMEPAD_DPAD_RIGHT, XINPUT_GAMEPAD_DPAD_UP,
@@ -9,13 +10,20 @@ use winapi::um::xinput::{self, XINPUT_CAPABILITIES as XCapabilities, XINPUT_STAT
                          XINPUT_GAMEPAD_RIGHT_SHOULDER, XINPUT_GAMEPAD_RIGHT_THUMB,
                          XINPUT_GAMEPAD_START, XINPUT_GAMEPAD_X, XINPUT_GAMEPAD_Y};
 
-use super::super::{ControllerInfo, ControllerState, ControllerStatus, DEFAULT_CONTROLLER_INFO,
-                   DEFAULT_CONTROLLER_STATE, MAX_DEVICES, MAX_DIGITAL};
+use std::time::{Duration, Instant};
+
+use super::super::{current_time, Axis, AxisRange, Button, ControllerEvent, ControllerEventKind,
+                   ControllerInfo, ControllerState, ControllerStatus, EventQueue, Mapping,
+                   MappingInput, DEFAULT_CONTROLLER_INFO, DEFAULT_CONTROLLER_STATE, MAX_DEVICES,
+                   MAX_DIGITAL};
 
 pub struct ControllerContext {
     info: Vec<ControllerInfo>,
     state: Vec<ControllerState>,
     buttons: Vec<[u16; MAX_DIGITAL]>,
+    events: EventQueue,
+    // XInput has no effect durations, so rumble is stopped by `update` once this has passed
+    rumble_end: Vec<Option<Instant>>,
 }
 
 impl ControllerContext {
@@ -33,6 +41,8 @@ impl ControllerContext {
             info,
             state,
             buttons,
+            events: EventQueue::new(),
+            rumble_end: vec![None; MAX_DEVICES],
         })
     }
     /// Scan all device and return number of valid controllers
@@ -43,22 +53,78 @@ impl ControllerContext {
             let val = unsafe { xinput::XInputGetState(id as u32, &mut state) };
             if val == ERROR_SUCCESS {
                 count += 1;
-                self.state[id].status = ControllerStatus::Connected;
+                self.set_status(id, ControllerStatus::Connected);
             } else {
-                self.state[id].status = ControllerStatus::Disconnected;
+                self.set_status(id, ControllerStatus::Disconnected);
             }
         }
         count
     }
     /// Update controller state by index
     pub fn update(&mut self, index: usize) {
+        self.events.clear_edges(index);
         let mut state = unsafe { mem::zeroed::<XState>() };
         let val = unsafe { xinput::XInputGetState(index as u32, &mut state) };
         if val == ERROR_SUCCESS {
-            self.state[index].status = ControllerStatus::Connected;
+            self.set_status(index, ControllerStatus::Connected);
             self.update_state(index, &state);
+            if let Some(end) = self.rumble_end[index] {
+                if Instant::now() >= end {
+                    self.set_vibration(index, 0, 0);
+                }
+            }
+        } else {
+            self.set_status(index, ControllerStatus::Disconnected);
+        }
+    }
+    /// Rumbles the controller for the given duration. Magnitudes range from 0.0 to 1.0,
+    /// and the strong motor is the low frequency one. Returns false if rumble is not supported
+    pub fn rumble(
+        &mut self,
+        index: usize,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+        duration: Duration,
+    ) -> bool {
+        if index >= MAX_DEVICES || !self.info[index].has_rumble {
+            return false;
+        }
+        let to_speed = |value: f32| (value.max(0.0).min(1.0) * u16::max_value() as f32) as u16;
+        if self.set_vibration(index, to_speed(strong_magnitude), to_speed(weak_magnitude)) {
+            self.rumble_end[index] = Some(Instant::now() + duration);
+            true
         } else {
-            self.state[index].status = ControllerStatus::Disconnected;
+            false
+        }
+    }
+    fn set_vibration(&mut self, index: usize, left_motor_speed: u16, right_motor_speed: u16) -> bool {
+        self.rumble_end[index] = None;
+        let mut vibration = XVibration {
+            wLeftMotorSpeed: left_motor_speed,
+            wRightMotorSpeed: right_motor_speed,
+        };
+        unsafe { xinput::XInputSetState(index as u32, &mut vibration) == ERROR_SUCCESS }
+    }
+    /// Returns true if the button was pressed during the last update of the controller
+    pub fn is_pressed(&self, index: usize, button: usize) -> bool {
+        self.events.is_pressed(index, button)
+    }
+    /// Returns true if the button was released during the last update of the controller
+    pub fn is_released(&self, index: usize, button: usize) -> bool {
+        self.events.is_released(index, button)
+    }
+    /// Returns the next queued event, if any. Button and axis events are queued by `update`
+    pub fn poll_event(&mut self) -> Option<ControllerEvent> {
+        self.events.pop()
+    }
+    fn set_status(&mut self, index: usize, status: ControllerStatus) {
+        if self.state[index].status != status {
+            self.state[index].status = status;
+            let kind = match status {
+                ControllerStatus::Connected => ControllerEventKind::Connected,
+                ControllerStatus::Disconnected => ControllerEventKind::Disconnected,
+            };
+            self.events.push(index, current_time(), kind);
         }
     }
     /// Get current information of Controller
@@ -82,6 +148,8 @@ impl ControllerContext {
             // no change in state
             return;
         }
+        // XInput has no event timestamps, so changes are queued with the time of the update
+        let previous = self.state[index].clone();
         self.state[index].sequence = state.dwPacketNumber as usize;
         if self.info[index].digital_count == 0 {
             // we did not yet get the capabilities
@@ -107,6 +175,8 @@ impl ControllerContext {
             (state.Gamepad.sThumbRX as i32 + 32768) as f32 / 65535.0 * 2.0 - 1.0;
         self.state[index].analog_state[5] =
             (state.Gamepad.sThumbRY as i32 + 32768) as f32 / 65535.0 * 2.0 - 1.0;
+        self.events
+            .push_changes(index, &previous, &self.state[index], current_time());
     }
     fn update_info(&mut self, index: usize, capabilities: &XCapabilities) {
         let mut name = String::from("XBOX360");
@@ -121,63 +191,15 @@ impl ControllerContext {
             _ => (),
         };
         name.push_str(" controller");
+        self.info[index].mapping = xinput_mapping(&name, capabilities.Gamepad.wButtons);
+        self.info[index].has_rumble = true;
         self.info[index].name = name;
         let mut buttons = 0;
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_A != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_A;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_B != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_B;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_X != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_X;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_Y != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_Y;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_DPAD_UP != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_DPAD_UP;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_DPAD_DOWN != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_DPAD_DOWN;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_DPAD_LEFT != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_DPAD_LEFT;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_DPAD_RIGHT != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_DPAD_RIGHT;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_START != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_START;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_BACK != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_BACK;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_LEFT_THUMB != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_LEFT_THUMB;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_RIGHT_THUMB != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_RIGHT_THUMB;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_LEFT_SHOULDER != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_LEFT_SHOULDER;
-            buttons += 1;
-        }
-        if capabilities.Gamepad.wButtons & XINPUT_GAMEPAD_RIGHT_SHOULDER != 0 {
-            self.buttons[index][buttons] = XINPUT_GAMEPAD_RIGHT_SHOULDER;
-            buttons += 1;
+        for &(flag, _) in XINPUT_BUTTONS.iter() {
+            if capabilities.Gamepad.wButtons & flag != 0 {
+                self.buttons[index][buttons] = flag;
+                buttons += 1;
+            }
         }
         self.info[index].digital_count = buttons;
         let mut axis = 0;
@@ -202,3 +224,80 @@ impl ControllerContext {
         self.info[index].analog_count = axis;
     }
 }
+
+// The XInput buttons, in the order that `update_info` packs the buttons a controller reports into raw indices
+const XINPUT_BUTTONS: [(u16, Button); 14] = [
+    (XINPUT_GAMEPAD_A, Button::A),
+    (XINPUT_GAMEPAD_B, Button::B),
+    (XINPUT_GAMEPAD_X, Button::X),
+    (XINPUT_GAMEPAD_Y, Button::Y),
+    (XINPUT_GAMEPAD_DPAD_UP, Button::DPadUp),
+    (XINPUT_GAMEPAD_DPAD_DOWN, Button::DPadDown),
+    (XINPUT_GAMEPAD_DPAD_LEFT, Button::DPadLeft),
+    (XINPUT_GAMEPAD_DPAD_RIGHT, Button::DPadRight),
+    (XINPUT_GAMEPAD_START, Button::Start),
+    (XINPUT_GAMEPAD_BACK, Button::Back),
+    (XINPUT_GAMEPAD_LEFT_THUMB, Button::LeftStick),
+    (XINPUT_GAMEPAD_RIGHT_THUMB, Button::RightStick),
+    (XINPUT_GAMEPAD_LEFT_SHOULDER, Button::LeftShoulder),
+    (XINPUT_GAMEPAD_RIGHT_SHOULDER, Button::RightShoulder),
+];
+
+// The raw layout of the XInput backend, where only the buttons in `button_mask` have raw indices
+fn xinput_mapping(name: &str, button_mask: u16) -> Mapping {
+    let mut mapping = Mapping::new(name);
+    let buttons = XINPUT_BUTTONS
+        .iter()
+        .filter(|&&(flag, _)| button_mask & flag != 0)
+        .map(|&(_, button)| button);
+    for (index, button) in buttons.enumerate() {
+        mapping.set_button(button, MappingInput::Button(index));
+    }
+    // XInput stick y axes are positive upwards
+    let axes = [
+        (Axis::LeftX, false),
+        (Axis::LeftY, true),
+        (Axis::LeftTrigger, false),
+        (Axis::RightTrigger, false),
+        (Axis::RightX, false),
+        (Axis::RightY, true),
+    ];
+    for (index, &(axis, is_inverted)) in axes.iter().enumerate() {
+        mapping.set_axis(
+            axis,
+            MappingInput::Axis {
+                index,
+                range: AxisRange::Full,
+                is_inverted,
+            },
+        );
+    }
+    mapping
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapping_skips_missing_buttons() {
+        let all_buttons = XINPUT_BUTTONS.iter().fold(0, |mask, &(flag, _)| mask | flag);
+        let mapping = xinput_mapping("full", all_buttons);
+        for (index, &(_, button)) in XINPUT_BUTTONS.iter().enumerate() {
+            assert_eq!(mapping.buttons[button as usize], Some(MappingInput::Button(index)));
+        }
+
+        // A pad without face buttons X and Y, or a back button
+        let mask = all_buttons & !(XINPUT_GAMEPAD_X | XINPUT_GAMEPAD_Y | XINPUT_GAMEPAD_BACK);
+        let mapping = xinput_mapping("partial", mask);
+        assert_eq!(mapping.buttons[Button::A as usize], Some(MappingInput::Button(0)));
+        assert_eq!(mapping.buttons[Button::B as usize], Some(MappingInput::Button(1)));
+        assert_eq!(mapping.buttons[Button::X as usize], None);
+        assert_eq!(mapping.buttons[Button::Y as usize], None);
+        assert_eq!(mapping.buttons[Button::DPadUp as usize], Some(MappingInput::Button(2)));
+        assert_eq!(mapping.buttons[Button::Start as usize], Some(MappingInput::Button(6)));
+        assert_eq!(mapping.buttons[Button::Back as usize], None);
+        assert_eq!(mapping.buttons[Button::LeftStick as usize], Some(MappingInput::Button(7)));
+        assert_eq!(mapping.buttons[Button::RightShoulder as usize], Some(MappingInput::Button(10)));
+    }
+}
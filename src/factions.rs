@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::{
+    get_global,
+    Resources,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FactionStance {
+    Allied,
+    Neutral,
+    Hostile,
+}
+
+impl FactionStance {
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            FactionStance::Allied => "Allied",
+            FactionStance::Neutral => "Neutral",
+            FactionStance::Hostile => "Hostile",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FactionStanceParams {
+    pub faction: String,
+    pub stance: FactionStance,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FactionParams {
+    pub id: String,
+    pub name: String,
+    /// The stances of this faction towards other factions. Stances only have to be defined on one of the two factions
+    pub stances: Option<Vec<FactionStanceParams>>,
+    /// The reputation that the player starts out with
+    pub reputation: Option<i32>,
+    /// The reputation that the player loses when killing a member of the faction
+    pub kill_penalty: Option<i32>,
+}
+
+/// A change to the reputation of the player with a faction, used by quest rewards and dialogue
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReputationParams {
+    pub faction: String,
+    pub amount: i32,
+}
+
+/// The reputation of the player with each faction. The stance between the player faction and other factions is
+/// decided by reputation, while the stances between all other factions are defined in the factions file.
+#[derive(Clone, Default)]
+pub struct Factions {
+    reputation: HashMap<String, i32>,
+}
+
+impl Factions {
+    pub const PLAYER_FACTION: &'static str = "player_faction";
+
+    pub const MIN_REPUTATION: i32 = -100;
+    pub const MAX_REPUTATION: i32 = 100;
+
+    pub const ALLIED_REPUTATION: i32 = 50;
+    pub const HOSTILE_REPUTATION: i32 = -50;
+
+    const DEFAULT_KILL_PENALTY: i32 = 10;
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn from_reputation(reputation: &[ReputationParams]) -> Self {
+        Factions {
+            reputation: reputation
+                .iter()
+                .map(|params| (params.faction.clone(), params.amount))
+                .collect(),
+        }
+    }
+
+    /// Returns the reputation of the player with all factions that it has changed with
+    pub fn to_reputation_params(&self) -> Vec<ReputationParams> {
+        self.reputation
+            .iter()
+            .map(|(faction, amount)| ReputationParams {
+                faction: faction.clone(),
+                amount: *amount,
+            })
+            .collect()
+    }
+
+    pub fn get_reputation(&self, faction: &str) -> i32 {
+        match self.reputation.get(faction) {
+            Some(reputation) => *reputation,
+            None => {
+                let resources = get_global::<Resources>();
+                resources.try_get_faction(faction)
+                    .and_then(|params| params.reputation)
+                    .unwrap_or(0)
+            }
+        }
+    }
+
+    pub fn add_reputation(&mut self, faction: &str, amount: i32) {
+        let reputation = (self.get_reputation(faction) + amount)
+            .clamp(Self::MIN_REPUTATION, Self::MAX_REPUTATION);
+        self.reputation.insert(faction.to_string(), reputation);
+    }
+
+    /// Lowers the reputation of the player with all the factions of a killed actor
+    pub fn on_killed(&mut self, factions: &[String]) {
+        for faction in factions {
+            let penalty = {
+                let resources = get_global::<Resources>();
+                resources.try_get_faction(faction)
+                    .and_then(|params| params.kill_penalty)
+                    .unwrap_or(Self::DEFAULT_KILL_PENALTY)
+            };
+            self.add_reputation(faction, -penalty);
+        }
+    }
+
+    fn get_reputation_stance(&self, faction: &str) -> FactionStance {
+        let reputation = self.get_reputation(faction);
+        if reputation >= Self::ALLIED_REPUTATION {
+            FactionStance::Allied
+        } else if reputation <= Self::HOSTILE_REPUTATION {
+            FactionStance::Hostile
+        } else {
+            FactionStance::Neutral
+        }
+    }
+
+    fn get_faction_stance(&self, faction: &str, other: &str) -> FactionStance {
+        if faction == other {
+            return FactionStance::Allied;
+        }
+        if faction == Self::PLAYER_FACTION {
+            return self.get_reputation_stance(other);
+        }
+        if other == Self::PLAYER_FACTION {
+            return self.get_reputation_stance(faction);
+        }
+        let resources = get_global::<Resources>();
+        for (from, to) in [(faction, other), (other, faction)] {
+            let stance = resources.try_get_faction(from)
+                .and_then(|params| params.stances.as_ref())
+                .and_then(|stances| stances.iter().find(|stance| stance.faction == to));
+            if let Some(stance) = stance {
+                return stance.stance;
+            }
+        }
+        FactionStance::Hostile
+    }
+
+    /// Returns the stance between two actors, by their factions.
+    /// If any pair of their factions are allied they are allied, otherwise they are hostile if any pair is hostile.
+    /// Actors without factions are hostile to everyone, as are factions without a defined stance towards each other.
+    pub fn get_stance(factions: &[String], other: &[String]) -> FactionStance {
+        if factions.is_empty() || other.is_empty() {
+            return FactionStance::Hostile;
+        }
+        let this = get_global::<Factions>();
+        let mut stance = FactionStance::Neutral;
+        for faction in factions {
+            for other_faction in other {
+                match this.get_faction_stance(faction, other_faction) {
+                    FactionStance::Allied => return FactionStance::Allied,
+                    FactionStance::Hostile => stance = FactionStance::Hostile,
+                    FactionStance::Neutral => {}
+                }
+            }
+        }
+        stance
+    }
+}
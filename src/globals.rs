@@ -1,6 +1,9 @@
 use std::{
     any::Any,
-    ops::Deref,
+    ops::{
+        Deref,
+        DerefMut,
+    },
 };
 
 use macroquad::experimental::collections::storage;
@@ -18,6 +21,14 @@ pub fn get_global<T: Any>() -> impl Deref<Target = T> {
     storage::get::<T>()
 }
 
+pub fn try_get_global_mut<T: Any>() -> Option<impl DerefMut<Target = T>> {
+    storage::try_get_mut::<T>()
+}
+
+pub fn get_global_mut<T: Any>() -> impl DerefMut<Target = T> {
+    storage::get_mut::<T>()
+}
+
 pub fn set_global<T: Any>(data: T) {
     storage::store::<T>(data);
 }
@@ -1,10 +1,32 @@
 mod inventory;
 mod character;
+mod save_games;
+mod spell_book;
+mod settings;
+mod dialogue;
+mod quest_log;
+mod death;
+mod transfer;
+mod notices;
 
 use macroquad::prelude::*;
 
-use inventory::draw_inventory_window;
+use inventory::{
+    draw_inventory_window,
+    draw_quick_use_bar,
+};
 use character::draw_character_window;
+use save_games::draw_save_games_window;
+use settings::draw_settings_window;
+use dialogue::draw_dialogue_window;
+use quest_log::draw_quest_log_window;
+use death::draw_death_window;
+use transfer::draw_transfer_window;
+use notices::draw_notices;
+use spell_book::{
+    draw_spell_book_window,
+    draw_ability_bar,
+};
 
 use crate::nodes::{
     GameState,
@@ -12,12 +34,32 @@ use crate::nodes::{
 };
 
 pub fn draw_gui() {
-    let game_state = scene::find_node_by_type::<GameState>().unwrap();
-    let mut player = Actor::find_local_player().unwrap();
-    if game_state.show_character_window {
-        draw_character_window(&*player);
+    let mut game_state = scene::find_node_by_type::<GameState>().unwrap();
+    if let Some(mut player) = Actor::find_local_player() {
+        if game_state.show_character_window {
+            draw_character_window(&mut *player);
+        }
+        if game_state.show_inventory_window {
+            draw_inventory_window(&mut *player);
+        }
+        if game_state.show_spell_book_window {
+            draw_spell_book_window(&mut *player);
+        }
+        if game_state.show_quest_log_window {
+            draw_quest_log_window(&*player);
+        }
+        draw_ability_bar(&*player);
+        draw_quick_use_bar(&*player);
+        draw_dialogue_window(&mut *game_state, &mut *player);
+        draw_transfer_window(&mut *game_state, &mut *player);
+    } else if game_state.dead_player.is_some() {
+        draw_death_window(&mut *game_state);
+    }
+    if game_state.show_save_games_window {
+        draw_save_games_window(&mut *game_state);
     }
-    if game_state.show_inventory_window {
-        draw_inventory_window(&mut *player);
+    if game_state.show_settings_window {
+        draw_settings_window();
     }
+    draw_notices();
 }
@@ -7,20 +7,53 @@ use macroquad::{
 };
 
 use crate::{
-    nodes::Actor,
+    get_global,
+    nodes::{
+        Actor,
+        actor::ActorStats,
+    },
+    Resources,
+    Factions,
 };
 
-pub fn draw_character_window(player: &Actor) {
+const ATTRIBUTE_BUTTON_OFFSET: f32 = 100.0;
+
+pub fn draw_character_window(player: &mut Actor) {
     widgets::Window::new(hash!(), vec2(50.0, 150.0), vec2(300.0, 300.0))
         .label(&player.name)
         .ui(&mut *root_ui(), |ui| {
-            ui.label(None, &format!("STR: {}", player.stats.strength));
-            ui.label(None, &format!("DEX: {}", player.stats.dexterity));
-            ui.label(None, &format!("CON: {}", player.stats.constitution));
-            ui.label(None, &format!("INT: {}", player.stats.intelligence));
-            ui.label(None, &format!("WIL: {}", player.stats.willpower));
-            ui.label(None, &format!("PER: {}", player.stats.perception));
-            ui.label(None, &format!("CHA: {}", player.stats.charisma));
+            ui.label(None, &format!("Level: {}", player.stats.level));
+            {
+                let resources = get_global::<Resources>();
+                match resources.try_get_level(player.stats.level + 1) {
+                    Some(next_level) => ui.label(None, &format!("XP: {}/{}", player.stats.experience, next_level.experience)),
+                    None => ui.label(None, &format!("XP: {}", player.stats.experience)),
+                }
+            }
+            if player.stats.attribute_points > 0 {
+                ui.label(None, &format!("Attribute points: {}", player.stats.attribute_points));
+            }
+
+            ui.separator();
+
+            let attributes = [
+                ("STR", ActorStats::STRENGTH_ATTRIBUTE, player.stats.get_strength()),
+                ("DEX", ActorStats::DEXTERITY_ATTRIBUTE, player.stats.get_dexterity()),
+                ("CON", ActorStats::CONSTITUTION_ATTRIBUTE, player.stats.get_constitution()),
+                ("INT", ActorStats::INTELLIGENCE_ATTRIBUTE, player.stats.get_intelligence()),
+                ("WIL", ActorStats::WILLPOWER_ATTRIBUTE, player.stats.get_willpower()),
+                ("PER", ActorStats::PERCEPTION_ATTRIBUTE, player.stats.get_perception()),
+                ("CHA", ActorStats::CHARISMA_ATTRIBUTE, player.stats.get_charisma()),
+            ];
+            for (label, attribute, value) in attributes {
+                ui.label(None, &format!("{}: {}", label, value));
+                if player.stats.attribute_points > 0 {
+                    ui.same_line(ATTRIBUTE_BUTTON_OFFSET);
+                    if ui.button(None, "+") {
+                        player.stats.allocate_attribute_point(attribute);
+                    }
+                }
+            }
 
             ui.separator();
 
@@ -29,5 +62,47 @@ pub fn draw_character_window(player: &Actor) {
                 ui.label(None, &format!("Stamina: {}", player.stats.stamina_regen));
                 ui.label(None, &format!("Energy:  {}", player.stats.energy_regen));
             });
+
+            ui.tree_node(hash!(), "Defense", |ui| {
+                ui.label(None, &format!("Armor:   {}", player.stats.armor));
+                ui.label(None, &format!("Kinetic: {}%", player.stats.kinetic_resistance));
+                ui.label(None, &format!("Energy:  {}%", player.stats.energy_resistance));
+                ui.label(None, &format!("Fire:    {}%", player.stats.fire_resistance));
+                ui.label(None, &format!("Psychic: {}%", player.stats.psychic_resistance));
+            });
+
+            {
+                let resources = get_global::<Resources>();
+                let factions = get_global::<Factions>();
+                let mut all_factions: Vec<_> = resources.get_all_factions()
+                    .into_iter()
+                    .filter(|params| params.id != Factions::PLAYER_FACTION)
+                    .collect();
+                all_factions.sort_by(|a, b| a.name.cmp(&b.name));
+                ui.tree_node(hash!(), "Reputation", |ui| {
+                    for params in all_factions {
+                        let stance = Factions::get_stance(&player.factions, &[params.id.clone()]);
+                        ui.label(None, &format!("{}: {} ({})", params.name, factions.get_reputation(&params.id), stance.get_label()));
+                    }
+                });
+            }
+
+            if player.feats.len() > 0 {
+                let resources = get_global::<Resources>();
+                ui.tree_node(hash!(), "Feats", |ui| {
+                    for feat_id in &player.feats {
+                        let feat = resources.get_feat(feat_id);
+                        ui.label(None, &format!("{}: {}", feat.name, feat.description));
+                    }
+                });
+            }
+
+            if player.status_effects.len() > 0 {
+                ui.tree_node(hash!(), "Effects", |ui| {
+                    for effect in &player.status_effects {
+                        ui.label(None, &effect.get_label());
+                    }
+                });
+            }
         });
 }
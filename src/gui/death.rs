@@ -0,0 +1,32 @@
+use macroquad::{
+    ui::{
+        hash, root_ui,
+        widgets::{self},
+    },
+    prelude::*,
+};
+
+use crate::nodes::GameState;
+
+pub fn draw_death_window(game_state: &mut GameState) {
+    let size = vec2(250.0, 150.0);
+    let position = vec2((screen_width() - size.x) / 2.0, (screen_height() - size.y) / 2.0);
+    widgets::Window::new(hash!(), position, size)
+        .label("You Died")
+        .movable(false)
+        .ui(&mut *root_ui(), |ui| {
+            if let Some(player) = &game_state.dead_player {
+                ui.label(None, &format!("{} has died", player.name));
+            }
+            ui.separator();
+            if ui.button(None, "Respawn") {
+                game_state.should_respawn = true;
+            }
+            if ui.button(None, "Load Game") {
+                game_state.show_save_games_window = !game_state.show_save_games_window;
+            }
+            if ui.button(None, "Quit") {
+                game_state.should_quit = true;
+            }
+        });
+}
@@ -0,0 +1,73 @@
+use macroquad::{
+    ui::{
+        hash, root_ui,
+        widgets::{self},
+    },
+    prelude::*,
+};
+
+use crate::{
+    get_global,
+    nodes::{
+        GameState,
+        Actor,
+        actor::Dialogue,
+    },
+    show_notice,
+    Resources,
+};
+
+pub fn draw_dialogue_window(game_state: &mut GameState, player: &mut Actor) {
+    let mut dialogue = match game_state.dialogue.take() {
+        Some(dialogue) => dialogue,
+        None => return,
+    };
+
+    let is_in_range = Actor::find_with_id(&dialogue.actor_id)
+        .map(|actor| actor.body.position.distance(player.body.position) <= Dialogue::MAX_DISTANCE)
+        .unwrap_or(false);
+    if is_in_range == false {
+        return;
+    }
+
+    let params = {
+        let resources = get_global::<Resources>();
+        resources.get_dialogue(&dialogue.dialogue_id).clone()
+    };
+    let node = match params.get_node(&dialogue.node_id) {
+        Some(node) => node,
+        None => {
+            show_notice(&format!("Unable to find node '{}' in dialogue '{}'", dialogue.node_id, dialogue.dialogue_id));
+            return;
+        }
+    };
+
+    let mut is_open = true;
+    widgets::Window::new(hash!(), vec2(400.0, 475.0), vec2(450.0, 250.0))
+        .label(&dialogue.actor_name)
+        .ui(&mut *root_ui(), |ui| {
+            for line in node.text.lines() {
+                ui.label(None, line);
+            }
+
+            ui.separator();
+
+            let choices: Vec<_> = node.choices
+                .iter()
+                .flatten()
+                .filter(|choice| choice.is_available(player))
+                .collect();
+            for (i, choice) in choices.iter().enumerate() {
+                if ui.button(None, format!("{}. {}", i + 1, choice.text).as_str()) {
+                    is_open = dialogue.select_choice(choice, player);
+                }
+            }
+            if choices.is_empty() && ui.button(None, "End") {
+                is_open = false;
+            }
+        });
+
+    if is_open {
+        game_state.dialogue = Some(dialogue);
+    }
+}
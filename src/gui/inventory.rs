@@ -7,54 +7,107 @@ use macroquad::{
 };
 
 use crate::{
-    nodes::Actor,
+    nodes::{
+        Actor,
+        actor::{
+            ActorEquipment,
+            ActorInventoryEntry,
+            ActorQuickUseBar,
+        },
+    },
+    get_global,
+    ActionMap,
     Item,
 };
-use crate::nodes::ActorAbility;
+
+const SLOT_BUTTON_OFFSET: f32 = 20.0;
+const QUICK_USE_BUTTON_OFFSET: f32 = 90.0;
+
+const QUICK_USE_BAR_SLOT_WIDTH: f32 = 160.0;
+const QUICK_USE_BAR_HEIGHT: f32 = 40.0;
+// The quick use bar is drawn above the ability bar
+const QUICK_USE_BAR_MARGIN: f32 = 60.0;
+
+fn get_entry_label(entry: &ActorInventoryEntry) -> String {
+    if entry.quantity > 1 {
+        format!("{} x{}", entry.params.name, entry.quantity)
+    } else {
+        entry.params.name.clone()
+    }
+}
 
 pub fn draw_inventory_window(player: &mut Actor) {
     widgets::Window::new(hash!(), vec2(50.0, 475.0), vec2(300.0, 300.0))
         .label("Inventory")
         .ui(&mut *root_ui(), |ui| {
-            ui.label(None, &format!("weight: {}/{}", player.inventory.get_total_weight(), player.stats.carry_capacity));
+            ui.label(None, &format!("weight: {}/{}", player.get_total_weight(), player.stats.carry_capacity));
+            ui.label(None, &format!("gold: {}", player.gold));
             {
-                let items = player.inventory.get_all_of_kind(Item::WEAPON_KINDS);
+                let equipped: Vec<_> = player.equipment.get_all()
+                    .into_iter()
+                    .map(|(slot, entry)| (slot, entry.params.name.clone()))
+                    .collect();
+                if equipped.len() > 0 {
+                    ui.tree_node(hash!(), "Equipment", |ui| {
+                        for (slot, name) in equipped {
+                            ui.label(None, &format!("{}: {}", slot.get_label(), name));
+                            if ui.button(None, "Unequip") {
+                                player.unequip_item(slot);
+                            }
+                        }
+                    });
+                }
+            }
+            for (label, kinds) in [
+                ("Weapons", Item::WEAPON_KINDS),
+                ("Armor", Item::ARMOR_KINDS),
+                ("Trinkets", &[Item::TRINKET_KIND]),
+                ("Miscellaneous", &[Item::MISC_KIND]),
+            ] {
+                let items = player.inventory.get_all_of_kind(kinds);
                 if items.len() > 0 {
-                    ui.tree_node(hash!(), "Weapons", |ui| {
+                    ui.tree_node(hash!(label), label, |ui| {
                         for item in &items {
-                            ui.label(None, &item.params.name);
-                            if ui.button(None, "Equip") {
-                                if item.params.ability_params.action_kind == ActorAbility::PRIMARY_ABILITY.to_string() {
-                                    player.primary_ability = Some(item.to_actor_ability());
-                                } else if item.params.ability_params.action_kind == ActorAbility::SECONDARY_ABILITY.to_string() {
-                                    player.secondary_ability = Some(item.to_actor_ability());
-                                }
+                            ui.label(None, &get_entry_label(item));
+                            if ActorEquipment::is_equippable(&item.params.kind) && ui.button(None, "Equip") {
+                                player.equip_item(&item.instance_id);
                             }
                             if ui.button(None, "Drop") {
                                 let position = player.body.position;
                                 player.inventory.drop_item(&item.instance_id, position);
                             }
+                            if item.quantity > 1 {
+                                if ui.button(None, "Drop One") {
+                                    let position = player.body.position;
+                                    player.inventory.drop_quantity(&item.instance_id, 1, position);
+                                }
+                                if ui.button(None, "Split") {
+                                    player.inventory.split_entry(&item.instance_id, item.quantity / 2);
+                                }
+                            }
                         }
                     });
                 }
             }
             {
-                let items = player.inventory.get_all_of_kind(&[Item::MISC_KIND]);
+                let items = player.inventory.get_all_of_kind(&[Item::CONSUMABLE_KIND]);
                 if items.len() > 0 {
-                    ui.tree_node(hash!(), "Miscellaneous", |ui| {
+                    ui.tree_node(hash!(), "Consumables", |ui| {
                         for item in &items {
-                            ui.label(None, &item.params.name);
-                            if ui.button(None, "Equip") {
-                                if item.params.ability_params.action_kind == ActorAbility::PRIMARY_ABILITY.to_string() {
-                                    player.primary_ability = Some(item.to_actor_ability());
-                                } else if item.params.ability_params.action_kind == ActorAbility::SECONDARY_ABILITY.to_string() {
-                                    player.secondary_ability = Some(item.to_actor_ability());
-                                }
+                            ui.label(None, &get_entry_label(item));
+                            if ui.button(None, "Use") {
+                                player.use_item(&item.instance_id);
                             }
                             if ui.button(None, "Drop") {
                                 let position = player.body.position;
                                 player.inventory.drop_item(&item.instance_id, position);
                             }
+                            for slot in 0..ActorQuickUseBar::SLOT_COUNT {
+                                ui.same_line(QUICK_USE_BUTTON_OFFSET + slot as f32 * SLOT_BUTTON_OFFSET);
+                                if ui.button(None, format!("{}", slot + 1).as_str()) {
+                                    player.assign_quick_use(slot, Some(&item.params.id));
+                                }
+                            }
                         }
                     });
                 }
@@ -64,10 +117,38 @@ pub fn draw_inventory_window(player: &mut Actor) {
                 if items.len() > 0 {
                     ui.tree_node(hash!(), "Quest Items", |ui| {
                         for item in &items {
-                            ui.label(None, &item.params.name);
+                            ui.label(None, &get_entry_label(item));
                         }
                     });
                 }
             }
         });
 }
+
+pub fn draw_quick_use_bar(player: &Actor) {
+    let size = vec2(QUICK_USE_BAR_SLOT_WIDTH * ActorQuickUseBar::SLOT_COUNT as f32, QUICK_USE_BAR_HEIGHT);
+    let position = vec2((screen_width() - size.x) / 2.0, screen_height() - size.y - QUICK_USE_BAR_MARGIN);
+    let action_map = get_global::<ActionMap>();
+    widgets::Window::new(hash!(), position, size)
+        .titlebar(false)
+        .movable(false)
+        .ui(&mut *root_ui(), |ui| {
+            for slot in 0..ActorQuickUseBar::SLOT_COUNT {
+                if slot > 0 {
+                    ui.same_line(slot as f32 * QUICK_USE_BAR_SLOT_WIDTH);
+                }
+                let key = action_map.get_bindings(ActionMap::QUICK_USE_ACTIONS[slot])
+                    .first()
+                    .map(|binding| binding.get_name())
+                    .unwrap_or(format!("{}", slot + 1));
+                let label = match player.quick_use_bar.get(slot) {
+                    Some(item_id) => match player.inventory.get_first_of_item(item_id) {
+                        Some(entry) => format!("{}: {} x{}", key, entry.params.name, player.inventory.count_item(item_id)),
+                        None => format!("{}: {} x0", key, item_id),
+                    },
+                    None => format!("{}: -", key),
+                };
+                ui.label(None, &label);
+            }
+        });
+}
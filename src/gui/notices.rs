@@ -0,0 +1,31 @@
+use macroquad::{
+    ui::{
+        hash, root_ui,
+        widgets::{self},
+    },
+    prelude::*,
+};
+
+use crate::{
+    get_global_mut,
+    Notices,
+};
+
+pub fn draw_notices() {
+    let mut notices = get_global_mut::<Notices>();
+    notices.update(get_frame_time());
+    let count = notices.get_all().len();
+    if count == 0 {
+        return;
+    }
+    let size = vec2(450.0, 40.0 + count as f32 * 20.0);
+    let position = vec2((screen_width() - size.x) / 2.0, 10.0);
+    widgets::Window::new(hash!(), position, size)
+        .label("Notices")
+        .movable(false)
+        .ui(&mut *root_ui(), |ui| {
+            for notice in notices.get_all() {
+                ui.label(None, &notice.text);
+            }
+        });
+}
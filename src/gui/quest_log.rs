@@ -0,0 +1,45 @@
+use macroquad::{
+    ui::{
+        hash, root_ui,
+        widgets::{self},
+    },
+    prelude::*,
+};
+
+use crate::nodes::Actor;
+
+pub fn draw_quest_log_window(player: &Actor) {
+    widgets::Window::new(hash!(), vec2(750.0, 150.0), vec2(350.0, 300.0))
+        .label("Quest Log")
+        .ui(&mut *root_ui(), |ui| {
+            if player.quests.is_empty() {
+                ui.label(None, "No quests");
+            }
+            for quest in player.quests.iter().filter(|quest| quest.is_completed == false) {
+                ui.tree_node(hash!(&quest.params.id), &quest.params.name, |ui| {
+                    ui.label(None, &quest.params.description);
+                    if let Some(stage) = quest.get_current_stage() {
+                        ui.separator();
+                        ui.label(None, &stage.description);
+                        for (objective, progress) in stage.objectives.iter().zip(&quest.progress) {
+                            let amount = objective.get_amount();
+                            let check = if *progress >= amount { "x" } else { " " };
+                            if amount > 1 {
+                                ui.label(None, &format!("[{}] {} ({}/{})", check, objective.description, progress, amount));
+                            } else {
+                                ui.label(None, &format!("[{}] {}", check, objective.description));
+                            }
+                        }
+                    }
+                });
+            }
+            let completed: Vec<_> = player.quests.iter().filter(|quest| quest.is_completed).collect();
+            if completed.len() > 0 {
+                ui.tree_node(hash!(), "Completed", |ui| {
+                    for quest in completed {
+                        ui.label(None, &quest.params.name);
+                    }
+                });
+            }
+        });
+}
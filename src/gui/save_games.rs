@@ -0,0 +1,40 @@
+use macroquad::{
+    ui::{
+        hash, root_ui,
+        widgets::{self},
+    },
+    prelude::*,
+};
+
+use crate::{
+    nodes::GameState,
+    SaveGame,
+};
+
+pub fn draw_save_games_window(game_state: &mut GameState) {
+    widgets::Window::new(hash!(), vec2(400.0, 150.0), vec2(300.0, 300.0))
+        .label("Save Games")
+        .ui(&mut *root_ui(), |ui| {
+            {
+                let slot = SaveGame::QUICKSAVE_SLOT.to_string();
+                if SaveGame::exists(&slot) {
+                    ui.label(None, "Quicksave");
+                    if ui.button(None, "Load") {
+                        game_state.should_load_from = Some(slot);
+                    }
+                    ui.separator();
+                }
+            }
+            for i in 0..SaveGame::SLOT_COUNT {
+                let slot = SaveGame::get_slot_name(i);
+                let exists = SaveGame::exists(&slot);
+                ui.label(None, &format!("Slot {}{}", i + 1, if exists { "" } else { " (empty)" }));
+                if ui.button(None, "Save") {
+                    game_state.should_save_to = Some(slot.clone());
+                }
+                if exists && ui.button(None, "Load") {
+                    game_state.should_load_from = Some(slot);
+                }
+            }
+        });
+}
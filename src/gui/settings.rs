@@ -0,0 +1,79 @@
+use macroquad::{
+    ui::{
+        hash, root_ui,
+        widgets::{self},
+    },
+    prelude::*,
+};
+
+use crate::{
+    get_global_mut,
+    input::{
+        ActionMap,
+        InputState,
+    },
+    show_notice,
+    Settings,
+};
+
+const BINDINGS_OFFSET: f32 = 150.0;
+
+pub fn draw_settings_window() {
+    let mut settings = get_global_mut::<Settings>();
+    let mut action_map = get_global_mut::<ActionMap>();
+    let mut input_state = get_global_mut::<InputState>();
+    widgets::Window::new(hash!(), vec2(400.0, 150.0), vec2(450.0, 500.0))
+        .label("Settings")
+        .ui(&mut *root_ui(), |ui| {
+            ui.tree_node(hash!(), "Audio", |ui| {
+                ui.slider(hash!(), "Master", 0.0..1.0, &mut settings.master_volume);
+                ui.slider(hash!(), "Effects", 0.0..1.0, &mut settings.sound_effects_volume);
+                ui.slider(hash!(), "Music", 0.0..1.0, &mut settings.music_volume);
+            });
+
+            ui.separator();
+
+            match &input_state.rebinding {
+                Some(rebinding) => ui.label(None, &format!(
+                    "Press a key or button for '{}' (Escape to cancel)",
+                    ActionMap::get_action_label(&rebinding.action),
+                )),
+                None => ui.label(None, "Click a binding to remove it, or '+' to add one"),
+            }
+            if let Some(notice) = &input_state.rebinding_notice {
+                ui.label(None, notice);
+            }
+
+            ui.tree_node(hash!(), "Key bindings", |ui| {
+                for (action, label) in ActionMap::get_actions() {
+                    ui.label(None, label);
+                    ui.same_line(BINDINGS_OFFSET);
+                    for binding in action_map.get_bindings(action).to_vec() {
+                        if ui.button(None, binding.get_name().as_str()) {
+                            action_map.unbind(action, binding);
+                        }
+                        ui.same_line(0.0);
+                    }
+                    if ui.button(None, "+") {
+                        input_state.start_rebinding(action);
+                    }
+                }
+            });
+
+            ui.separator();
+
+            if ui.button(None, "Save") {
+                if let Err(err) = settings.save() {
+                    show_notice(&format!("Unable to save settings: {}", err));
+                }
+                if let Err(err) = action_map.save() {
+                    show_notice(&format!("Unable to save keymap: {}", err));
+                }
+            }
+            ui.same_line(0.0);
+            if ui.button(None, "Reset key bindings") {
+                *action_map = ActionMap::default();
+                input_state.rebinding_notice = None;
+            }
+        });
+}
@@ -0,0 +1,79 @@
+use macroquad::{
+    ui::{
+        hash, root_ui,
+        widgets::{self},
+    },
+    prelude::*,
+};
+
+use crate::{
+    get_global,
+    nodes::{
+        Actor,
+        actor::ActorAbilityBar,
+    },
+    Resources,
+};
+
+const SLOT_BUTTON_OFFSET: f32 = 20.0;
+
+const ABILITY_BAR_SLOT_WIDTH: f32 = 110.0;
+const ABILITY_BAR_HEIGHT: f32 = 40.0;
+const ABILITY_BAR_MARGIN: f32 = 10.0;
+
+pub fn draw_spell_book_window(player: &mut Actor) {
+    widgets::Window::new(hash!(), vec2(400.0, 475.0), vec2(300.0, 300.0))
+        .label("Spell Book")
+        .ui(&mut *root_ui(), |ui| {
+            let spells: Vec<(String, String, String)> = {
+                let resources = get_global::<Resources>();
+                player.spells.iter().map(|spell_id| {
+                    let params = resources.get_spell(spell_id);
+                    (params.id.clone(), params.name.clone(), params.description.clone())
+                }).collect()
+            };
+            if spells.is_empty() {
+                ui.label(None, "No spells learned");
+            }
+            for (spell_id, name, description) in spells {
+                ui.tree_node(hash!(&spell_id), &name, |ui| {
+                    ui.label(None, &description);
+                    for slot in 0..ActorAbilityBar::SLOT_COUNT {
+                        if slot > 0 {
+                            ui.same_line(slot as f32 * SLOT_BUTTON_OFFSET);
+                        }
+                        if ui.button(None, format!("{}", slot + 1).as_str()) {
+                            player.assign_spell(slot, Some(&spell_id));
+                        }
+                    }
+                });
+            }
+        });
+}
+
+pub fn draw_ability_bar(player: &Actor) {
+    let size = vec2(ABILITY_BAR_SLOT_WIDTH * ActorAbilityBar::SLOT_COUNT as f32, ABILITY_BAR_HEIGHT);
+    let position = vec2((screen_width() - size.x) / 2.0, screen_height() - size.y - ABILITY_BAR_MARGIN);
+    widgets::Window::new(hash!(), position, size)
+        .titlebar(false)
+        .movable(false)
+        .ui(&mut *root_ui(), |ui| {
+            for slot in 0..ActorAbilityBar::SLOT_COUNT {
+                if slot > 0 {
+                    ui.same_line(slot as f32 * ABILITY_BAR_SLOT_WIDTH);
+                }
+                let label = match player.ability_bar.get(slot) {
+                    Some(entry) => {
+                        let cooldown = entry.ability.cooldown - entry.ability.cooldown_timer;
+                        if cooldown > 0.0 {
+                            format!("{}: {} ({:.1})", slot + 1, entry.name, cooldown)
+                        } else {
+                            format!("{}: {}", slot + 1, entry.name)
+                        }
+                    }
+                    None => format!("{}: -", slot + 1),
+                };
+                ui.label(None, &label);
+            }
+        });
+}
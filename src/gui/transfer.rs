@@ -0,0 +1,146 @@
+use macroquad::{
+    ui::{
+        hash, root_ui,
+        widgets::{self},
+        Drag,
+        Id,
+        Layout,
+        Ui,
+    },
+    prelude::*,
+};
+
+use crate::nodes::{
+    Actor,
+    Containers,
+    Corpses,
+    GameState,
+    TransferTarget,
+    actor::ActorInventoryEntry,
+};
+
+const PANE_WIDTH: f32 = 240.0;
+const PANE_HEIGHT: f32 = 300.0;
+const ENTRY_HEIGHT: f32 = 22.0;
+
+fn get_entry_id(entry: &ActorInventoryEntry) -> Id {
+    hash!("transfer_entry", &entry.instance_id)
+}
+
+/// Draws the entries of an inventory as draggable groups and returns the instance ids of the entries that
+/// were dropped on any of the `drop_ids`, which are the ids of the other pane and its entries
+fn draw_pane(ui: &mut Ui, id: Id, label: &str, entries: &[ActorInventoryEntry], drop_ids: &[Id]) -> Vec<String> {
+    let mut dropped = Vec::new();
+    widgets::Group::new(id, vec2(PANE_WIDTH, PANE_HEIGHT))
+        .hoverable(true)
+        .ui(ui, |ui| {
+            ui.label(None, label);
+            for entry in entries {
+                let drag = widgets::Group::new(get_entry_id(entry), vec2(PANE_WIDTH - 20.0, ENTRY_HEIGHT))
+                    .layout(Layout::Vertical)
+                    .draggable(true)
+                    .ui(ui, |ui| {
+                        if entry.quantity > 1 {
+                            ui.label(None, &format!("{} x{} ({})", entry.params.name, entry.quantity, entry.get_weight()));
+                        } else {
+                            ui.label(None, &format!("{} ({})", entry.params.name, entry.get_weight()));
+                        }
+                    });
+                if let Drag::Dropped(_, Some(target_id)) = drag {
+                    if drop_ids.contains(&target_id) {
+                        dropped.push(entry.instance_id.clone());
+                    }
+                }
+            }
+        });
+    dropped
+}
+
+pub fn draw_transfer_window(game_state: &mut GameState, player: &mut Actor) {
+    let target = match game_state.transfer_target.take() {
+        Some(target) => target,
+        None => return,
+    };
+    let position = player.body.position;
+    let mut containers = scene::find_node_by_type::<Containers>().unwrap();
+    let mut corpses = scene::find_node_by_type::<Corpses>().unwrap();
+    // The window is closed if the container or corpse is gone, or if the player has moved away from it
+    let other = match &target {
+        TransferTarget::Container { id } => containers.get_mut(id)
+            .filter(|container| container.distance_to(position) <= TransferTarget::MAX_DISTANCE)
+            .map(|container| (container.name.clone(), &mut container.inventory, None)),
+        TransferTarget::Corpse { id } => corpses.get_mut(id)
+            .filter(|corpse| corpse.position.distance(position) <= TransferTarget::MAX_DISTANCE)
+            .map(|corpse| (corpse.name.clone(), &mut corpse.inventory, Some(&mut corpse.gold))),
+    };
+    let (name, inventory, mut gold) = match other {
+        Some(other) => other,
+        None => return,
+    };
+
+    let player_entries = player.inventory.get_all();
+    let other_entries = inventory.get_all();
+    let player_pane_id = hash!("transfer_player_pane");
+    let other_pane_id = hash!("transfer_other_pane");
+    let player_drop_ids: Vec<Id> = std::iter::once(player_pane_id)
+        .chain(player_entries.iter().map(|entry| get_entry_id(entry)))
+        .collect();
+    let other_drop_ids: Vec<Id> = std::iter::once(other_pane_id)
+        .chain(other_entries.iter().map(|entry| get_entry_id(entry)))
+        .collect();
+
+    let mut to_other = Vec::new();
+    let mut to_player = Vec::new();
+    let mut should_take_all = false;
+    let mut should_take_gold = false;
+    let mut is_open = true;
+    widgets::Window::new(hash!(), vec2(400.0, 150.0), vec2(PANE_WIDTH * 2.0 + 30.0, PANE_HEIGHT + 120.0))
+        .label(&name)
+        .ui(&mut *root_ui(), |ui| {
+            ui.label(None, &format!("Weight: {}/{}", player.get_total_weight(), player.stats.carry_capacity));
+            if let Some(gold) = &gold {
+                ui.label(None, &format!("Gold: {}", gold));
+            }
+            to_other = draw_pane(ui, player_pane_id, "Inventory", &player_entries, &other_drop_ids);
+            to_player = draw_pane(ui, other_pane_id, &name, &other_entries, &player_drop_ids);
+            if ui.button(None, "Take All") {
+                should_take_all = true;
+            }
+            if gold.as_ref().map(|gold| **gold > 0).unwrap_or(false) && ui.button(None, "Take Gold") {
+                should_take_gold = true;
+            }
+            if ui.button(None, "Close") {
+                is_open = false;
+            }
+        });
+
+    for instance_id in to_other {
+        if let Some(entry) = player.inventory.take_entry(&instance_id) {
+            inventory.add_entry(entry);
+        }
+    }
+    if should_take_all {
+        to_player = other_entries.iter().map(|entry| entry.instance_id.clone()).collect();
+        should_take_gold = true;
+    }
+    // Entries that are too heavy for the player to carry are left where they are
+    for instance_id in to_player {
+        let weight = inventory.get_entry(&instance_id).map(|entry| entry.get_weight());
+        if let Some(weight) = weight {
+            if player.can_carry(weight) {
+                let entry = inventory.take_entry(&instance_id).unwrap();
+                player.add_to_inventory(entry);
+            }
+        }
+    }
+    if should_take_gold {
+        if let Some(gold) = gold.as_mut() {
+            player.gold += **gold;
+            **gold = 0;
+        }
+    }
+
+    if is_open {
+        game_state.transfer_target = Some(target);
+    }
+}
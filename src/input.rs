@@ -1,61 +1,402 @@
+use std::time::Duration;
+
 use macroquad::{
     prelude::*,
 };
 
+use gamepad_rs::{
+    Axis,
+    Button,
+    ControllerContext,
+    ControllerEventKind,
+    ControllerStatus,
+    StandardLayout,
+    StandardState,
+};
+
 use crate::{
     get_global,
+    get_global_mut,
+    try_get_global_mut,
     render::Viewport,
     nodes::{
         GameState,
     },
+    SaveGame,
+    show_notice,
 };
 use crate::nodes::ActorController;
 
+mod action_map;
+
+pub use action_map::{
+    ActionMap,
+    InputBinding,
+};
+
+// Optional SDL gamecontrollerdb file, with mappings for controllers that gamepad-rs can not guess
+const GAMECONTROLLERDB_PATH: &str = "gamecontrollerdb.txt";
+
+const GAMEPAD_TRIGGER_THRESHOLD: f32 = 0.5;
+const GAMEPAD_AIM_DISTANCE: f32 = 100.0;
+
+const MOUSE_BUTTONS: &[MouseButton] = &[
+    MouseButton::Left,
+    MouseButton::Right,
+    MouseButton::Middle,
+];
+
+/// An action that is waiting for the next input, to bind it
+pub struct Rebinding {
+    pub action: String,
+    // The click that started the rebinding must be released before mouse buttons can be bound
+    is_mouse_armed: bool,
+}
+
+/// Input state that has to be kept between frames, to detect released gamepad buttons
+/// and to switch between mouse and gamepad aiming, depending on which was used last
+pub struct InputState {
+    pub gamepad_index: usize,
+    pub gamepad_layout: StandardLayout,
+    pub is_gamepad_aiming: bool,
+    pub rebinding: Option<Rebinding>,
+    pub rebinding_notice: Option<String>,
+    // The key that ended the last rebinding is swallowed until it is released,
+    // so that its release does not trigger the action it is bound to, like quit on Escape
+    swallowed_key: Option<KeyCode>,
+    // Set by `update_input` while a rebinding or a swallowed key should keep the local player still
+    is_player_input_blocked: bool,
+    // The gamepad input of the current frame, read once by `update_input`
+    gamepad: Option<GamepadInput>,
+    aim_direction: Vec2,
+    last_mouse_position: Vec2,
+    previous_gamepad_state: StandardState,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        let mut gamepad_layout = StandardLayout::new();
+        if let Err(err) = gamepad_layout.load_mappings(GAMECONTROLLERDB_PATH) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                show_notice(&format!("Unable to load gamepad mappings from '{}': {}", GAMECONTROLLERDB_PATH, err));
+            }
+        }
+        InputState {
+            gamepad_index: 0,
+            gamepad_layout,
+            is_gamepad_aiming: false,
+            rebinding: None,
+            rebinding_notice: None,
+            swallowed_key: None,
+            is_player_input_blocked: false,
+            gamepad: None,
+            aim_direction: vec2(1.0, 0.0),
+            last_mouse_position: get_mouse_position(),
+            previous_gamepad_state: StandardState::new(),
+        }
+    }
+
+    pub fn start_rebinding(&mut self, action: &str) {
+        self.rebinding = Some(Rebinding {
+            action: action.to_string(),
+            is_mouse_armed: false,
+        });
+        self.rebinding_notice = None;
+    }
+}
+
+#[derive(Copy, Clone)]
+struct GamepadInput {
+    state: StandardState,
+    previous_state: StandardState,
+    left_stick: Vec2,
+    right_stick: Vec2,
+}
+
+// A newly connected gamepad is used if the current one is not connected
+fn handle_gamepad_events(input_state: &mut InputState, gamepads: &mut ControllerContext) {
+    while let Some(event) = gamepads.poll_event() {
+        match event.kind {
+            ControllerEventKind::Connected => {
+                if gamepads.state(input_state.gamepad_index).status != ControllerStatus::Connected {
+                    input_state.gamepad_index = event.index;
+                    input_state.previous_gamepad_state = StandardState::new();
+                }
+            }
+            ControllerEventKind::Disconnected => {
+                if event.index == input_state.gamepad_index {
+                    input_state.is_gamepad_aiming = false;
+                    input_state.previous_gamepad_state = StandardState::new();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn read_gamepad(input_state: &mut InputState) -> Option<GamepadInput> {
+    let mut gamepads = try_get_global_mut::<ControllerContext>()?;
+    gamepads.update(input_state.gamepad_index);
+    handle_gamepad_events(input_state, &mut gamepads);
+    if gamepads.state(input_state.gamepad_index).status != ControllerStatus::Connected {
+        return None;
+    }
+    let state = input_state.gamepad_layout.read(
+        gamepads.info(input_state.gamepad_index),
+        gamepads.state(input_state.gamepad_index),
+    );
+    let previous_state = input_state.previous_gamepad_state;
+    input_state.previous_gamepad_state = state;
+    let (left_x, left_y) = state.left_stick();
+    let (right_x, right_y) = state.right_stick();
+    Some(GamepadInput {
+        state,
+        previous_state,
+        left_stick: vec2(left_x, left_y),
+        right_stick: vec2(right_x, right_y),
+    })
+}
+
+fn is_binding_down(binding: &InputBinding, gamepad: &Option<GamepadInput>) -> bool {
+    match binding {
+        InputBinding::Key(key) => is_key_down(*key),
+        InputBinding::Mouse(button) => is_mouse_button_down(*button),
+        InputBinding::GamepadButton(button) => gamepad.as_ref()
+            .map(|gamepad| gamepad.state.is_down(*button))
+            .unwrap_or(false),
+        InputBinding::GamepadTrigger(axis) => gamepad.as_ref()
+            .map(|gamepad| gamepad.state.axis(*axis) > GAMEPAD_TRIGGER_THRESHOLD)
+            .unwrap_or(false),
+    }
+}
+
+fn is_binding_released(binding: &InputBinding, gamepad: &Option<GamepadInput>) -> bool {
+    match binding {
+        InputBinding::Key(key) => is_key_released(*key),
+        InputBinding::Mouse(button) => is_mouse_button_released(*button),
+        InputBinding::GamepadButton(button) => gamepad.as_ref()
+            .map(|gamepad| gamepad.previous_state.is_down(*button) && !gamepad.state.is_down(*button))
+            .unwrap_or(false),
+        InputBinding::GamepadTrigger(axis) => gamepad.as_ref()
+            .map(|gamepad| gamepad.previous_state.axis(*axis) > GAMEPAD_TRIGGER_THRESHOLD
+                && gamepad.state.axis(*axis) <= GAMEPAD_TRIGGER_THRESHOLD)
+            .unwrap_or(false),
+    }
+}
+
+fn is_action_down(action_map: &ActionMap, action: &str, gamepad: &Option<GamepadInput>) -> bool {
+    action_map.get_bindings(action).iter().any(|binding| is_binding_down(binding, gamepad))
+}
+
+fn is_action_released(action_map: &ActionMap, action: &str, gamepad: &Option<GamepadInput>) -> bool {
+    action_map.get_bindings(action).iter().any(|binding| is_binding_released(binding, gamepad))
+}
+
+fn capture_binding(rebinding: &mut Rebinding, gamepad: &Option<GamepadInput>) -> Option<InputBinding> {
+    if let Some(key) = get_last_key_pressed() {
+        return Some(InputBinding::Key(key));
+    }
+    if rebinding.is_mouse_armed {
+        for button in MOUSE_BUTTONS {
+            if is_mouse_button_pressed(*button) {
+                return Some(InputBinding::Mouse(*button));
+            }
+        }
+    } else if MOUSE_BUTTONS.iter().all(|button| !is_mouse_button_down(*button)) {
+        rebinding.is_mouse_armed = true;
+    }
+    if let Some(gamepad) = gamepad {
+        for button in Button::ALL.iter() {
+            if gamepad.state.is_down(*button) && !gamepad.previous_state.is_down(*button) {
+                return Some(InputBinding::GamepadButton(*button));
+            }
+        }
+        for axis in &[Axis::LeftTrigger, Axis::RightTrigger] {
+            if gamepad.state.axis(*axis) > GAMEPAD_TRIGGER_THRESHOLD
+                && gamepad.previous_state.axis(*axis) <= GAMEPAD_TRIGGER_THRESHOLD {
+                return Some(InputBinding::GamepadTrigger(*axis));
+            }
+        }
+    }
+    None
+}
+
+// Escape cancels the rebinding, so it can not be bound through the settings window
+fn update_rebinding(input_state: &mut InputState, gamepad: &Option<GamepadInput>) {
+    if let Some(mut rebinding) = input_state.rebinding.take() {
+        if is_key_pressed(KeyCode::Escape) {
+            input_state.swallowed_key = Some(KeyCode::Escape);
+            return;
+        }
+        match capture_binding(&mut rebinding, gamepad) {
+            Some(binding) => {
+                if let InputBinding::Key(key) = binding {
+                    input_state.swallowed_key = Some(key);
+                }
+                let mut action_map = get_global_mut::<ActionMap>();
+                if let Some(conflict) = action_map.bind(&rebinding.action, binding) {
+                    input_state.rebinding_notice = Some(format!(
+                        "'{}' was unbound from '{}'",
+                        binding.get_name(),
+                        ActionMap::get_action_label(&conflict),
+                    ));
+                }
+            }
+            None => input_state.rebinding = Some(rebinding),
+        }
+    }
+}
+
+/// Rumbles the gamepad of the local player, if it supports it. The duration is in seconds
+pub fn rumble_gamepad(strong_magnitude: f32, weak_magnitude: f32, duration: f32) {
+    let input_state = get_global::<InputState>();
+    if let Some(mut gamepads) = try_get_global_mut::<ControllerContext>() {
+        gamepads.rumble(
+            input_state.gamepad_index,
+            strong_magnitude,
+            weak_magnitude,
+            Duration::from_secs_f32(duration),
+        );
+    }
+}
+
 pub fn get_mouse_position() -> Vec2 {
     let (x, y) = mouse_position();
     vec2(x, y)
 }
 
-pub fn apply_local_player_input(controller: &mut ActorController) {
-    let viewport = get_global::<Viewport>();
-    let coords = viewport.get_mouse_world_coords();
-    controller.primary_target = if is_mouse_button_down(MouseButton::Left) {
-        Some(coords)
-    } else {
-        None
-    };
-    controller.secondary_target = if is_mouse_button_down(MouseButton::Right) {
-        Some(coords)
+/// Reads the gamepad and handles rebinding and the actions that are not tied to the local player actor,
+/// like toggling windows, quick saving and quitting. This is called once per frame from the main loop,
+/// so that these also work while the local player is dead
+pub fn update_input() {
+    let mut input_state = get_global_mut::<InputState>();
+    let gamepad = read_gamepad(&mut *input_state);
+    input_state.gamepad = gamepad;
+    input_state.is_player_input_blocked = true;
+
+    if input_state.rebinding.is_some() {
+        update_rebinding(&mut *input_state, &gamepad);
+        return;
+    }
+
+    if let Some(key) = input_state.swallowed_key {
+        if is_key_released(key) || !is_key_down(key) {
+            input_state.swallowed_key = None;
+        }
+        return;
+    }
+
+    input_state.is_player_input_blocked = false;
+
+    let action_map = get_global::<ActionMap>();
+    let mut game_state = scene::find_node_by_type::<GameState>().unwrap();
+    if is_action_released(&action_map, ActionMap::TOGGLE_CHARACTER_WINDOW, &gamepad) {
+        game_state.show_character_window = !game_state.show_character_window;
+    }
+    if is_action_released(&action_map, ActionMap::TOGGLE_INVENTORY_WINDOW, &gamepad) {
+        game_state.show_inventory_window = !game_state.show_inventory_window;
+    }
+    if is_action_released(&action_map, ActionMap::TOGGLE_SPELL_BOOK_WINDOW, &gamepad) {
+        game_state.show_spell_book_window = !game_state.show_spell_book_window;
+    }
+    if is_action_released(&action_map, ActionMap::TOGGLE_SAVE_GAMES_WINDOW, &gamepad) {
+        game_state.show_save_games_window = !game_state.show_save_games_window;
+    }
+    if is_action_released(&action_map, ActionMap::TOGGLE_SETTINGS_WINDOW, &gamepad) {
+        game_state.show_settings_window = !game_state.show_settings_window;
+    }
+    if is_action_released(&action_map, ActionMap::TOGGLE_QUEST_LOG_WINDOW, &gamepad) {
+        game_state.show_quest_log_window = !game_state.show_quest_log_window;
+    }
+    if is_action_released(&action_map, ActionMap::QUICKSAVE, &gamepad) {
+        game_state.should_save_to = Some(SaveGame::QUICKSAVE_SLOT.to_string());
+    }
+    if is_action_released(&action_map, ActionMap::QUICKLOAD, &gamepad) {
+        game_state.should_load_from = Some(SaveGame::QUICKSAVE_SLOT.to_string());
+    }
+    if is_action_released(&action_map, ActionMap::QUIT, &gamepad) {
+        game_state.should_quit = true;
+    }
+}
+
+pub fn apply_local_player_input(controller: &mut ActorController, position: Vec2) {
+    let mut input_state = get_global_mut::<InputState>();
+
+    controller.primary_target = None;
+    controller.secondary_target = None;
+    controller.ability_bar_target = None;
+    controller.quick_use_slot = None;
+    controller.direction = Vec2::ZERO;
+    controller.is_sprinting = false;
+    controller.is_interacting = false;
+    controller.is_picking_up_items = false;
+
+    if input_state.is_player_input_blocked || input_state.rebinding.is_some() {
+        return;
+    }
+
+    let gamepad = input_state.gamepad;
+    let action_map = get_global::<ActionMap>();
+
+    let mouse_position = get_mouse_position();
+    if mouse_position != input_state.last_mouse_position {
+        input_state.last_mouse_position = mouse_position;
+        input_state.is_gamepad_aiming = false;
+    }
+    if let Some(gamepad) = &gamepad {
+        if gamepad.right_stick != Vec2::ZERO {
+            input_state.aim_direction = gamepad.right_stick.normalize();
+            input_state.is_gamepad_aiming = true;
+        }
+    }
+
+    let coords = if input_state.is_gamepad_aiming {
+        position + input_state.aim_direction * GAMEPAD_AIM_DISTANCE
     } else {
-        None
+        let viewport = get_global::<Viewport>();
+        viewport.get_mouse_world_coords()
     };
 
-    controller.direction = Vec2::ZERO;
-    if is_key_down(KeyCode::Up) || is_key_down(KeyCode::W) {
+    if is_action_down(&action_map, ActionMap::PRIMARY_ABILITY, &gamepad) {
+        controller.primary_target = Some(coords);
+    }
+    if is_action_down(&action_map, ActionMap::SECONDARY_ABILITY, &gamepad) {
+        controller.secondary_target = Some(coords);
+    }
+
+    for (slot, action) in ActionMap::ABILITY_BAR_ACTIONS.iter().enumerate() {
+        if is_action_down(&action_map, action, &gamepad) {
+            controller.ability_bar_target = Some((slot, coords));
+            break;
+        }
+    }
+
+    for (slot, action) in ActionMap::QUICK_USE_ACTIONS.iter().enumerate() {
+        if is_action_released(&action_map, action, &gamepad) {
+            controller.quick_use_slot = Some(slot);
+            break;
+        }
+    }
+
+    if is_action_down(&action_map, ActionMap::MOVE_UP, &gamepad) {
         controller.direction.y -= 1.0;
     }
-    if is_key_down(KeyCode::Down) || is_key_down(KeyCode::S) {
+    if is_action_down(&action_map, ActionMap::MOVE_DOWN, &gamepad) {
         controller.direction.y += 1.0;
     }
-    if is_key_down(KeyCode::Left) || is_key_down(KeyCode::A) {
+    if is_action_down(&action_map, ActionMap::MOVE_LEFT, &gamepad) {
         controller.direction.x -= 1.0;
     }
-    if is_key_down(KeyCode::Right) || is_key_down(KeyCode::D) {
+    if is_action_down(&action_map, ActionMap::MOVE_RIGHT, &gamepad) {
         controller.direction.x += 1.0;
     }
+    if let Some(gamepad) = &gamepad {
+        controller.direction += gamepad.left_stick;
+    }
 
-    controller.is_sprinting = is_key_down(KeyCode::LeftShift);
-
-    controller.is_interacting = is_key_released(KeyCode::E);
+    controller.is_sprinting = is_action_down(&action_map, ActionMap::SPRINT, &gamepad);
 
-    controller.is_picking_up_items = is_key_down(KeyCode::R);
+    controller.is_interacting = is_action_released(&action_map, ActionMap::INTERACT, &gamepad);
 
-    let mut game_state = scene::find_node_by_type::<GameState>().unwrap();
-    if is_key_released(KeyCode::C) {
-        game_state.show_character_window = !game_state.show_character_window;
-    }
-    if is_key_released(KeyCode::I) {
-        game_state.show_inventory_window = !game_state.show_inventory_window;
-    }
-    game_state.should_quit = is_key_released(KeyCode::Escape) || is_key_pressed(KeyCode::Q);
+    controller.is_picking_up_items = is_action_down(&action_map, ActionMap::PICK_UP, &gamepad);
 }
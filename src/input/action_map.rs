@@ -0,0 +1,326 @@
+use std::fs;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use macroquad::prelude::*;
+
+use gamepad_rs::{
+    Axis,
+    Button,
+};
+
+use crate::show_notice;
+
+const KEY_CODES: &[KeyCode] = &[
+    KeyCode::Space, KeyCode::Apostrophe, KeyCode::Comma, KeyCode::Minus, KeyCode::Period,
+    KeyCode::Slash, KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9, KeyCode::Semicolon,
+    KeyCode::Equal, KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F,
+    KeyCode::G, KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V,
+    KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z, KeyCode::LeftBracket, KeyCode::Backslash,
+    KeyCode::RightBracket, KeyCode::GraveAccent, KeyCode::World1, KeyCode::World2, KeyCode::Escape,
+    KeyCode::Enter, KeyCode::Tab, KeyCode::Backspace, KeyCode::Insert, KeyCode::Delete,
+    KeyCode::Right, KeyCode::Left, KeyCode::Down, KeyCode::Up, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::Home, KeyCode::End, KeyCode::CapsLock, KeyCode::ScrollLock, KeyCode::NumLock,
+    KeyCode::PrintScreen, KeyCode::Pause, KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4,
+    KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11,
+    KeyCode::F12, KeyCode::F13, KeyCode::F14, KeyCode::F15, KeyCode::F16, KeyCode::F17,
+    KeyCode::F18, KeyCode::F19, KeyCode::F20, KeyCode::F21, KeyCode::F22, KeyCode::F23,
+    KeyCode::F24, KeyCode::F25, KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3,
+    KeyCode::Kp4, KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+    KeyCode::KpDecimal, KeyCode::KpDivide, KeyCode::KpMultiply, KeyCode::KpSubtract, KeyCode::KpAdd,
+    KeyCode::KpEnter, KeyCode::KpEqual, KeyCode::LeftShift, KeyCode::LeftControl, KeyCode::LeftAlt,
+    KeyCode::LeftSuper, KeyCode::RightShift, KeyCode::RightControl, KeyCode::RightAlt,
+    KeyCode::RightSuper, KeyCode::Menu,
+];
+
+// Gamepad buttons follow the standard layout of gamepad-rs
+const GAMEPAD_BUTTON_NAMES: &[(&'static str, Button)] = &[
+    ("GamepadA", Button::A),
+    ("GamepadB", Button::B),
+    ("GamepadX", Button::X),
+    ("GamepadY", Button::Y),
+    ("GamepadUp", Button::DPadUp),
+    ("GamepadDown", Button::DPadDown),
+    ("GamepadLeft", Button::DPadLeft),
+    ("GamepadRight", Button::DPadRight),
+    ("GamepadStart", Button::Start),
+    ("GamepadBack", Button::Back),
+    ("GamepadGuide", Button::Guide),
+    ("GamepadLeftThumb", Button::LeftStick),
+    ("GamepadRightThumb", Button::RightStick),
+    ("GamepadLeftShoulder", Button::LeftShoulder),
+    ("GamepadRightShoulder", Button::RightShoulder),
+];
+
+const GAMEPAD_TRIGGER_NAMES: &[(&'static str, Axis)] = &[
+    ("GamepadLeftTrigger", Axis::LeftTrigger),
+    ("GamepadRightTrigger", Axis::RightTrigger),
+];
+
+const MOUSE_BUTTON_NAMES: &[(&'static str, MouseButton)] = &[
+    ("MouseLeft", MouseButton::Left),
+    ("MouseRight", MouseButton::Right),
+    ("MouseMiddle", MouseButton::Middle),
+];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(Button),
+    GamepadTrigger(Axis),
+}
+
+impl InputBinding {
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(key) = KEY_CODES.iter().find(|key| format!("{:?}", key) == name) {
+            return Some(InputBinding::Key(*key));
+        }
+        if let Some((_, button)) = MOUSE_BUTTON_NAMES.iter().find(|(button_name, _)| *button_name == name) {
+            return Some(InputBinding::Mouse(*button));
+        }
+        if let Some((_, button)) = GAMEPAD_BUTTON_NAMES.iter().find(|(button_name, _)| *button_name == name) {
+            return Some(InputBinding::GamepadButton(*button));
+        }
+        if let Some((_, axis)) = GAMEPAD_TRIGGER_NAMES.iter().find(|(trigger_name, _)| *trigger_name == name) {
+            return Some(InputBinding::GamepadTrigger(*axis));
+        }
+        None
+    }
+
+    pub fn get_name(&self) -> String {
+        match self {
+            InputBinding::Key(key) => format!("{:?}", key),
+            InputBinding::Mouse(button) => MOUSE_BUTTON_NAMES.iter()
+                .find(|(_, other)| other == button)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_default(),
+            InputBinding::GamepadButton(button) => GAMEPAD_BUTTON_NAMES.iter()
+                .find(|(_, other)| other == button)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_default(),
+            InputBinding::GamepadTrigger(axis) => GAMEPAD_TRIGGER_NAMES.iter()
+                .find(|(_, other)| other == axis)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ActionBindingsData {
+    pub action: String,
+    pub bindings: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct ActionMap {
+    bindings: Vec<(String, Vec<InputBinding>)>,
+}
+
+impl ActionMap {
+    pub const MOVE_UP: &'static str = "move_up";
+    pub const MOVE_DOWN: &'static str = "move_down";
+    pub const MOVE_LEFT: &'static str = "move_left";
+    pub const MOVE_RIGHT: &'static str = "move_right";
+    pub const SPRINT: &'static str = "sprint";
+    pub const INTERACT: &'static str = "interact";
+    pub const PICK_UP: &'static str = "pick_up";
+    pub const PRIMARY_ABILITY: &'static str = "primary_ability";
+    pub const SECONDARY_ABILITY: &'static str = "secondary_ability";
+    pub const ABILITY_BAR_ACTIONS: &'static [&'static str] = &[
+        "ability_1",
+        "ability_2",
+        "ability_3",
+        "ability_4",
+        "ability_5",
+        "ability_6",
+    ];
+    pub const QUICK_USE_ACTIONS: &'static [&'static str] = &[
+        "quick_use_1",
+        "quick_use_2",
+        "quick_use_3",
+        "quick_use_4",
+    ];
+    pub const TOGGLE_CHARACTER_WINDOW: &'static str = "toggle_character_window";
+    pub const TOGGLE_INVENTORY_WINDOW: &'static str = "toggle_inventory_window";
+    pub const TOGGLE_SPELL_BOOK_WINDOW: &'static str = "toggle_spell_book_window";
+    pub const TOGGLE_SAVE_GAMES_WINDOW: &'static str = "toggle_save_games_window";
+    pub const TOGGLE_SETTINGS_WINDOW: &'static str = "toggle_settings_window";
+    pub const TOGGLE_QUEST_LOG_WINDOW: &'static str = "toggle_quest_log_window";
+    pub const QUICKSAVE: &'static str = "quicksave";
+    pub const QUICKLOAD: &'static str = "quickload";
+    pub const QUIT: &'static str = "quit";
+
+    const KEYMAP_FILE_PATH: &'static str = "keymap.json";
+
+    /// All actions, in the order they are listed in the settings window, with their labels
+    pub fn get_actions() -> Vec<(&'static str, &'static str)> {
+        vec!(
+            (Self::MOVE_UP, "Move up"),
+            (Self::MOVE_DOWN, "Move down"),
+            (Self::MOVE_LEFT, "Move left"),
+            (Self::MOVE_RIGHT, "Move right"),
+            (Self::SPRINT, "Sprint"),
+            (Self::INTERACT, "Interact"),
+            (Self::PICK_UP, "Pick up items"),
+            (Self::PRIMARY_ABILITY, "Primary ability"),
+            (Self::SECONDARY_ABILITY, "Secondary ability"),
+            (Self::ABILITY_BAR_ACTIONS[0], "Ability bar 1"),
+            (Self::ABILITY_BAR_ACTIONS[1], "Ability bar 2"),
+            (Self::ABILITY_BAR_ACTIONS[2], "Ability bar 3"),
+            (Self::ABILITY_BAR_ACTIONS[3], "Ability bar 4"),
+            (Self::ABILITY_BAR_ACTIONS[4], "Ability bar 5"),
+            (Self::ABILITY_BAR_ACTIONS[5], "Ability bar 6"),
+            (Self::QUICK_USE_ACTIONS[0], "Quick use 1"),
+            (Self::QUICK_USE_ACTIONS[1], "Quick use 2"),
+            (Self::QUICK_USE_ACTIONS[2], "Quick use 3"),
+            (Self::QUICK_USE_ACTIONS[3], "Quick use 4"),
+            (Self::TOGGLE_CHARACTER_WINDOW, "Character window"),
+            (Self::TOGGLE_INVENTORY_WINDOW, "Inventory window"),
+            (Self::TOGGLE_SPELL_BOOK_WINDOW, "Spell book window"),
+            (Self::TOGGLE_SAVE_GAMES_WINDOW, "Save games window"),
+            (Self::TOGGLE_SETTINGS_WINDOW, "Settings window"),
+            (Self::TOGGLE_QUEST_LOG_WINDOW, "Quest log window"),
+            (Self::QUICKSAVE, "Quicksave"),
+            (Self::QUICKLOAD, "Quickload"),
+            (Self::QUIT, "Quit"),
+        )
+    }
+
+    pub fn get_action_label(action: &str) -> &'static str {
+        Self::get_actions()
+            .into_iter()
+            .find(|(other, _)| *other == action)
+            .map(|(_, label)| label)
+            .unwrap_or("Unknown action")
+    }
+
+    /// Loads the keymap file, falling back to the default bindings for any action that is not in it
+    pub fn load() -> Self {
+        let mut action_map = Self::default();
+        let json = match fs::read_to_string(Self::KEYMAP_FILE_PATH) {
+            Ok(json) => json,
+            Err(_) => return action_map,
+        };
+        let data: Vec<ActionBindingsData> = match serde_json::from_str(&json) {
+            Ok(data) => data,
+            Err(err) => {
+                show_notice(&format!("Error when parsing keymap file '{}': {}", Self::KEYMAP_FILE_PATH, err));
+                return action_map;
+            }
+        };
+        for action_data in data {
+            let mut bindings = Vec::new();
+            for name in &action_data.bindings {
+                match InputBinding::from_name(name) {
+                    Some(binding) => bindings.push(binding),
+                    None => show_notice(&format!("Invalid input binding '{}' for action '{}'", name, action_data.action)),
+                }
+            }
+            match action_map.bindings.iter_mut().find(|(action, _)| *action == action_data.action) {
+                Some((_, existing)) => *existing = bindings,
+                None => show_notice(&format!("Invalid input action '{}'", action_data.action)),
+            }
+        }
+        action_map
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let data: Vec<ActionBindingsData> = self.bindings
+            .iter()
+            .map(|(action, bindings)| ActionBindingsData {
+                action: action.clone(),
+                bindings: bindings.iter().map(|binding| binding.get_name()).collect(),
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&data)?;
+        fs::write(Self::KEYMAP_FILE_PATH, json)
+    }
+
+    pub fn get_bindings(&self, action: &str) -> &[InputBinding] {
+        match self.bindings.iter().find(|(other, _)| other == action) {
+            Some((_, bindings)) => bindings,
+            None => &[],
+        }
+    }
+
+    /// Returns the action that the binding is already bound to, if any, other than the given action
+    pub fn find_conflict(&self, action: &str, binding: InputBinding) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(other, bindings)| other != action && bindings.contains(&binding))
+            .map(|(other, _)| other.clone())
+    }
+
+    /// Adds a binding to an action, removing it from any other action it was bound to.
+    /// The conflicting action is returned, so that it can be reported to the player
+    pub fn bind(&mut self, action: &str, binding: InputBinding) -> Option<String> {
+        let conflict = self.find_conflict(action, binding);
+        for (other, bindings) in &mut self.bindings {
+            if other == action {
+                if !bindings.contains(&binding) {
+                    bindings.push(binding);
+                }
+            } else {
+                bindings.retain(|other_binding| *other_binding != binding);
+            }
+        }
+        conflict
+    }
+
+    pub fn unbind(&mut self, action: &str, binding: InputBinding) {
+        if let Some((_, bindings)) = self.bindings.iter_mut().find(|(other, _)| other == action) {
+            bindings.retain(|other| *other != binding);
+        }
+    }
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        let defaults: &[(&str, &[&str])] = &[
+            (Self::MOVE_UP, &["Up", "W", "GamepadUp"]),
+            (Self::MOVE_DOWN, &["Down", "S", "GamepadDown"]),
+            (Self::MOVE_LEFT, &["Left", "A", "GamepadLeft"]),
+            (Self::MOVE_RIGHT, &["Right", "D", "GamepadRight"]),
+            (Self::SPRINT, &["LeftShift", "GamepadB"]),
+            (Self::INTERACT, &["E", "GamepadA"]),
+            (Self::PICK_UP, &["R", "GamepadX"]),
+            (Self::PRIMARY_ABILITY, &["MouseLeft", "GamepadRightTrigger"]),
+            (Self::SECONDARY_ABILITY, &["MouseRight", "GamepadLeftTrigger"]),
+            (Self::ABILITY_BAR_ACTIONS[0], &["Key1"]),
+            (Self::ABILITY_BAR_ACTIONS[1], &["Key2"]),
+            (Self::ABILITY_BAR_ACTIONS[2], &["Key3"]),
+            (Self::ABILITY_BAR_ACTIONS[3], &["Key4"]),
+            (Self::ABILITY_BAR_ACTIONS[4], &["Key5"]),
+            (Self::ABILITY_BAR_ACTIONS[5], &["Key6"]),
+            (Self::QUICK_USE_ACTIONS[0], &["Key7", "GamepadLeftShoulder"]),
+            (Self::QUICK_USE_ACTIONS[1], &["Key8", "GamepadRightShoulder"]),
+            (Self::QUICK_USE_ACTIONS[2], &["Key9"]),
+            (Self::QUICK_USE_ACTIONS[3], &["Key0"]),
+            (Self::TOGGLE_CHARACTER_WINDOW, &["C", "GamepadBack"]),
+            (Self::TOGGLE_INVENTORY_WINDOW, &["I", "GamepadY"]),
+            (Self::TOGGLE_SPELL_BOOK_WINDOW, &["B"]),
+            (Self::TOGGLE_SAVE_GAMES_WINDOW, &["F1"]),
+            (Self::TOGGLE_SETTINGS_WINDOW, &["O"]),
+            (Self::TOGGLE_QUEST_LOG_WINDOW, &["J"]),
+            (Self::QUICKSAVE, &["F5"]),
+            (Self::QUICKLOAD, &["F9"]),
+            (Self::QUIT, &["Escape", "Q"]),
+        ];
+        ActionMap {
+            bindings: defaults
+                .iter()
+                .map(|(action, names)| (
+                    action.to_string(),
+                    names.iter().filter_map(|name| InputBinding::from_name(name)).collect(),
+                ))
+                .collect(),
+        }
+    }
+}
@@ -142,6 +142,9 @@ pub struct ActorStats {
     pub willpower: u32,
     pub perception: u32,
     pub charisma: u32,
+    pub level: Option<u32>,
+    pub experience: Option<u32>,
+    pub attribute_points: Option<u32>,
     pub current_health: Option<f32>,
     pub max_health: Option<f32>,
     pub current_stamina: Option<f32>,
@@ -153,6 +156,11 @@ pub struct ActorStats {
     pub energy_regen: Option<f32>,
     pub carry_capacity: Option<f32>,
     pub move_speed: Option<f32>,
+    pub armor: Option<f32>,
+    pub kinetic_resistance: Option<f32>,
+    pub energy_resistance: Option<f32>,
+    pub fire_resistance: Option<f32>,
+    pub psychic_resistance: Option<f32>,
     pub is_static: Option<bool>,
 }
 
@@ -212,6 +220,9 @@ impl ActorStats {
             willpower: other.willpower,
             perception: other.perception,
             charisma: other.charisma,
+            level: Some(other.level),
+            experience: Some(other.experience),
+            attribute_points: Some(other.attribute_points),
             current_health: Some(other.current_health),
             max_health: Some(other.max_health),
             current_stamina: Some(other.current_stamina),
@@ -223,6 +234,11 @@ impl ActorStats {
             energy_regen: Some(other.energy_regen),
             carry_capacity: Some(other.carry_capacity),
             move_speed: Some(other.move_speed),
+            armor: Some(other.armor),
+            kinetic_resistance: Some(other.kinetic_resistance),
+            energy_resistance: Some(other.energy_resistance),
+            fire_resistance: Some(other.fire_resistance),
+            psychic_resistance: Some(other.psychic_resistance),
             is_static: Some(other.is_static),
         }
     }
@@ -236,6 +252,9 @@ impl ActorStats {
             willpower: self.willpower,
             perception: self.perception,
             charisma: self.charisma,
+            level: self.level.unwrap_or(1),
+            experience: self.experience.unwrap_or_default(),
+            attribute_points: self.attribute_points.unwrap_or_default(),
             current_health: self.current_health.unwrap_or_default(),
             max_health: self.max_health.unwrap_or_default(),
             current_stamina: self.current_stamina.unwrap_or_default(),
@@ -247,7 +266,13 @@ impl ActorStats {
             energy_regen: self.energy_regen.unwrap_or_default(),
             carry_capacity: self.carry_capacity.unwrap_or_default(),
             move_speed: self.move_speed.unwrap_or_default(),
+            armor: self.armor.unwrap_or_default(),
+            kinetic_resistance: self.kinetic_resistance.unwrap_or_default(),
+            energy_resistance: self.energy_resistance.unwrap_or_default(),
+            fire_resistance: self.fire_resistance.unwrap_or_default(),
+            psychic_resistance: self.psychic_resistance.unwrap_or_default(),
             is_static: self.is_static.unwrap_or_default(),
+            ..Default::default()
         };
         stats.update_derived(max_vitals);
         stats
@@ -264,6 +289,9 @@ impl Default for ActorStats {
             willpower: 0,
             perception: 0,
             charisma: 0,
+            level: Some(1),
+            experience: Some(0),
+            attribute_points: Some(0),
             current_health: Some(1.0),
             max_health: Some(1.0),
             current_stamina: Some(0.0),
@@ -275,11 +303,69 @@ impl Default for ActorStats {
             energy_regen: Some(0.0),
             carry_capacity: Some(0.0),
             move_speed: Some(0.0),
+            armor: Some(0.0),
+            kinetic_resistance: Some(0.0),
+            energy_resistance: Some(0.0),
+            fire_resistance: Some(0.0),
+            psychic_resistance: Some(0.0),
             is_static: Some(true),
         }
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActorControllerKind {
+    pub kind: String,
+    pub player_id: Option<u32>,
+}
+
+impl ActorControllerKind {
+    pub const PLAYER_KIND: &'static str = "player";
+    pub const COMPUTER_KIND: &'static str = "computer";
+    pub const NONE_KIND: &'static str = "none";
+
+    pub fn from(other: crate::ActorControllerKind) -> Self {
+        match other {
+            crate::ActorControllerKind::Player { id } => ActorControllerKind {
+                kind: Self::PLAYER_KIND.to_string(),
+                player_id: Some(id),
+            },
+            crate::ActorControllerKind::Computer => ActorControllerKind {
+                kind: Self::COMPUTER_KIND.to_string(),
+                player_id: None,
+            },
+            crate::ActorControllerKind::None => ActorControllerKind {
+                kind: Self::NONE_KIND.to_string(),
+                player_id: None,
+            },
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.kind == Self::PLAYER_KIND {
+            if self.player_id.is_none() {
+                return Err("Player controller has no player id".to_string());
+            }
+        } else if self.kind != Self::COMPUTER_KIND && self.kind != Self::NONE_KIND {
+            return Err(format!("Invalid controller kind '{}'", self.kind));
+        }
+        Ok(())
+    }
+
+    pub fn to_controller_kind(&self) -> crate::ActorControllerKind {
+        if self.kind == Self::PLAYER_KIND {
+            crate::ActorControllerKind::Player { id: self.player_id.unwrap() }
+        } else if self.kind == Self::COMPUTER_KIND {
+            crate::ActorControllerKind::Computer
+        } else if self.kind == Self::NONE_KIND {
+            crate::ActorControllerKind::None
+        } else {
+            assert!(false, "Invalid controller kind '{}'", self.kind);
+            crate::ActorControllerKind::None
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Color {
     pub r: f32,
@@ -14,9 +14,15 @@ pub use globals::{
     get_global,
     set_global,
     try_get_global,
+    get_global_mut,
+    try_get_global_mut,
 };
 use globals::LocalPlayer;
-pub use input::get_mouse_position;
+pub use input::{
+    get_mouse_position,
+    ActionMap,
+    InputState,
+};
 pub use map::{
     Map,
 };
@@ -31,6 +37,7 @@ use nodes::{
     ItemParams,
 };
 use physics::Collider;
+use gamepad_rs::ControllerContext;
 use render::{
     HorizontalAlignment,
 };
@@ -38,14 +45,36 @@ pub use render::text::draw_aligned_text;
 pub use resources::{
     Resources,
 };
+pub use save_game::SaveGame;
+pub use settings::Settings;
+pub use factions::{
+    Factions,
+    FactionStance,
+};
+pub use notices::{
+    Notices,
+    show_notice,
+};
 
 use crate::nodes::actor::{ActorDrawBuffer, ActorStats};
 use crate::nodes::item::ItemDrawBuffer;
-use crate::nodes::ContinuousBeams;
+use crate::nodes::{
+    AudioManager,
+    CombatText,
+    ContinuousBeams,
+    AreaEffects,
+    Corpses,
+    Container,
+    Containers,
+};
 
 mod resources;
 mod globals;
 mod map;
+mod save_game;
+mod settings;
+mod factions;
+mod notices;
 
 pub mod nodes;
 pub mod render;
@@ -87,6 +116,8 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
+    set_global(Notices::new());
+
     let load_resources = start_coroutine(async move {
         let resources = Resources::new().await.unwrap();
         set_global(resources);
@@ -114,12 +145,27 @@ async fn main() {
             id: 0,
         });
 
+        set_global(Settings::load());
+        set_global(Factions::new());
+
+        set_global(ActionMap::load());
+        set_global(InputState::new());
+        match ControllerContext::new() {
+            Some(gamepads) => set_global(gamepads),
+            None => show_notice("Unable to initialize gamepad support"),
+        }
+
         let map = Map::new(uvec2(16, 16), "assets/maps/map_01.json").await;
         let player_spawn = map.get_spawn_point(Map::PLAYER_SPAWN_POINT_NAME);
+        let map_path = map.path.clone();
         let resources = get_global::<Resources>();
         for (_, item) in &map.items {
-            Item::add_node(item.position, resources.get_item(&item.id).clone());
+            Item::add_node(item.position, resources.get_item(&item.id).clone(), item.quantity);
         }
+        let containers: Vec<Container> = map.containers
+            .values()
+            .map(|container| Container::from_map_container(container))
+            .collect();
 
         GameState::add_node(map);
 
@@ -129,6 +175,17 @@ async fn main() {
 
         Projectiles::add_node();
         ContinuousBeams::add_node();
+        AreaEffects::add_node();
+
+        Corpses::add_node();
+
+        {
+            let node = Containers::add_node();
+            let mut node = scene::get_node(node);
+            for container in containers {
+                node.spawn(container);
+            }
+        }
 
         scene::add_node(generic_actor(
             "Player Actor",
@@ -138,31 +195,60 @@ async fn main() {
             Some(0),
         ));
 
-        scene::add_node(generic_actor(
+        let mut friendly_actor = generic_actor(
             "Friendly Actor",
             vec2(225.0, 375.0),
             2,
             &["player_faction".to_string()],
             None,
-        ));
+        );
+        friendly_actor.dialogue = Some("friendly_actor_greeting".to_string());
+        scene::add_node(friendly_actor);
 
-        scene::add_node(generic_actor(
+        let mut enemy_actor = generic_actor(
             "Enemy Actor",
             vec2(300.0, 350.0),
             1,
-            &[],
+            &["raiders".to_string()],
             None,
-        ));
+        );
+        enemy_actor.loot_table = Some("raider_loot".to_string());
+        scene::add_node(enemy_actor);
 
         ActorDrawBuffer::add_node();
+
+        CombatText::add_node();
+
+        let audio_manager = AudioManager::add_node();
+        scene::get_node(audio_manager).play_map_music(&map_path);
     }
 
     loop {
-        {
-            let game_state = scene::find_node_by_type::<GameState>().unwrap();
+        input::update_input();
+
+        let (save_slot, load_slot) = {
+            let mut game_state = scene::find_node_by_type::<GameState>().unwrap();
             if game_state.should_quit {
                 break;
             }
+            (game_state.should_save_to.take(), game_state.should_load_from.take())
+        };
+
+        if let Some(slot) = save_slot {
+            if let Err(err) = SaveGame::create().save(&slot) {
+                show_notice(&format!("Unable to save game to slot '{}': {}", slot, err));
+            }
+        }
+
+        if let Some(slot) = load_slot {
+            match SaveGame::load(&slot) {
+                Ok(save_game) => {
+                    save_game.restore().await;
+                    next_frame().await;
+                    continue;
+                }
+                Err(err) => show_notice(&err),
+            }
         }
 
         gui::draw_gui();
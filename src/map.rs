@@ -5,26 +5,55 @@ use macroquad::{
 };
 
 use macroquad_tiled as tiled;
-use crate::{get_global, Resources};
+use crate::{get_global, show_notice, Resources};
+use crate::nodes::actor::ActorInventoryEntryParams;
 use crate::physics::{Collider, beam_collision_check};
 
+mod navigation;
+
+pub use navigation::NavigationGrid;
+
 #[derive(Clone)]
 pub struct SpawnPoint {
     pub id: String,
     pub position: Vec2,
 }
 
+/// A rectangular area of the map, used by quest objectives
+#[derive(Clone)]
+pub struct MapRegion {
+    pub id: String,
+    pub rect: Rect,
+}
+
+/// A container placed on the map. The `label` property is the name shown to the player and
+/// the `items` property is a comma separated list of the ids of the items that it starts out with,
+/// each optionally followed by a colon and a quantity, like `energy_cell:5`.
+#[derive(Clone)]
+pub struct MapContainer {
+    pub id: String,
+    pub name: String,
+    pub rect: Rect,
+    pub items: Vec<ActorInventoryEntryParams>,
+}
+
+/// An item placed on the map. The `quantity` property is optional and defaults to one
 #[derive(Clone)]
 pub struct MapItem {
     pub id: String,
     pub position: Vec2,
+    pub quantity: u32,
 }
 
 pub struct Map {
+    pub path: String,
     pub map_size: UVec2,
     pub tile_size: UVec2,
     pub items: HashMap<String, MapItem>,
     pub spawn_points: HashMap<String, SpawnPoint>,
+    pub regions: HashMap<String, MapRegion>,
+    pub containers: HashMap<String, MapContainer>,
+    navigation_grid: NavigationGrid,
     tiled_map: tiled::Map,
 }
 
@@ -34,6 +63,12 @@ impl Map {
     pub const BARRIERS_LAYER: &'static str = "barriers";
     pub const ITEMS_LAYER: &'static str = "items";
     pub const SPAWN_POINTS_LAYER: &'static str = "spawn_points";
+    pub const REGIONS_LAYER: &'static str = "regions";
+    pub const CONTAINERS_LAYER: &'static str = "containers";
+
+    const CONTAINER_LABEL_PROPERTY: &'static str = "label";
+    const CONTAINER_ITEMS_PROPERTY: &'static str = "items";
+    const ITEM_QUANTITY_PROPERTY: &'static str = "quantity";
 
     pub const PLAYER_SPAWN_POINT_NAME: &'static str = "player_spawn";
 
@@ -56,12 +91,16 @@ impl Map {
 
         let mut items = HashMap::new();
         for item in tiled_map.layers[Map::ITEMS_LAYER].objects.clone() {
+            let quantity = item.properties.get(Self::ITEM_QUANTITY_PROPERTY)
+                .and_then(|quantity| Self::parse_quantity(quantity))
+                .unwrap_or(1);
             items.insert(item.name.clone(), MapItem {
                 id: item.name,
                 position: vec2(
                     item.world_x,
                     item.world_y,
                 ),
+                quantity,
             });
         }
 
@@ -76,15 +115,119 @@ impl Map {
             });
         }
 
+        // The regions layer is optional, as not all maps have quests that use it
+        let mut regions = HashMap::new();
+        if let Some(layer) = tiled_map.layers.get(Map::REGIONS_LAYER) {
+            for region in layer.objects.clone() {
+                regions.insert(region.name.clone(), MapRegion {
+                    id: region.name,
+                    rect: Rect::new(
+                        region.world_x,
+                        region.world_y,
+                        region.world_w,
+                        region.world_h,
+                    ),
+                });
+            }
+        }
+
+        // The containers layer is optional, as well
+        let mut containers = HashMap::new();
+        if let Some(layer) = tiled_map.layers.get(Map::CONTAINERS_LAYER) {
+            for container in layer.objects.clone() {
+                let name = container.properties.get(Self::CONTAINER_LABEL_PROPERTY)
+                    .cloned()
+                    .unwrap_or(container.name.clone());
+                let items = container.properties.get(Self::CONTAINER_ITEMS_PROPERTY)
+                    .map(|items| items
+                        .split(',')
+                        .map(|item| item.trim())
+                        .filter(|item| item.is_empty() == false)
+                        .map(|item| {
+                            let mut parts = item.splitn(2, ':');
+                            ActorInventoryEntryParams {
+                                item_id: parts.next().unwrap().trim().to_string(),
+                                quantity: parts.next().and_then(|quantity| Self::parse_quantity(quantity)),
+                            }
+                        })
+                        .collect())
+                    .unwrap_or_default();
+                containers.insert(container.name.clone(), MapContainer {
+                    id: container.name,
+                    name,
+                    rect: Rect::new(
+                        container.world_x,
+                        container.world_y,
+                        container.world_w,
+                        container.world_h,
+                    ),
+                    items,
+                });
+            }
+        }
+
+        let navigation_grid = NavigationGrid::new(
+            map_size,
+            tile_size,
+            Self::get_navigation_data(&tiled_map, map_size),
+        );
+
         Map {
+            path: path.to_string(),
             map_size,
             tile_size,
             tiled_map,
             items,
             spawn_points,
+            regions,
+            containers,
+            navigation_grid,
         }
     }
 
+    fn parse_quantity(quantity: &str) -> Option<u32> {
+        match quantity.trim().parse() {
+            Ok(quantity) => Some(quantity),
+            Err(_) => {
+                show_notice(&format!("Invalid item quantity '{}' in Tiled map", quantity));
+                None
+            }
+        }
+    }
+
+    fn get_navigation_data(tiled_map: &tiled::Map, map_size: UVec2) -> Vec<bool> {
+        let mut data = Vec::with_capacity((map_size.x * map_size.y) as usize);
+        for y in 0..map_size.y {
+            for x in 0..map_size.x {
+                data.push(tiled_map.get_tile(Self::SOLIDS_LAYER, x, y).is_some()
+                    || tiled_map.get_tile(Self::BARRIERS_LAYER, x, y).is_some());
+            }
+        }
+        data
+    }
+
+    pub fn find_path(&mut self, start: Vec2, goal: Vec2, collider: Option<Collider>) -> Option<Vec<Vec2>> {
+        self.navigation_grid.find_path(start, goal, collider)
+    }
+
+    pub fn is_line_of_sight(&mut self, start: Vec2, end: Vec2, collider: Option<Collider>) -> bool {
+        self.navigation_grid.is_line_of_sight(start, end, collider)
+    }
+
+    /// Rebuilds the navigation grid from the solids and barriers layers.
+    /// This should be called whenever tiles on these layers are changed.
+    pub fn invalidate_navigation(&mut self) {
+        let data = Self::get_navigation_data(&self.tiled_map, self.map_size);
+        self.navigation_grid.invalidate(data);
+    }
+
+    /// Updates the navigation grid for a single tile, on the solids or barriers layers, that has been changed
+    pub fn invalidate_navigation_at(&mut self, coords: UVec2) {
+        let is_blocked = self.tiled_map.get_tile(Self::SOLIDS_LAYER, coords.x, coords.y).is_some()
+            || self.tiled_map.get_tile(Self::BARRIERS_LAYER, coords.x, coords.y).is_some();
+        self.navigation_grid.set_blocked(coords, is_blocked);
+    }
+
     pub fn solid_at(&self, position: Vec2, include_barriers: bool) -> bool {
         let coords = uvec2(
             position.x as u32 / self.tile_size.x,
@@ -207,4 +350,11 @@ impl Map {
     pub fn get_spawn_point(&self, id: &str) -> SpawnPoint {
         self.try_get_spawn_point(id).unwrap()
     }
+
+    pub fn try_get_region(&self, id: &str) -> Option<MapRegion> {
+        match self.regions.get(id) {
+            Some(region) => Some(region.clone()),
+            None => None,
+        }
+    }
 }
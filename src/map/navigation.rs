@@ -0,0 +1,321 @@
+use std::{
+    cmp::Ordering,
+    collections::{
+        BinaryHeap,
+        HashMap,
+    },
+};
+
+use macroquad::prelude::*;
+
+use crate::physics::Collider;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct OpenNode {
+    cost: u32,
+    index: usize,
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed, so that the BinaryHeap becomes a min-heap
+        other.cost.cmp(&self.cost).then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub struct NavigationGrid {
+    pub size: UVec2,
+    pub tile_size: UVec2,
+    blocked: Vec<bool>,
+    inflated: HashMap<(u32, u32), Vec<bool>>,
+}
+
+impl NavigationGrid {
+    const STRAIGHT_COST: u32 = 10;
+    const DIAGONAL_COST: u32 = 14;
+
+    const MAX_SEARCH_NODES: usize = 8192;
+    const MAX_SNAP_DISTANCE: i32 = 3;
+
+    const LINE_OF_SIGHT_STEP_FACTOR: f32 = 0.25;
+
+    pub fn new(size: UVec2, tile_size: UVec2, blocked: Vec<bool>) -> Self {
+        assert_eq!(blocked.len(), (size.x * size.y) as usize, "Navigation grid data does not match grid size");
+        NavigationGrid {
+            size,
+            tile_size,
+            blocked,
+            inflated: HashMap::new(),
+        }
+    }
+
+    pub fn set_blocked(&mut self, coords: UVec2, is_blocked: bool) {
+        if coords.x < self.size.x && coords.y < self.size.y {
+            let index = self.to_index(coords);
+            if self.blocked[index] != is_blocked {
+                self.blocked[index] = is_blocked;
+                self.inflated.clear();
+            }
+        }
+    }
+
+    pub fn invalidate(&mut self, blocked: Vec<bool>) {
+        assert_eq!(blocked.len(), self.blocked.len(), "Navigation grid data does not match grid size");
+        self.blocked = blocked;
+        self.inflated.clear();
+    }
+
+    pub fn to_coords(&self, position: Vec2) -> Option<UVec2> {
+        if position.x < 0.0 || position.y < 0.0 {
+            return None;
+        }
+        let coords = uvec2(
+            position.x as u32 / self.tile_size.x,
+            position.y as u32 / self.tile_size.y,
+        );
+        if coords.x < self.size.x && coords.y < self.size.y {
+            Some(coords)
+        } else {
+            None
+        }
+    }
+
+    pub fn to_position(&self, coords: UVec2) -> Vec2 {
+        vec2(
+            (coords.x * self.tile_size.x + self.tile_size.x / 2) as f32,
+            (coords.y * self.tile_size.y + self.tile_size.y / 2) as f32,
+        )
+    }
+
+    /// Finds a path from `start` to `goal` that a body with the given `collider` can travel,
+    /// returning a list of waypoints, excluding the start position and including the goal.
+    /// Positions are body positions, so the collider offset is accounted for internally.
+    pub fn find_path(&mut self, start: Vec2, goal: Vec2, collider: Option<Collider>) -> Option<Vec<Vec2>> {
+        let (offset, clearance) = self.get_clearance(collider);
+        let start_coords = self.to_coords(start + offset)?;
+        let goal_coords = self.to_coords(goal + offset)?;
+
+        self.build_inflated(clearance);
+        let grid = &self.inflated[&clearance];
+
+        let start_coords = self.snap_to_passable(grid, start_coords)?;
+        let goal_coords = self.snap_to_passable(grid, goal_coords)?;
+
+        let start_index = self.to_index(start_coords);
+        let goal_index = self.to_index(goal_coords);
+
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut costs: HashMap<usize, u32> = HashMap::new();
+        let mut open = BinaryHeap::new();
+
+        costs.insert(start_index, 0);
+        open.push(OpenNode { cost: self.heuristic(start_coords, goal_coords), index: start_index });
+
+        let mut visited = 0;
+        let mut is_found = false;
+        while let Some(OpenNode { index, .. }) = open.pop() {
+            if index == goal_index {
+                is_found = true;
+                break;
+            }
+            visited += 1;
+            if visited > Self::MAX_SEARCH_NODES {
+                break;
+            }
+            let coords = self.from_index(index);
+            let cost = costs[&index];
+            for (neighbor, step_cost) in self.get_neighbors(grid, coords) {
+                let neighbor_index = self.to_index(neighbor);
+                let new_cost = cost + step_cost;
+                if costs.get(&neighbor_index).map(|old_cost| new_cost < *old_cost).unwrap_or(true) {
+                    costs.insert(neighbor_index, new_cost);
+                    came_from.insert(neighbor_index, index);
+                    open.push(OpenNode {
+                        cost: new_cost + self.heuristic(neighbor, goal_coords),
+                        index: neighbor_index,
+                    });
+                }
+            }
+        }
+
+        if !is_found {
+            return None;
+        }
+
+        let mut tiles = vec!(goal_index);
+        let mut current = goal_index;
+        while let Some(previous) = came_from.get(&current) {
+            tiles.push(*previous);
+            current = *previous;
+        }
+        tiles.reverse();
+
+        let mut waypoints: Vec<Vec2> = tiles
+            .into_iter()
+            .map(|index| self.to_position(self.from_index(index)))
+            .collect();
+        waypoints[0] = start + offset;
+        let last = waypoints.len() - 1;
+        if self.is_line_walkable(grid, waypoints[last], goal + offset) {
+            waypoints[last] = goal + offset;
+        }
+
+        let mut path = self.smooth_path(grid, waypoints);
+        path.remove(0);
+        Some(path.into_iter().map(|waypoint| waypoint - offset).collect())
+    }
+
+    /// Returns `true` if a body with the given `collider` can move in a straight line between the two positions
+    pub fn is_line_of_sight(&mut self, start: Vec2, end: Vec2, collider: Option<Collider>) -> bool {
+        let (offset, clearance) = self.get_clearance(collider);
+        self.build_inflated(clearance);
+        self.is_line_walkable(&self.inflated[&clearance], start + offset, end + offset)
+    }
+
+    fn get_clearance(&self, collider: Option<Collider>) -> (Vec2, (u32, u32)) {
+        let (offset, half_extents) = match collider {
+            Some(Collider::Rectangle(rect)) => (vec2(rect.x, rect.y), vec2(rect.w / 2.0, rect.h / 2.0)),
+            Some(Collider::Circle(circle)) => (vec2(circle.x, circle.y), vec2(circle.r, circle.r)),
+            None => (Vec2::ZERO, Vec2::ZERO),
+        };
+        let tile_size = vec2(self.tile_size.x as f32, self.tile_size.y as f32);
+        let clearance = (
+            ((half_extents.x - tile_size.x / 2.0) / tile_size.x).ceil().max(0.0) as u32,
+            ((half_extents.y - tile_size.y / 2.0) / tile_size.y).ceil().max(0.0) as u32,
+        );
+        (offset, clearance)
+    }
+
+    fn build_inflated(&mut self, clearance: (u32, u32)) {
+        if self.inflated.contains_key(&clearance) {
+            return;
+        }
+        let mut grid = self.blocked.clone();
+        if clearance != (0, 0) {
+            for y in 0..self.size.y {
+                for x in 0..self.size.x {
+                    if !self.blocked[self.to_index(uvec2(x, y))] {
+                        continue;
+                    }
+                    let min_x = x.saturating_sub(clearance.0);
+                    let min_y = y.saturating_sub(clearance.1);
+                    let max_x = (x + clearance.0).min(self.size.x - 1);
+                    let max_y = (y + clearance.1).min(self.size.y - 1);
+                    for iy in min_y..max_y + 1 {
+                        for ix in min_x..max_x + 1 {
+                            grid[self.to_index(uvec2(ix, iy))] = true;
+                        }
+                    }
+                }
+            }
+        }
+        self.inflated.insert(clearance, grid);
+    }
+
+    fn snap_to_passable(&self, grid: &[bool], coords: UVec2) -> Option<UVec2> {
+        if !grid[self.to_index(coords)] {
+            return Some(coords);
+        }
+        for distance in 1..Self::MAX_SNAP_DISTANCE + 1 {
+            for dy in -distance..distance + 1 {
+                for dx in -distance..distance + 1 {
+                    if dx.abs() != distance && dy.abs() != distance {
+                        continue;
+                    }
+                    let x = coords.x as i32 + dx;
+                    let y = coords.y as i32 + dy;
+                    if x < 0 || y < 0 || x >= self.size.x as i32 || y >= self.size.y as i32 {
+                        continue;
+                    }
+                    let candidate = uvec2(x as u32, y as u32);
+                    if !grid[self.to_index(candidate)] {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn get_neighbors(&self, grid: &[bool], coords: UVec2) -> Vec<(UVec2, u32)> {
+        let mut neighbors = Vec::with_capacity(8);
+        let is_free = |x: i32, y: i32| -> bool {
+            x >= 0 && y >= 0 && x < self.size.x as i32 && y < self.size.y as i32
+                && !grid[self.to_index(uvec2(x as u32, y as u32))]
+        };
+        let (x, y) = (coords.x as i32, coords.y as i32);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)] {
+            if !is_free(x + dx, y + dy) {
+                continue;
+            }
+            if dx != 0 && dy != 0 {
+                // don't cut corners
+                if !is_free(x + dx, y) || !is_free(x, y + dy) {
+                    continue;
+                }
+                neighbors.push((uvec2((x + dx) as u32, (y + dy) as u32), Self::DIAGONAL_COST));
+            } else {
+                neighbors.push((uvec2((x + dx) as u32, (y + dy) as u32), Self::STRAIGHT_COST));
+            }
+        }
+        neighbors
+    }
+
+    fn heuristic(&self, a: UVec2, b: UVec2) -> u32 {
+        let dx = (a.x as i32 - b.x as i32).abs() as u32;
+        let dy = (a.y as i32 - b.y as i32).abs() as u32;
+        Self::STRAIGHT_COST * (dx + dy) - (2 * Self::STRAIGHT_COST - Self::DIAGONAL_COST) * dx.min(dy)
+    }
+
+    fn smooth_path(&self, grid: &[bool], waypoints: Vec<Vec2>) -> Vec<Vec2> {
+        if waypoints.len() <= 2 {
+            return waypoints;
+        }
+        let mut path = vec!(waypoints[0]);
+        let mut anchor = 0;
+        while anchor < waypoints.len() - 1 {
+            let mut next = anchor + 1;
+            for i in (anchor + 2..waypoints.len()).rev() {
+                if self.is_line_walkable(grid, waypoints[anchor], waypoints[i]) {
+                    next = i;
+                    break;
+                }
+            }
+            path.push(waypoints[next]);
+            anchor = next;
+        }
+        path
+    }
+
+    fn is_line_walkable(&self, grid: &[bool], start: Vec2, end: Vec2) -> bool {
+        let step = self.tile_size.x.min(self.tile_size.y) as f32 * Self::LINE_OF_SIGHT_STEP_FACTOR;
+        let distance = start.distance(end);
+        let steps = (distance / step).ceil() as u32;
+        for i in 0..steps + 1 {
+            let position = start.lerp(end, if steps > 0 { i as f32 / steps as f32 } else { 0.0 });
+            match self.to_coords(position) {
+                Some(coords) => {
+                    if grid[self.to_index(coords)] {
+                        return false;
+                    }
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn to_index(&self, coords: UVec2) -> usize {
+        (coords.y * self.size.x + coords.x) as usize
+    }
+
+    fn from_index(&self, index: usize) -> UVec2 {
+        uvec2(index as u32 % self.size.x, index as u32 / self.size.x)
+    }
+}
@@ -5,12 +5,28 @@ pub use actor::{
     ActorControllerKind,
     ActorInventory,
     ActorParams,
+    ActorSoundEffectsParams,
 };
 pub use camera::Camera;
 pub use game_state::GameState;
 pub use projectiles::Projectiles;
 pub use continuous_beams::ContinuousBeams;
+pub use area_effects::AreaEffects;
+pub use combat_text::CombatText;
+pub use audio_manager::AudioManager;
+pub use corpses::{
+    CorpseParams,
+    Corpse,
+    Corpses,
+};
+pub use containers::{
+    ContainerParams,
+    Container,
+    Containers,
+    TransferTarget,
+};
 pub use item::{
+    ConsumableParams,
     ItemParams,
     Item,
 };
@@ -21,3 +37,8 @@ pub mod actor;
 pub mod camera;
 pub mod item;
 pub mod continuous_beams;
+pub mod area_effects;
+pub mod combat_text;
+pub mod audio_manager;
+pub mod corpses;
+pub mod containers;
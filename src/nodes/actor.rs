@@ -17,25 +17,96 @@ use macroquad::{
     prelude::*,
 };
 
-use crate::input::apply_local_player_input;
+use crate::input::{apply_local_player_input, rumble_gamepad};
 
 mod controller;
 mod inventory;
+mod equipment;
 mod ability;
+mod damage;
+mod melee;
+mod spell;
+mod feat;
+mod behavior;
 mod draw_buffer;
 mod stats;
+mod status_effect;
+mod dialogue;
+mod quest;
+mod loot_table;
 
-pub use stats::ActorStats;
+pub use stats::{
+    ActorLevelParams,
+    ActorStats,
+    ActorStatModifiers,
+    ActorStatModifierKind,
+    ActorStatModifier,
+};
+pub use status_effect::{
+    StatusEffectParams,
+    StatusEffect,
+};
 pub use controller::{
     ActorControllerKind,
     ActorController,
 };
 
-pub use inventory::ActorInventory;
+pub use inventory::{
+    ActorInventory,
+    ActorInventoryEntry,
+    ActorInventoryEntryParams,
+    ActorQuickUseBar,
+};
+pub use equipment::{
+    ActorEquipmentParams,
+    ActorEquipment,
+    EquipmentSlot,
+};
 pub use ability::{
     ActorAbilityParams,
     ActorAbility,
 };
+pub use damage::{
+    Damage,
+    DamageEvent,
+    KillEvent,
+};
+pub use melee::MeleeAttack;
+pub use spell::{
+    SpellParams,
+    AbilityBarSlot,
+    ActorAbilityBar,
+};
+pub use feat::{
+    FeatParams,
+    AbilityModifiers,
+};
+pub use behavior::{
+    ActorBehaviorParams,
+    ActorBehavior,
+    BehaviorStatus,
+};
+pub use dialogue::{
+    DialogueParams,
+    DialogueNode,
+    DialogueChoice,
+    DialogueRequirement,
+    DialogueAction,
+    Dialogue,
+};
+pub use quest::{
+    QuestParams,
+    QuestStageParams,
+    QuestObjectiveParams,
+    QuestRewardsParams,
+    QuestProgressParams,
+    QuestEvent,
+    Quest,
+};
+pub use loot_table::{
+    LootTableParams,
+    LootEntryParams,
+};
 
 pub use draw_buffer::ActorDrawBuffer;
 
@@ -48,10 +119,26 @@ use crate::{get_global, render::{
     PhysicsBody,
     PhysicsObject,
     Collider,
-}, json, generate_id, draw_aligned_text};
-use crate::nodes::Item;
+}, json, generate_id, draw_aligned_text, Resources, Factions, FactionStance, get_global_mut, show_notice};
+use crate::nodes::{
+    AudioManager,
+    CombatText,
+    GameState,
+    Item,
+    Corpses,
+    Corpse,
+    Containers,
+    TransferTarget,
+};
 use crate::render::Viewport;
 
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ActorSoundEffectsParams {
+    pub hit: Option<String>,
+    pub death: Option<String>,
+    pub footsteps: Option<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ActorParams {
     pub id: String,
@@ -60,7 +147,18 @@ pub struct ActorParams {
     pub stats: json::ActorStats,
     pub factions: Vec<String>,
     pub collider: Option<json::Collider>,
-    pub inventory: Vec<String>,
+    pub inventory: Vec<ActorInventoryEntryParams>,
+    pub equipment: Option<ActorEquipmentParams>,
+    pub spells: Option<Vec<String>>,
+    pub feats: Option<Vec<String>>,
+    pub ability_bar: Option<Vec<Option<String>>>,
+    pub quick_use_bar: Option<Vec<Option<String>>>,
+    pub behavior: Option<ActorBehaviorParams>,
+    pub dialogue: Option<String>,
+    pub quests: Option<Vec<QuestProgressParams>>,
+    pub gold: Option<u32>,
+    pub loot_table: Option<String>,
+    pub sound_effects: Option<ActorSoundEffectsParams>,
     pub sprite_animation_params: SpriteAnimationParams,
 }
 
@@ -74,11 +172,35 @@ impl Default for ActorParams {
             factions: Vec::new(),
             collider: None,
             inventory: Vec::new(),
+            equipment: None,
+            spells: None,
+            feats: None,
+            ability_bar: None,
+            quick_use_bar: None,
+            behavior: None,
+            dialogue: None,
+            quests: None,
+            gold: None,
+            loot_table: None,
+            sound_effects: None,
             sprite_animation_params: Default::default(),
         }
     }
 }
 
+impl ActorParams {
+    /// Checks that the items held by the actor exist and that its equipment fits its slots
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        for entry in &self.inventory {
+            entry.validate(resources)?;
+        }
+        if let Some(equipment) = &self.equipment {
+            equipment.validate(resources)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct Actor {
     pub id: String,
@@ -88,9 +210,30 @@ pub struct Actor {
     pub body: PhysicsBody,
     sprite_animation: SpriteAnimationPlayer,
     pub inventory: ActorInventory,
+    pub equipment: ActorEquipment,
+    pub status_effects: Vec<StatusEffect>,
+    pub damage_events: Vec<DamageEvent>,
     pub primary_ability: Option<ActorAbility>,
     pub secondary_ability: Option<ActorAbility>,
+    pub spells: Vec<String>,
+    pub feats: Vec<String>,
+    pub ability_bar: ActorAbilityBar,
+    pub quick_use_bar: ActorQuickUseBar,
+    pub melee_attack: Option<MeleeAttack>,
+    /// The direction the actor last aimed or moved in
+    pub facing_direction: Vec2,
+    pub knockback: Vec2,
     pub controller: ActorController,
+    pub behavior: Option<ActorBehavior>,
+    pub dialogue: Option<String>,
+    pub quests: Vec<Quest>,
+    pub gold: u32,
+    pub loot_table: Option<String>,
+    pub kill_events: Vec<KillEvent>,
+    pub sound_effects: ActorSoundEffectsParams,
+    hit_sound_timer: f32,
+    death_timer: Option<f32>,
+    footstep_distance: f32,
 }
 
 impl Actor {
@@ -103,6 +246,22 @@ impl Actor {
     const SPRINT_SPEED_FACTOR: f32 = 2.0;
     const SPRINT_STAMINA_COST: f32 = 10.0;
 
+    const KNOCKBACK_DAMPING: f32 = 0.8;
+    const MIN_KNOCKBACK: f32 = 0.1;
+
+    const EXPERIENCE_PER_LEVEL_KILLED: u32 = 50;
+
+    // The time from when an actor dies until it is replaced by a corpse, which should cover the death animation
+    const DEATH_DURATION: f32 = 1.0;
+
+    // Damage over time is dealt every frame, so hit sounds are limited to one per interval
+    const HIT_SOUND_INTERVAL: f32 = 0.5;
+    // The rumble of the local player's gamepad scales with the share of max health lost
+    const DAMAGE_RUMBLE_SCALE: f32 = 4.0;
+    const MIN_DAMAGE_RUMBLE: f32 = 0.3;
+    const DAMAGE_RUMBLE_DURATION: f32 = 0.2;
+    const FOOTSTEP_DISTANCE: f32 = 16.0;
+
     const PICK_UP_RADIUS: f32 = 36.0;
     const INTERACT_RADIUS: f32 = 36.0;
 
@@ -112,7 +271,7 @@ impl Actor {
             None => None,
         };
         let body = PhysicsBody::new(position, 0.0, collider);
-        Actor {
+        let mut actor = Actor {
             id: generate_id(),
             name: params.name,
             stats: params.stats.to_actor_stats(max_vitals),
@@ -120,10 +279,55 @@ impl Actor {
             body,
             sprite_animation: SpriteAnimationPlayer::new(params.sprite_animation_params.clone()),
             inventory: ActorInventory::new(&params.inventory),
+            equipment: ActorEquipment::new(&params.equipment.unwrap_or_default()),
+            status_effects: Vec::new(),
+            damage_events: Vec::new(),
             primary_ability: None,
             secondary_ability: None,
+            spells: Vec::new(),
+            feats: Vec::new(),
+            ability_bar: ActorAbilityBar::new(),
+            quick_use_bar: ActorQuickUseBar::new(&params.quick_use_bar.clone().unwrap_or_default()),
+            melee_attack: None,
+            facing_direction: vec2(0.0, 1.0),
+            knockback: Vec2::ZERO,
             controller: ActorController::new(controller_kind),
+            behavior: params.behavior.map(|params| ActorBehavior::new(params)),
+            dialogue: params.dialogue,
+            quests: params.quests
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|params| Quest::from_progress_params(params))
+                .collect(),
+            gold: params.gold.unwrap_or(0),
+            loot_table: params.loot_table,
+            kill_events: Vec::new(),
+            sound_effects: params.sound_effects.unwrap_or_default(),
+            hit_sound_timer: 0.0,
+            death_timer: None,
+            footstep_distance: 0.0,
+        };
+        let equipped: Vec<ActorInventoryEntry> = actor.equipment.get_all()
+            .into_iter()
+            .map(|(_, entry)| entry.clone())
+            .collect();
+        for entry in &equipped {
+            actor.on_equipped(entry);
         }
+        for feat_id in params.feats.unwrap_or_default() {
+            actor.add_feat(&feat_id);
+        }
+        for spell_id in params.spells.unwrap_or_default() {
+            actor.learn_spell(&spell_id);
+        }
+        for (slot, spell_id) in params.ability_bar.unwrap_or_default().into_iter().enumerate() {
+            if slot < ActorAbilityBar::SLOT_COUNT {
+                actor.assign_spell(slot, spell_id.as_deref());
+            }
+        }
+        actor.stats.update_derived(max_vitals);
+        actor.update_abilities();
+        actor
     }
 
     pub fn add_node(position: Vec2, controller_kind: ActorControllerKind, max_vitals: bool, params: ActorParams) -> Handle<Self> {
@@ -142,13 +346,511 @@ impl Actor {
             stats: json::ActorStats::from(self.stats.clone()),
             factions: self.factions.clone(),
             collider,
-            inventory: self.inventory.to_item_ids(),
+            inventory: self.inventory.to_entry_params(),
+            equipment: Some(self.equipment.to_equipment_params()),
+            spells: Some(self.spells.clone()),
+            feats: Some(self.feats.clone()),
+            ability_bar: Some(self.ability_bar.to_spell_ids()),
+            quick_use_bar: Some(self.quick_use_bar.to_item_ids()),
+            behavior: self.behavior.as_ref().map(|behavior| behavior.to_behavior_params()),
+            dialogue: self.dialogue.clone(),
+            quests: Some(self.quests.iter().map(|quest| quest.to_progress_params()).collect()),
+            gold: Some(self.gold),
+            loot_table: self.loot_table.clone(),
+            sound_effects: Some(self.sound_effects.clone()),
             sprite_animation_params: self.sprite_animation.to_sprite_params(),
         }
     }
 
-    pub fn take_damage(&mut self, _actor_id: &str, damage: f32) {
-        self.stats.current_health -= damage;
+    pub fn get_total_weight(&self) -> f32 {
+        self.inventory.get_total_weight() + self.equipment.get_total_weight()
+    }
+
+    /// Adds the entry to the inventory, or to the gold of the actor, if it is currency
+    pub fn add_to_inventory(&mut self, entry: ActorInventoryEntry) {
+        if entry.params.kind == Item::CURRENCY_KIND {
+            self.gold += entry.quantity;
+        } else {
+            self.inventory.add_entry(entry);
+        }
+    }
+
+    pub fn pick_up_item(&mut self, item: RefMut<Item>) {
+        self.add_to_inventory(ActorInventoryEntry::with_quantity(item.params.clone(), item.quantity));
+        item.delete();
+    }
+
+    /// Returns `true` if the actor can carry the additional weight, without exceeding its carry capacity
+    pub fn can_carry(&self, weight: f32) -> bool {
+        self.get_total_weight() + weight <= self.stats.carry_capacity
+    }
+
+    /// Equips an item from the inventory in the first free slot that is valid for it
+    pub fn equip_item(&mut self, instance_id: &str) -> bool {
+        let slot = self.inventory.get_entry(instance_id)
+            .and_then(|entry| self.equipment.get_slot_for(entry));
+        match slot {
+            Some(slot) => self.equip_item_to(instance_id, slot),
+            None => false,
+        }
+    }
+
+    pub fn equip_item_to(&mut self, instance_id: &str, slot: EquipmentSlot) -> bool {
+        let entry = match self.inventory.take_quantity(instance_id, 1) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        let equipped = entry.clone();
+        match self.equipment.equip(slot, entry) {
+            Ok(unequipped) => {
+                for entry in unequipped {
+                    self.on_unequipped(&entry);
+                    self.inventory.add_entry(entry);
+                }
+                self.on_equipped(&equipped);
+                self.update_abilities();
+                true
+            }
+            Err(entry) => {
+                self.inventory.add_entry(entry);
+                false
+            }
+        }
+    }
+
+    pub fn unequip_item(&mut self, slot: EquipmentSlot) -> bool {
+        if let Some(entry) = self.equipment.unequip(slot) {
+            self.on_unequipped(&entry);
+            self.inventory.add_entry(entry);
+            self.update_abilities();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn unequip_all(&mut self) {
+        for entry in self.equipment.unequip_all() {
+            self.on_unequipped(&entry);
+            self.inventory.add_entry(entry);
+        }
+        self.update_abilities();
+    }
+
+    // Equipped items apply their modifiers and status effects for as long as they are equipped,
+    // using the instance id of the item as source id
+    fn on_equipped(&mut self, entry: &ActorInventoryEntry) {
+        if let Some(values) = entry.params.stat_modifiers.clone() {
+            self.stats.add_modifier(ActorStatModifier::flat(&entry.instance_id, values, None));
+        }
+        for params in entry.params.status_effects.clone().unwrap_or_default() {
+            self.apply_status_effect(&entry.instance_id, params);
+        }
+    }
+
+    fn on_unequipped(&mut self, entry: &ActorInventoryEntry) {
+        self.stats.remove_modifiers(&entry.instance_id);
+        self.remove_status_effects(&entry.instance_id);
+    }
+
+    /// Applies a status effect from a source, like an ability or an item.
+    /// Effects that are not stackable will replace any active effect of the same kind.
+    pub fn apply_status_effect(&mut self, source_id: &str, params: StatusEffectParams) {
+        let effect = StatusEffect::new(source_id, params);
+        if !effect.is_stackable {
+            let replaced: Vec<String> = self.status_effects
+                .iter()
+                .filter(|other| other.kind == effect.kind && !other.is_stackable)
+                .map(|other| other.id.clone())
+                .collect();
+            for id in replaced {
+                self.stats.remove_modifiers(&id);
+            }
+            self.status_effects.retain(|other| other.kind != effect.kind || other.is_stackable);
+        }
+        for modifier in effect.get_stat_modifiers() {
+            self.stats.add_modifier(modifier);
+        }
+        self.status_effects.push(effect);
+    }
+
+    /// Applies a status effect from a continuous source, like a beam, that hits every frame.
+    /// An active effect of the same kind from the same source is restarted, instead of being stacked.
+    pub fn refresh_status_effect(&mut self, source_id: &str, params: StatusEffectParams) {
+        let refreshed: Vec<String> = self.status_effects
+            .iter()
+            .filter(|effect| effect.source_id == source_id && effect.kind == params.kind)
+            .map(|effect| effect.id.clone())
+            .collect();
+        for id in &refreshed {
+            self.stats.remove_modifiers(id);
+        }
+        self.status_effects.retain(|effect| !refreshed.contains(&effect.id));
+        self.apply_status_effect(source_id, params);
+    }
+
+    pub fn remove_status_effects(&mut self, source_id: &str) {
+        let removed: Vec<String> = self.status_effects
+            .iter()
+            .filter(|effect| effect.source_id == source_id)
+            .map(|effect| effect.id.clone())
+            .collect();
+        for id in removed {
+            self.stats.remove_modifiers(&id);
+        }
+        self.status_effects.retain(|effect| effect.source_id != source_id);
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.status_effects.iter().any(|effect| effect.kind == StatusEffect::STUNNED_KIND)
+    }
+
+    fn update_status_effects(&mut self) {
+        let dt = get_frame_time();
+        for mut effect in self.status_effects.clone() {
+            if effect.kind == StatusEffect::BURNING_KIND {
+                self.take_damage(&effect.source_id, Damage::new(Damage::FIRE_TYPE, effect.magnitude * dt));
+            } else if effect.kind == StatusEffect::REGENERATING_KIND {
+                self.stats.current_health = (self.stats.current_health + effect.magnitude * dt).min(self.stats.max_health);
+            }
+            if let Some(time_left) = effect.time_left.as_mut() {
+                *time_left -= dt;
+            }
+            if effect.is_expired() {
+                self.stats.remove_modifiers(&effect.id);
+                self.status_effects.retain(|other| other.id != effect.id);
+            } else if let Some(other) = self.status_effects.iter_mut().find(|other| other.id == effect.id) {
+                *other = effect;
+            }
+        }
+    }
+
+    // The abilities are provided by the equipped items, with the main hand taking precedence,
+    // followed by the off hand and then the trinkets
+    fn update_abilities(&mut self) {
+        let mut primary_ability = None;
+        let mut secondary_ability = None;
+        for (slot, entry) in self.equipment.get_all() {
+            if let EquipmentSlot::Head | EquipmentSlot::Body | EquipmentSlot::Legs = slot {
+                continue;
+            }
+            if let Some(ability_params) = &entry.params.ability_params {
+                if ability_params.action_kind == ActorAbility::PRIMARY_ABILITY && primary_ability.is_none() {
+                    primary_ability = Some(self.create_ability(ability_params.clone()));
+                } else if ability_params.action_kind == ActorAbility::SECONDARY_ABILITY && secondary_ability.is_none() {
+                    secondary_ability = Some(self.create_ability(ability_params.clone()));
+                }
+            }
+        }
+        self.primary_ability = primary_ability;
+        self.secondary_ability = secondary_ability;
+    }
+
+    /// Creates an ability, with the ability modifiers of the actors feats applied
+    pub fn create_ability(&self, params: ActorAbilityParams) -> ActorAbility {
+        let mut ability = ActorAbility::new(params);
+        let resources = get_global::<Resources>();
+        for feat_id in &self.feats {
+            if let Some(modifiers) = &resources.get_feat(feat_id).ability_modifiers {
+                ability.apply_modifiers(modifiers);
+            }
+        }
+        ability
+    }
+
+    pub fn learn_spell(&mut self, spell_id: &str) -> bool {
+        let resources = get_global::<Resources>();
+        if self.spells.iter().any(|id| id == spell_id) || resources.try_get_spell(spell_id).is_none() {
+            return false;
+        }
+        self.spells.push(spell_id.to_string());
+        true
+    }
+
+    /// Binds a learned spell to a slot on the ability bar, or clears the slot if no spell id is given
+    pub fn assign_spell(&mut self, slot: usize, spell_id: Option<&str>) -> bool {
+        let entry = match spell_id {
+            Some(spell_id) => {
+                if !self.spells.iter().any(|id| id == spell_id) {
+                    return false;
+                }
+                let params = get_global::<Resources>().get_spell(spell_id).clone();
+                Some(AbilityBarSlot {
+                    spell_id: params.id,
+                    name: params.name,
+                    ability: self.create_ability(params.ability_params),
+                })
+            }
+            None => None,
+        };
+        self.ability_bar.set(slot, entry);
+        true
+    }
+
+    /// Uses a consumable item, applying its effects and removing one of it from its stack.
+    /// Returns `false` if the item can not be used
+    pub fn use_item(&mut self, instance_id: &str) -> bool {
+        if self.is_dead() {
+            return false;
+        }
+        let (item_id, params) = match self.inventory.get_entry(instance_id) {
+            Some(entry) => match &entry.params.consumable {
+                Some(params) => (entry.params.id.clone(), params.clone()),
+                None => return false,
+            },
+            None => return false,
+        };
+        // Items that only restore vitals are not wasted when every vital they restore is full
+        let is_vital_restore_only = params.spell_id.is_none()
+            && params.status_effects.as_ref().map(|effects| effects.is_empty()).unwrap_or(true);
+        if is_vital_restore_only
+            && (params.health.is_none() || self.stats.current_health >= self.stats.max_health)
+            && (params.stamina.is_none() || self.stats.current_stamina >= self.stats.max_stamina)
+            && (params.energy.is_none() || self.stats.current_energy >= self.stats.max_energy) {
+            return false;
+        }
+        if let Some(spell_id) = &params.spell_id {
+            if self.learn_spell(spell_id) == false {
+                return false;
+            }
+        }
+        if let Some(health) = params.health {
+            self.stats.current_health = (self.stats.current_health + health).min(self.stats.max_health);
+        }
+        if let Some(stamina) = params.stamina {
+            self.stats.current_stamina = (self.stats.current_stamina + stamina).min(self.stats.max_stamina);
+        }
+        if let Some(energy) = params.energy {
+            self.stats.current_energy = (self.stats.current_energy + energy).min(self.stats.max_energy);
+        }
+        for effect in params.status_effects.unwrap_or_default() {
+            self.apply_status_effect(&item_id, effect);
+        }
+        self.inventory.take_quantity(instance_id, 1);
+        true
+    }
+
+    /// Binds a consumable item to a slot on the quick use bar, or clears the slot if no item id is given
+    pub fn assign_quick_use(&mut self, slot: usize, item_id: Option<&str>) {
+        self.quick_use_bar.set(slot, item_id.map(|item_id| item_id.to_string()));
+    }
+
+    /// Uses the first stack in the inventory of the item that is bound to the quick use bar slot
+    pub fn quick_use(&mut self, slot: usize) -> bool {
+        let instance_id = self.quick_use_bar.get(slot)
+            .and_then(|item_id| self.inventory.get_first_of_item(item_id))
+            .map(|entry| entry.instance_id.clone());
+        match instance_id {
+            Some(instance_id) => self.use_item(&instance_id),
+            None => false,
+        }
+    }
+
+    /// Adds a passive feat, applying its stat modifiers, using the feat id as source id, and its ability modifiers
+    pub fn add_feat(&mut self, feat_id: &str) -> bool {
+        let params = match get_global::<Resources>().try_get_feat(feat_id) {
+            Some(params) => params.clone(),
+            None => return false,
+        };
+        if self.feats.contains(&params.id) {
+            return false;
+        }
+        self.feats.push(params.id.clone());
+        if let Some(values) = params.stat_modifiers {
+            self.stats.add_modifier(ActorStatModifier::flat(&params.id, values, None));
+        }
+        // The abilities are recreated with the new modifiers, but keep their cooldowns
+        let cooldown_timers = (
+            self.primary_ability.as_ref().map(|ability| ability.cooldown_timer),
+            self.secondary_ability.as_ref().map(|ability| ability.cooldown_timer),
+        );
+        self.update_abilities();
+        if let (Some(ability), Some(cooldown_timer)) = (self.primary_ability.as_mut(), cooldown_timers.0) {
+            ability.cooldown_timer = cooldown_timer;
+        }
+        if let (Some(ability), Some(cooldown_timer)) = (self.secondary_ability.as_mut(), cooldown_timers.1) {
+            ability.cooldown_timer = cooldown_timer;
+        }
+        let spell_ids = self.ability_bar.to_spell_ids();
+        for (slot, spell_id) in spell_ids.into_iter().enumerate() {
+            let cooldown_timer = self.ability_bar.get(slot).map(|entry| entry.ability.cooldown_timer);
+            self.assign_spell(slot, spell_id.as_deref());
+            if let (Some(ability), Some(cooldown_timer)) = (self.ability_bar.get_ability_mut(slot), cooldown_timer) {
+                ability.cooldown_timer = cooldown_timer;
+            }
+        }
+        true
+    }
+
+    pub fn get_experience_reward(&self) -> u32 {
+        self.stats.level * Self::EXPERIENCE_PER_LEVEL_KILLED
+    }
+
+    /// Adds experience and levels up for as long as the next level threshold, defined in the levels file, is met
+    pub fn add_experience(&mut self, amount: u32) {
+        self.stats.experience += amount;
+        let resources = get_global::<Resources>();
+        while let Some(next_level) = resources.try_get_level(self.stats.level + 1) {
+            if self.stats.experience < next_level.experience {
+                break;
+            }
+            self.stats.level = next_level.level;
+            self.stats.attribute_points += next_level.attribute_points;
+        }
+    }
+
+    /// Starts the quest, unless the actor has already started it
+    pub fn start_quest(&mut self, quest_id: &str) -> bool {
+        if self.has_quest(quest_id) {
+            return false;
+        }
+        match Quest::new(quest_id) {
+            Some(quest) => {
+                self.quests.push(quest);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn has_quest(&self, quest_id: &str) -> bool {
+        self.quests.iter().any(|quest| quest.params.id == quest_id)
+    }
+
+    pub fn has_completed_quest(&self, quest_id: &str) -> bool {
+        self.quests.iter().any(|quest| quest.params.id == quest_id && quest.is_completed)
+    }
+
+    pub fn on_quest_event(&mut self, event: &QuestEvent) {
+        for quest in &mut self.quests {
+            quest.on_event(event);
+        }
+    }
+
+    fn update_quests(&mut self) {
+        if self.quests.is_empty() {
+            return;
+        }
+        let mut rewards = Vec::new();
+        {
+            let game_state = scene::find_node_by_type::<GameState>().unwrap();
+            for quest in &mut self.quests {
+                if quest.update(&self.inventory, self.body.position, &game_state.map) {
+                    rewards.push(quest.params.rewards.clone().unwrap_or_default());
+                }
+            }
+        }
+        for reward in rewards {
+            if let Some(experience) = reward.experience {
+                self.add_experience(experience);
+            }
+            for item_id in reward.items.unwrap_or_default() {
+                let resources = get_global::<Resources>();
+                let params = resources.get_item(&item_id).clone();
+                self.add_to_inventory(ActorInventoryEntry::new(params));
+            }
+            if self.is_local_player() {
+                let mut factions = get_global_mut::<Factions>();
+                for params in reward.reputation.unwrap_or_default() {
+                    factions.add_reputation(&params.faction, params.amount);
+                }
+            }
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.death_timer.is_some() || self.stats.current_health <= 0.0
+    }
+
+    /// Rewards the actor for a kill
+    fn on_kill(&mut self, event: &KillEvent) {
+        self.add_experience(event.experience);
+        self.on_quest_event(&QuestEvent::ActorKilled { actor_name: event.victim_name.clone() });
+        if self.is_local_player() {
+            get_global_mut::<Factions>().on_killed(&event.victim_factions);
+        }
+    }
+
+    fn start_dying(&mut self) {
+        self.play_sound_effect(&self.sound_effects.death);
+        self.sprite_animation.play_death_animation();
+        self.controller.direction = Vec2::ZERO;
+        self.controller.primary_target = None;
+        self.controller.secondary_target = None;
+        self.controller.ability_bar_target = None;
+        self.melee_attack = None;
+        self.knockback = Vec2::ZERO;
+    }
+
+    /// Replaces the actor with a corpse, holding its gold and items, along with any loot rolled from its loot table.
+    /// The local player keeps its equipment and is stored in the game state, until it is respawned.
+    fn die(&mut self) {
+        let is_local_player = self.is_local_player();
+        if is_local_player == false {
+            self.unequip_all();
+        }
+        let mut inventory = ActorInventory::new(&[]);
+        for entry in self.inventory.take_all() {
+            inventory.add_entry(entry);
+        }
+        let mut gold = self.gold;
+        self.gold = 0;
+        if let Some(loot_table_id) = &self.loot_table {
+            let resources = get_global::<Resources>();
+            match resources.try_get_loot_table(loot_table_id) {
+                Some(loot_table) => {
+                    let (items, loot_gold) = loot_table.roll();
+                    for item in items {
+                        let params = resources.get_item(&item.item_id).clone();
+                        let quantity = item.quantity.unwrap_or(1);
+                        // Currency is added to the gold of the corpse, like `add_to_inventory` does for actors
+                        if params.kind == Item::CURRENCY_KIND {
+                            gold += quantity;
+                        } else {
+                            inventory.add_entry(ActorInventoryEntry::with_quantity(params, quantity));
+                        }
+                    }
+                    gold += loot_gold;
+                }
+                None => show_notice(&format!("Unable to find loot table '{}' of actor '{}'", loot_table_id, self.name)),
+            }
+        }
+        let mut corpses = scene::find_node_by_type::<Corpses>().unwrap();
+        corpses.spawn(Corpse::new(&self.name, self.body.position, self.sprite_animation.clone(), inventory, gold));
+        if is_local_player {
+            let mut game_state = scene::find_node_by_type::<GameState>().unwrap();
+            game_state.dead_player = Some(self.to_actor_params());
+        }
+    }
+
+    /// Applies damage, mitigated by armor and resistances, and records it as a damage event
+    pub fn take_damage(&mut self, attacker_id: &str, damage: Damage) -> DamageEvent {
+        let mitigated = damage.amount * self.stats.get_mitigation(&damage.damage_type);
+        let amount = damage.amount - mitigated;
+        let was_alive = self.stats.current_health > 0.0;
+        self.stats.current_health -= amount;
+        let event = DamageEvent {
+            attacker_id: attacker_id.to_string(),
+            target_id: self.id.clone(),
+            damage_type: damage.damage_type,
+            amount,
+            mitigated,
+            is_critical: damage.is_critical,
+            is_fatal: was_alive && self.stats.current_health <= 0.0,
+        };
+        self.damage_events.push(event.clone());
+        event
+    }
+
+    fn play_sound_effect(&self, id: &Option<String>) {
+        if let Some(id) = id {
+            let audio_manager = scene::find_node_by_type::<AudioManager>().unwrap();
+            audio_manager.play_sound_effect(id, Some(self.body.position));
+        }
+    }
+
+    pub fn apply_knockback(&mut self, force: Vec2) {
+        self.knockback += force;
     }
 
     pub fn find_player(player_id: u32) -> Option<RefMut<Self>> {
@@ -204,6 +906,31 @@ impl Actor {
         }
     }
 
+    fn set_facing_direction(&mut self, direction: Vec2) {
+        if direction != Vec2::ZERO {
+            self.facing_direction = direction;
+        }
+    }
+
+    /// Plays the attack animation for the direction of the swing, if the sprite has one,
+    /// falling back to the movement animation for that direction
+    pub fn set_attack_animation(&mut self, direction: Vec2) {
+        let (name, flip_x) = if direction.x.abs() > direction.y.abs() {
+            ("attack_right", direction.x < 0.0)
+        } else if direction.y > 0.0 {
+            ("attack_down", false)
+        } else {
+            ("attack_up", false)
+        };
+        match self.sprite_animation.get_animation_id(name) {
+            Some(id) => {
+                self.sprite_animation.start_animation(id);
+                self.sprite_animation.flip_x = flip_x;
+            }
+            None => self.set_animation(direction, false),
+        }
+    }
+
     pub fn draw_actor(&mut self) {
         {
             self.body.debug_draw();
@@ -285,8 +1012,16 @@ impl Actor {
         }
     }
 
+    /// Starts the dialogue of this actor, if it has one and `other` is the local player
     pub fn interact(&self, other: &mut Actor) {
-        println!("INTERACTION between '{}' and '{}'", self.name, other.name);
+        if other.is_local_player() == false {
+            return;
+        }
+        other.on_quest_event(&QuestEvent::TalkedTo { actor_name: self.name.clone() });
+        if let Some(dialogue_id) = &self.dialogue {
+            let mut game_state = scene::find_node_by_type::<GameState>().unwrap();
+            game_state.dialogue = Dialogue::new(self, dialogue_id);
+        }
     }
 }
 
@@ -299,16 +1034,69 @@ impl Node for Actor {
     }
 
     fn update(mut node: RefMut<Self>) {
-        node.stats.update_derived(false);
-        node.sprite_animation.update();
+        if node.is_dead() == false {
+            node.update_status_effects();
+            node.stats.update_modifiers(get_frame_time());
+            node.stats.update_derived(false);
+        }
 
-        if node.stats.current_health <= 0.0 {
+        node.hit_sound_timer += get_frame_time();
+        let damage_events: Vec<DamageEvent> = node.damage_events.drain(..).collect();
+        if damage_events.len() > 0 {
+            if node.hit_sound_timer >= Self::HIT_SOUND_INTERVAL {
+                node.hit_sound_timer = 0.0;
+                node.play_sound_effect(&node.sound_effects.hit);
+                if node.is_local_player() {
+                    let amount: f32 = damage_events.iter().map(|event| event.amount).sum();
+                    let magnitude = (amount / node.stats.max_health * Self::DAMAGE_RUMBLE_SCALE)
+                        .clamp(Self::MIN_DAMAGE_RUMBLE, 1.0);
+                    rumble_gamepad(magnitude, magnitude / 2.0, Self::DAMAGE_RUMBLE_DURATION);
+                }
+            }
             let position = node.body.position;
-            node.inventory.drop_all(position);
-            node.delete();
+            let mut combat_text = scene::find_node_by_type::<CombatText>().unwrap();
+            for event in &damage_events {
+                combat_text.add_damage_event(event, position);
+                if let Some(behavior) = node.behavior.as_mut() {
+                    behavior.on_damage(event);
+                }
+                if event.is_fatal {
+                    if let Some(mut attacker) = Actor::find_with_id(&event.attacker_id) {
+                        attacker.kill_events.push(KillEvent {
+                            killer_id: event.attacker_id.clone(),
+                            victim_name: node.name.clone(),
+                            victim_factions: node.factions.clone(),
+                            experience: node.get_experience_reward(),
+                        });
+                    }
+                }
+            }
+        }
+
+        let kill_events: Vec<KillEvent> = node.kill_events.drain(..).collect();
+        for event in &kill_events {
+            node.on_kill(event);
+        }
+
+        if node.is_dead() {
+            let death_timer = match node.death_timer {
+                Some(timer) => timer + get_frame_time(),
+                None => {
+                    node.start_dying();
+                    0.0
+                }
+            };
+            node.death_timer = Some(death_timer);
+            node.sprite_animation.update();
+            if death_timer >= Self::DEATH_DURATION {
+                node.die();
+                node.delete();
+            }
             return;
         }
 
+        node.sprite_animation.update();
+
         if let Some(ability) = node.primary_ability.as_mut() {
             ability.update();
         }
@@ -317,30 +1105,82 @@ impl Node for Actor {
             ability.update();
         }
 
+        node.ability_bar.update();
+
+        node.update_quests();
+
+        if let Some(mut attack) = node.melee_attack.take() {
+            if attack.timer == 0.0 {
+                node.sprite_animation.restart_animation();
+            }
+            attack.timer += get_frame_time();
+            if attack.should_resolve() {
+                let (id, factions, position) = (node.id.clone(), node.factions.clone(), node.body.position);
+                attack.resolve(&id, &factions, position);
+            }
+            if !attack.is_finished() {
+                node.melee_attack = Some(attack);
+            }
+        }
+
         match node.controller.kind {
             ActorControllerKind::Player { id } => {
                 let local_player = get_global::<LocalPlayer>();
                 if id == local_player.id {
-                    apply_local_player_input(&mut node.controller);
+                    let position = node.body.position;
+                    apply_local_player_input(&mut node.controller, position);
                 } else {
                     // TODO: Remote player (?)
                 }
             }
             ActorControllerKind::Computer => {
-                // TODO: Computer controlled
+                if let Some(mut behavior) = node.behavior.take() {
+                    behavior.update(&mut *node);
+                    node.behavior = Some(behavior);
+                }
             }
             ActorControllerKind::None => {}
         }
 
+        if node.is_stunned() {
+            node.controller.direction = Vec2::ZERO;
+            node.controller.primary_target = None;
+            node.controller.secondary_target = None;
+            node.controller.ability_bar_target = None;
+            node.controller.is_sprinting = false;
+            node.controller.is_interacting = false;
+            node.controller.is_picking_up_items = false;
+            node.controller.quick_use_slot = None;
+            node.melee_attack = None;
+        }
+
+        if let Some(slot) = node.controller.quick_use_slot.take() {
+            node.quick_use(slot);
+        }
+
+        // The actor is committed to a melee attack, until it has recovered
+        if node.melee_attack.is_some() {
+            node.controller.direction = Vec2::ZERO;
+            node.controller.primary_target = None;
+            node.controller.secondary_target = None;
+            node.controller.ability_bar_target = None;
+            node.controller.is_sprinting = false;
+        }
+
         let controller_direction = node.controller.direction;
-        if let Some(target) = node.controller.primary_target {
+        if let Some(direction) = node.melee_attack.as_ref().map(|attack| attack.direction) {
+            node.set_attack_animation(direction);
+        } else if let Some(target) = node.controller.primary_target {
             let direction = target.sub(node.body.position).normalize_or_zero();
             node.set_animation(direction, controller_direction == Vec2::ZERO);
+            node.set_facing_direction(direction);
         } else if let Some(target) = node.controller.secondary_target {
             let direction = target.sub(node.body.position).normalize_or_zero();
             node.set_animation(direction, controller_direction == Vec2::ZERO);
+            node.set_facing_direction(direction);
         } else {
             node.set_animation(controller_direction, false);
+            node.set_facing_direction(controller_direction.normalize_or_zero());
         }
 
         let controller = node.controller.clone();
@@ -360,11 +1200,23 @@ impl Node for Actor {
             }
             node.secondary_ability = secondary_ability;
         }
+        if let Some((slot, target)) = controller.ability_bar_target {
+            let mut ability_bar = node.ability_bar.clone();
+            let position = node.body.position;
+            if let Some(ability) = ability_bar.get_ability_mut(slot) {
+                ability.activate(&mut *node, position, target);
+            }
+            node.ability_bar = ability_bar;
+        }
     }
 
     fn fixed_update(mut node: RefMut<Self>) {
+        if node.is_dead() {
+            return;
+        }
+
         let direction = node.controller.direction.normalize_or_zero();
-        node.body.velocity = direction * if node.inventory.get_total_weight() >= node.stats.carry_capacity {
+        node.body.velocity = direction * if node.get_total_weight() >= node.stats.carry_capacity {
             node.stats.move_speed * Self::ENCUMBERED_SPEED_FACTOR
         } else if node.controller.is_sprinting && node.stats.current_stamina >= Self::SPRINT_STAMINA_COST {
             if direction != Vec2::ZERO {
@@ -374,34 +1226,75 @@ impl Node for Actor {
         } else {
             node.stats.move_speed
         };
+        let knockback = node.knockback;
+        node.body.velocity += knockback;
 
+        node.knockback *= Self::KNOCKBACK_DAMPING;
+        if node.knockback.length() < Self::MIN_KNOCKBACK {
+            node.knockback = Vec2::ZERO;
+        }
+
+        let previous_position = node.body.position;
         node.body.integrate();
 
+        node.footstep_distance += node.body.position.distance(previous_position);
+        if node.footstep_distance >= Self::FOOTSTEP_DISTANCE {
+            node.footstep_distance = 0.0;
+            node.play_sound_effect(&node.sound_effects.footsteps);
+        }
+
         if node.controller.is_picking_up_items {
             let collider = Collider::circle(0.0, 0.0, Self::PICK_UP_RADIUS).offset(node.body.position);
             for item in scene::find_nodes_by_type::<Item>() {
                 if collider.contains(item.position) {
-                    node.inventory.pick_up_item(item);
+                    node.pick_up_item(item);
                 }
             }
+            let position = node.body.position;
+            let mut corpses = scene::find_node_by_type::<Corpses>().unwrap();
+            for corpse in corpses.find_in_radius(position, Self::PICK_UP_RADIUS) {
+                corpse.loot(&mut *node);
+            }
         }
 
         if node.controller.is_interacting {
             let collider = Collider::circle(0.0, 0.0, Self::INTERACT_RADIUS).offset(node.body.position);
             for actor in scene::find_nodes_by_type::<Actor>() {
+                if actor.is_dead() || Factions::get_stance(&node.factions, &actor.factions) == FactionStance::Hostile {
+                    continue;
+                }
                 if let Some(other_collider) = actor.body.get_offset_collider() {
                     if collider.overlaps(&other_collider) {
-                        for faction in &node.factions {
-                            if actor.factions.contains(faction) {
-                                actor.interact(&mut *node);
-                                node.controller.is_interacting = false; // stop this form firing twice
-                                break;
-                            }
-                        }
+                        actor.interact(&mut *node);
+                        node.controller.is_interacting = false; // stop this form firing twice
+                        break;
                     }
                 }
             }
         }
+
+        // If there was no one to interact with, the local player opens the closest container or corpse instead
+        if node.controller.is_interacting && node.is_local_player() {
+            node.controller.is_interacting = false;
+            let position = node.body.position;
+            let mut targets = Vec::new();
+            {
+                let containers = scene::find_node_by_type::<Containers>().unwrap();
+                if let Some(container) = containers.find_closest(position, Self::INTERACT_RADIUS) {
+                    let target = TransferTarget::Container { id: container.id.clone() };
+                    targets.push((target, container.distance_to(position)));
+                }
+                let corpses = scene::find_node_by_type::<Corpses>().unwrap();
+                if let Some(corpse) = corpses.find_closest(position, Self::INTERACT_RADIUS) {
+                    let target = TransferTarget::Corpse { id: corpse.id.clone() };
+                    targets.push((target, corpse.position.distance(position)));
+                }
+            }
+            if let Some((target, _)) = targets.into_iter().min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()) {
+                let mut game_state = scene::find_node_by_type::<GameState>().unwrap();
+                game_state.transfer_target = Some(target);
+            }
+        }
     }
 
     fn draw(node: RefMut<Self>) {
@@ -9,7 +9,14 @@ use serde::{
 };
 
 use crate::{Actor, generate_id, json};
-use crate::nodes::{Projectiles, ContinuousBeams};
+use crate::input::rumble_gamepad;
+use crate::nodes::actor::{
+    AbilityModifiers,
+    Damage,
+    MeleeAttack,
+    StatusEffectParams,
+};
+use crate::nodes::{Projectiles, ContinuousBeams, AreaEffects, AudioManager};
 use std::ops::Sub;
 use crate::nodes::projectiles::ProjectileKind;
 use crate::render::{SpriteAnimationParams, SpriteAnimationPlayer};
@@ -27,9 +34,22 @@ pub struct ActorAbilityParams {
     pub spread: f32,
     pub range: f32,
     pub damage: f32,
+    pub damage_type: Option<String>,
     pub effect_size: f32,
     pub effect_color: json::Color,
     pub effect_sprite_animation_params: Option<SpriteAnimationParams>,
+    pub status_effects: Option<Vec<StatusEffectParams>>,
+    pub sound_effect_id: Option<String>,
+    pub radius: Option<f32>,
+    pub falloff: Option<f32>,
+    pub duration: Option<f32>,
+    pub tick_interval: Option<f32>,
+    pub angle: Option<f32>,
+    pub jumps: Option<u32>,
+    pub windup: Option<f32>,
+    pub recovery: Option<f32>,
+    pub knockback: Option<f32>,
+    pub rumble: Option<f32>,
 }
 
 impl Default for ActorAbilityParams {
@@ -46,9 +66,22 @@ impl Default for ActorAbilityParams {
             spread: 0.0,
             range: 100.0,
             damage: 0.0,
+            damage_type: None,
             effect_size: 5.0,
             effect_color: json::Color::from(color::WHITE),
             effect_sprite_animation_params: None,
+            status_effects: None,
+            sound_effect_id: None,
+            radius: None,
+            falloff: None,
+            duration: None,
+            tick_interval: None,
+            angle: None,
+            jumps: None,
+            windup: None,
+            recovery: None,
+            knockback: None,
+            rumble: None,
         }
     }
 }
@@ -60,6 +93,8 @@ pub struct ActorAbility {
     pub action_kind: String,
     pub cooldown: f32,
     pub cooldown_timer: f32,
+    /// Time since a continuous beam was last fired, used to play its sound effect when it starts
+    beam_timer: f32,
     pub health_cost: f32,
     pub stamina_cost: f32,
     pub energy_cost: f32,
@@ -67,9 +102,22 @@ pub struct ActorAbility {
     pub spread: f32,
     pub range: f32,
     pub damage: f32,
+    pub damage_type: String,
     pub effect_size: f32,
     pub effect_color: Color,
     pub effect_sprite_animation_params: Option<SpriteAnimationParams>,
+    pub status_effects: Vec<StatusEffectParams>,
+    pub sound_effect_id: Option<String>,
+    pub radius: f32,
+    pub falloff: f32,
+    pub duration: f32,
+    pub tick_interval: f32,
+    pub angle: f32,
+    pub jumps: u32,
+    pub windup: f32,
+    pub recovery: f32,
+    pub knockback: f32,
+    pub rumble: f32,
 }
 
 impl ActorAbility {
@@ -77,6 +125,24 @@ impl ActorAbility {
     pub const ENERGY_SPHERE_EFFECT: &'static str = "energy_sphere";
     pub const BEAM_EFFECT: &'static str = "beam";
     pub const CONTINUOUS_BEAM_EFFECT: &'static str = "continuous_beam";
+    pub const EXPLOSIVE_PROJECTILE_EFFECT: &'static str = "explosive_projectile";
+    pub const ZONE_EFFECT: &'static str = "zone";
+    pub const CONE_EFFECT: &'static str = "cone";
+    pub const CHAIN_LIGHTNING_EFFECT: &'static str = "chain_lightning";
+    pub const MELEE_EFFECT: &'static str = "melee";
+
+    const DEFAULT_RADIUS: f32 = 32.0;
+    const DEFAULT_FALLOFF: f32 = 0.5;
+    const DEFAULT_DURATION: f32 = 5.0;
+    const DEFAULT_TICK_INTERVAL: f32 = 0.5;
+    const DEFAULT_ANGLE: f32 = 60.0;
+    const DEFAULT_JUMPS: u32 = 3;
+    const DEFAULT_WINDUP: f32 = 0.15;
+    const DEFAULT_RECOVERY: f32 = 0.25;
+
+    const RUMBLE_DURATION: f32 = 0.15;
+    // A continuous beam that has not been fired for this long is considered to be starting again
+    const BEAM_RESTART_DELAY: f32 = 0.25;
 
     pub const PRIMARY_ABILITY: &'static str = "primary";
     pub const SECONDARY_ABILITY: &'static str = "secondary";
@@ -91,16 +157,83 @@ impl ActorAbility {
             energy_cost: params.energy_cost,
             cooldown: params.cooldown.unwrap_or_default(),
             cooldown_timer: params.cooldown.unwrap_or_default(),
+            beam_timer: Self::BEAM_RESTART_DELAY,
             speed: params.speed,
             spread: params.spread,
             range: params.range,
             damage: params.damage,
+            damage_type: params.damage_type.unwrap_or(Damage::KINETIC_TYPE.to_string()),
             effect_size: params.effect_size,
             effect_color: params.effect_color.to_macroquad(),
             effect_sprite_animation_params: params.effect_sprite_animation_params,
+            status_effects: params.status_effects.unwrap_or_default(),
+            sound_effect_id: params.sound_effect_id,
+            radius: params.radius.unwrap_or(Self::DEFAULT_RADIUS),
+            falloff: params.falloff.unwrap_or(Self::DEFAULT_FALLOFF),
+            duration: params.duration.unwrap_or(Self::DEFAULT_DURATION),
+            tick_interval: params.tick_interval.unwrap_or(Self::DEFAULT_TICK_INTERVAL),
+            angle: params.angle.unwrap_or(Self::DEFAULT_ANGLE),
+            jumps: params.jumps.unwrap_or(Self::DEFAULT_JUMPS),
+            windup: params.windup.unwrap_or(Self::DEFAULT_WINDUP),
+            recovery: params.recovery.unwrap_or(Self::DEFAULT_RECOVERY),
+            knockback: params.knockback.unwrap_or_default(),
+            rumble: params.rumble.unwrap_or_default(),
+        }
+    }
+
+    pub fn to_ability_params(&self) -> ActorAbilityParams {
+        ActorAbilityParams {
+            id: Some(self.id.clone()),
+            effect_kind: self.effect_kind.clone(),
+            action_kind: self.action_kind.clone(),
+            cooldown: Some(self.cooldown),
+            health_cost: self.health_cost,
+            stamina_cost: self.stamina_cost,
+            energy_cost: self.energy_cost,
+            speed: self.speed,
+            spread: self.spread,
+            range: self.range,
+            damage: self.damage,
+            damage_type: Some(self.damage_type.clone()),
+            effect_size: self.effect_size,
+            effect_color: json::Color::from(self.effect_color),
+            effect_sprite_animation_params: self.effect_sprite_animation_params.clone(),
+            status_effects: Some(self.status_effects.clone()),
+            sound_effect_id: self.sound_effect_id.clone(),
+            radius: Some(self.radius),
+            falloff: Some(self.falloff),
+            duration: Some(self.duration),
+            tick_interval: Some(self.tick_interval),
+            angle: Some(self.angle),
+            jumps: Some(self.jumps),
+            windup: Some(self.windup),
+            recovery: Some(self.recovery),
+            knockback: Some(self.knockback),
+            rumble: Some(self.rumble),
         }
     }
 
+    pub fn apply_modifiers(&mut self, modifiers: &AbilityModifiers) {
+        if let Some(damage_type) = &modifiers.damage_type {
+            if *damage_type != self.damage_type {
+                return;
+            }
+        }
+        let to_factor = |percentage: Option<f32>| (1.0 + percentage.unwrap_or_default() / 100.0).max(0.0);
+        self.damage *= to_factor(modifiers.damage);
+        // An ability that was ready stays ready, but one that is cooling down keeps its timer
+        let was_ready = self.cooldown_timer >= self.cooldown;
+        self.cooldown *= to_factor(modifiers.cooldown);
+        if was_ready {
+            self.cooldown_timer = self.cooldown_timer.max(self.cooldown);
+        }
+        let cost_factor = to_factor(modifiers.cost);
+        self.health_cost *= cost_factor;
+        self.stamina_cost *= cost_factor;
+        self.energy_cost *= cost_factor;
+        self.range *= to_factor(modifiers.range);
+    }
+
     pub fn activate(&mut self, actor: &mut Actor, origin: Vec2, target: Vec2) {
         if (self.health_cost == 0.0 || actor.stats.current_health >= self.health_cost)
             && (self.stamina_cost == 0.0 || actor.stats.current_stamina >= self.stamina_cost)
@@ -109,53 +242,142 @@ impl ActorAbility {
                 actor.stats.current_health -= self.health_cost;
                 actor.stats.current_stamina -= self.stamina_cost;
                 actor.stats.current_energy -= self.energy_cost;
+                // Continuous beams fire every frame, so their sound effect is only played as they start
+                if self.beam_timer >= Self::BEAM_RESTART_DELAY {
+                    if let Some(sound_effect_id) = &self.sound_effect_id {
+                        let audio_manager = scene::find_node_by_type::<AudioManager>().unwrap();
+                        audio_manager.play_sound_effect(sound_effect_id, Some(origin));
+                    }
+                }
+                self.beam_timer = 0.0;
                 let mut beams = scene::find_node_by_type::<ContinuousBeams>().unwrap();
                 let end = actor.body.position + target.sub(actor.body.position).normalize_or_zero() * self.range;
                 beams.spawn(
                     &actor.id,
                     &actor.factions,
-                    self.damage,
+                    Damage::roll(&self.damage_type, self.damage, &actor.stats),
+                    &self.status_effects,
                     self.effect_color,
                     self.effect_size,
                     actor.body.position,
                     end,
                 );
             } else if self.cooldown_timer >= self.cooldown {
-                let kind = if self.effect_kind == Self::PROJECTILE_EFFECT {
-                    ProjectileKind::Bullet
-                } else if self.effect_kind == Self::BEAM_EFFECT {
-                    ProjectileKind::Beam
-                } else if self.effect_kind == Self::ENERGY_SPHERE_EFFECT {
-                    ProjectileKind::EnergySphere
-                } else {
-                    assert!(false, "Invalid effect kind '{}'", self.effect_kind);
-                    ProjectileKind::Bullet
-                };
                 actor.stats.current_health -= self.health_cost;
                 actor.stats.current_stamina -= self.stamina_cost;
                 actor.stats.current_energy -= self.energy_cost;
                 self.cooldown_timer = 0.0;
-                let mut projectiles = scene::find_node_by_type::<Projectiles>().unwrap();
-                let ttl = self.range / self.speed;
-                projectiles.spawn(
-                    &actor.id,
-                    &actor.factions,
-                    kind,
-                    self.damage,
-                    self.effect_color,
-                    self.effect_size,
-                    origin,
-                    target,
-                    self.speed,
-                    self.spread,
-                    ttl,
-                    self.effect_sprite_animation_params.clone(),
-                );
+                if let Some(sound_effect_id) = &self.sound_effect_id {
+                    let audio_manager = scene::find_node_by_type::<AudioManager>().unwrap();
+                    audio_manager.play_sound_effect(sound_effect_id, Some(origin));
+                }
+                if self.rumble > 0.0 && actor.is_local_player() {
+                    rumble_gamepad(self.rumble, self.rumble, Self::RUMBLE_DURATION);
+                }
+                let damage = Damage::roll(&self.damage_type, self.damage, &actor.stats);
+                if self.effect_kind == Self::MELEE_EFFECT {
+                    actor.melee_attack = Some(MeleeAttack::new(
+                        damage,
+                        &self.status_effects,
+                        origin,
+                        target,
+                        actor.facing_direction,
+                        self.range,
+                        self.angle,
+                        self.knockback,
+                        self.windup,
+                        self.recovery,
+                    ));
+                } else if self.effect_kind == Self::ZONE_EFFECT {
+                    let mut area_effects = scene::find_node_by_type::<AreaEffects>().unwrap();
+                    let offset = target.sub(origin);
+                    let position = if offset.length() > self.range {
+                        origin + offset.normalize_or_zero() * self.range
+                    } else {
+                        target
+                    };
+                    area_effects.spawn_zone(
+                        &actor.id,
+                        &actor.factions,
+                        damage,
+                        &self.status_effects,
+                        self.effect_color,
+                        position,
+                        self.radius,
+                        self.duration,
+                        self.tick_interval,
+                    );
+                } else if self.effect_kind == Self::CONE_EFFECT {
+                    let mut area_effects = scene::find_node_by_type::<AreaEffects>().unwrap();
+                    area_effects.spawn_cone(
+                        &actor.id,
+                        &actor.factions,
+                        damage,
+                        &self.status_effects,
+                        self.effect_color,
+                        origin,
+                        target,
+                        self.range,
+                        self.angle,
+                    );
+                } else if self.effect_kind == Self::CHAIN_LIGHTNING_EFFECT {
+                    let mut area_effects = scene::find_node_by_type::<AreaEffects>().unwrap();
+                    let offset = target.sub(origin);
+                    let target = if offset.length() > self.range {
+                        origin + offset.normalize_or_zero() * self.range
+                    } else {
+                        target
+                    };
+                    area_effects.spawn_chain_lightning(
+                        &actor.id,
+                        &actor.factions,
+                        damage,
+                        &self.status_effects,
+                        self.effect_color,
+                        self.effect_size,
+                        origin,
+                        target,
+                        self.radius,
+                        self.jumps,
+                        self.falloff,
+                    );
+                } else {
+                    let kind = if self.effect_kind == Self::PROJECTILE_EFFECT {
+                        ProjectileKind::Bullet
+                    } else if self.effect_kind == Self::BEAM_EFFECT {
+                        ProjectileKind::Beam
+                    } else if self.effect_kind == Self::ENERGY_SPHERE_EFFECT {
+                        ProjectileKind::EnergySphere
+                    } else if self.effect_kind == Self::EXPLOSIVE_PROJECTILE_EFFECT {
+                        ProjectileKind::Explosive { radius: self.radius, falloff: self.falloff }
+                    } else {
+                        assert!(false, "Invalid effect kind '{}'", self.effect_kind);
+                        ProjectileKind::Bullet
+                    };
+                    let mut projectiles = scene::find_node_by_type::<Projectiles>().unwrap();
+                    let ttl = self.range / self.speed;
+                    projectiles.spawn(
+                        &actor.id,
+                        &actor.factions,
+                        kind,
+                        damage,
+                        &self.status_effects,
+                        self.effect_color,
+                        self.effect_size,
+                        origin,
+                        target,
+                        self.speed,
+                        self.spread,
+                        ttl,
+                        self.effect_sprite_animation_params.clone(),
+                    );
+                }
             }
         }
     }
 
     pub fn update(&mut self) {
         self.cooldown_timer += get_frame_time();
+        self.beam_timer += get_frame_time();
     }
 }
@@ -0,0 +1,381 @@
+use std::ops::Sub;
+
+use macroquad::prelude::*;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::{
+    nodes::{
+        Actor,
+        ActorAbility,
+        actor::DamageEvent,
+        GameState,
+        Item,
+    },
+    Factions,
+    FactionStance,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActorBehaviorParams {
+    pub kind: String,
+    pub children: Option<Vec<ActorBehaviorParams>>,
+    pub radius: Option<f32>,
+    pub range: Option<f32>,
+    pub threshold: Option<f32>,
+    pub ability: Option<String>,
+    pub is_sprinting: Option<bool>,
+}
+
+impl Default for ActorBehaviorParams {
+    fn default() -> Self {
+        ActorBehaviorParams {
+            kind: ActorBehavior::IDLE_NODE.to_string(),
+            children: None,
+            radius: None,
+            range: None,
+            threshold: None,
+            ability: None,
+            is_sprinting: None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum BehaviorStatus {
+    Success,
+    Failure,
+    Running,
+}
+
+#[derive(Clone)]
+enum BehaviorNode {
+    Selector(Vec<BehaviorNode>),
+    Sequence(Vec<BehaviorNode>),
+    Inverter(Box<BehaviorNode>),
+    FindTarget { radius: Option<f32> },
+    HasTarget,
+    IsHealthBelow { threshold: f32 },
+    IsTargetInRange { range: f32 },
+    Chase { range: f32, is_sprinting: bool },
+    Flee { is_sprinting: bool },
+    Attack { ability: String },
+    Idle,
+}
+
+impl BehaviorNode {
+    fn from(params: &ActorBehaviorParams) -> Self {
+        let children = || -> Vec<BehaviorNode> {
+            params.children
+                .as_ref()
+                .map(|children| children.iter().map(|child| BehaviorNode::from(child)).collect())
+                .unwrap_or_default()
+        };
+        let kind = params.kind.as_str();
+        if kind == ActorBehavior::SELECTOR_NODE {
+            BehaviorNode::Selector(children())
+        } else if kind == ActorBehavior::SEQUENCE_NODE {
+            BehaviorNode::Sequence(children())
+        } else if kind == ActorBehavior::INVERTER_NODE {
+            let mut children = children();
+            assert_eq!(children.len(), 1, "Behavior node '{}' must have exactly one child", kind);
+            BehaviorNode::Inverter(Box::new(children.remove(0)))
+        } else if kind == ActorBehavior::FIND_TARGET_NODE {
+            BehaviorNode::FindTarget { radius: params.radius }
+        } else if kind == ActorBehavior::HAS_TARGET_NODE {
+            BehaviorNode::HasTarget
+        } else if kind == ActorBehavior::IS_HEALTH_BELOW_NODE {
+            BehaviorNode::IsHealthBelow { threshold: params.threshold.unwrap_or(ActorBehavior::DEFAULT_HEALTH_THRESHOLD) }
+        } else if kind == ActorBehavior::IS_TARGET_IN_RANGE_NODE {
+            BehaviorNode::IsTargetInRange { range: params.range.unwrap_or(ActorBehavior::DEFAULT_ATTACK_RANGE) }
+        } else if kind == ActorBehavior::CHASE_NODE {
+            BehaviorNode::Chase {
+                range: params.range.unwrap_or(ActorBehavior::DEFAULT_CHASE_RANGE),
+                is_sprinting: params.is_sprinting.unwrap_or(false),
+            }
+        } else if kind == ActorBehavior::FLEE_NODE {
+            BehaviorNode::Flee { is_sprinting: params.is_sprinting.unwrap_or(true) }
+        } else if kind == ActorBehavior::ATTACK_NODE {
+            BehaviorNode::Attack { ability: params.ability.clone().unwrap_or(ActorAbility::PRIMARY_ABILITY.to_string()) }
+        } else if kind == ActorBehavior::IDLE_NODE {
+            BehaviorNode::Idle
+        } else {
+            assert!(false, "Invalid behavior node kind '{}'", kind);
+            BehaviorNode::Idle
+        }
+    }
+
+    fn tick(&self, blackboard: &mut Blackboard, actor: &mut Actor) -> BehaviorStatus {
+        match self {
+            BehaviorNode::Selector(children) => {
+                for child in children {
+                    let status = child.tick(blackboard, actor);
+                    if status != BehaviorStatus::Failure {
+                        return status;
+                    }
+                }
+                BehaviorStatus::Failure
+            }
+            BehaviorNode::Sequence(children) => {
+                for child in children {
+                    let status = child.tick(blackboard, actor);
+                    if status != BehaviorStatus::Success {
+                        return status;
+                    }
+                }
+                BehaviorStatus::Success
+            }
+            BehaviorNode::Inverter(child) => match child.tick(blackboard, actor) {
+                BehaviorStatus::Success => BehaviorStatus::Failure,
+                BehaviorStatus::Failure => BehaviorStatus::Success,
+                BehaviorStatus::Running => BehaviorStatus::Running,
+            },
+            BehaviorNode::FindTarget { radius } => {
+                let radius = radius.unwrap_or(actor.stats.get_perception() as f32 * ActorBehavior::PERCEPTION_RADIUS_FACTOR);
+                blackboard.find_target(actor, radius);
+                if blackboard.target_position.is_some() {
+                    BehaviorStatus::Success
+                } else {
+                    BehaviorStatus::Failure
+                }
+            }
+            BehaviorNode::HasTarget => {
+                if blackboard.target_position.is_some() {
+                    BehaviorStatus::Success
+                } else {
+                    BehaviorStatus::Failure
+                }
+            }
+            BehaviorNode::IsHealthBelow { threshold } => {
+                if actor.stats.current_health / actor.stats.max_health < *threshold {
+                    BehaviorStatus::Success
+                } else {
+                    BehaviorStatus::Failure
+                }
+            }
+            BehaviorNode::IsTargetInRange { range } => match blackboard.target_position {
+                Some(target) if actor.body.position.distance(target) <= *range => BehaviorStatus::Success,
+                _ => BehaviorStatus::Failure,
+            },
+            BehaviorNode::Chase { range, is_sprinting } => match blackboard.target_position {
+                Some(target) => {
+                    if actor.body.position.distance(target) <= *range {
+                        blackboard.path.clear();
+                        BehaviorStatus::Success
+                    } else {
+                        let waypoint = blackboard.get_next_waypoint(actor, target);
+                        actor.controller.direction = waypoint.sub(actor.body.position).normalize_or_zero();
+                        actor.controller.is_sprinting = *is_sprinting;
+                        BehaviorStatus::Running
+                    }
+                }
+                None => BehaviorStatus::Failure,
+            },
+            BehaviorNode::Flee { is_sprinting } => match blackboard.target_position {
+                Some(target) => {
+                    actor.controller.direction = actor.body.position.sub(target).normalize_or_zero();
+                    actor.controller.is_sprinting = *is_sprinting;
+                    BehaviorStatus::Running
+                }
+                None => BehaviorStatus::Failure,
+            },
+            BehaviorNode::Attack { ability } => match blackboard.target_position {
+                Some(target) => {
+                    if !equip_ability(actor, ability) {
+                        return BehaviorStatus::Failure;
+                    }
+                    if ability == ActorAbility::SECONDARY_ABILITY {
+                        actor.controller.secondary_target = Some(target);
+                    } else {
+                        actor.controller.primary_target = Some(target);
+                    }
+                    BehaviorStatus::Running
+                }
+                None => BehaviorStatus::Failure,
+            },
+            BehaviorNode::Idle => BehaviorStatus::Success,
+        }
+    }
+}
+
+// Computer controlled actors start out unarmed, so the first item in their inventory
+// that provides an ability of the requested kind is equipped when it is needed
+fn equip_ability(actor: &mut Actor, action_kind: &str) -> bool {
+    let is_equipped = if action_kind == ActorAbility::SECONDARY_ABILITY {
+        actor.secondary_ability.is_some()
+    } else {
+        actor.primary_ability.is_some()
+    };
+    if is_equipped {
+        return true;
+    }
+    let items = actor.inventory.get_all_of_kind(&[
+        Item::ONE_HANDED_WEAPON_KIND,
+        Item::TWO_HANDED_WEAPON_KIND,
+        Item::TRINKET_KIND,
+    ]);
+    for item in items {
+        if let Some(ability_params) = &item.params.ability_params {
+            if ability_params.action_kind == action_kind && actor.equip_item(&item.instance_id) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[derive(Clone, Default)]
+struct Blackboard {
+    target_id: Option<String>,
+    target_position: Option<Vec2>,
+    path: Vec<Vec2>,
+    path_goal: Option<Vec2>,
+    repath_timer: f32,
+    attacker_id: Option<String>,
+}
+
+impl Blackboard {
+    fn get_next_waypoint(&mut self, actor: &Actor, goal: Vec2) -> Vec2 {
+        // A path that ran out, or that could not be found, is only retried on an interval,
+        // unless the goal moves, so that unreachable goals don't run the path finding every frame
+        self.repath_timer -= get_frame_time();
+        let has_goal_moved = match self.path_goal {
+            Some(path_goal) => path_goal.distance(goal) > ActorBehavior::REPATH_DISTANCE,
+            None => true,
+        };
+        if has_goal_moved || (self.path.is_empty() && self.repath_timer <= 0.0) {
+            let mut game_state = scene::find_node_by_type::<GameState>().unwrap();
+            self.path = game_state.map
+                .find_path(actor.body.position, goal, actor.body.collider)
+                .unwrap_or_default();
+            self.path_goal = Some(goal);
+            self.repath_timer = ActorBehavior::REPATH_INTERVAL;
+        }
+        while let Some(waypoint) = self.path.first() {
+            if actor.body.position.distance(*waypoint) > ActorBehavior::WAYPOINT_RADIUS {
+                break;
+            }
+            self.path.remove(0);
+        }
+        // fall back to moving straight towards the goal, if no path could be found
+        self.path.first().cloned().unwrap_or(goal)
+    }
+
+    fn find_target(&mut self, actor: &Actor, radius: f32) {
+        let mut closest: Option<(String, Vec2, f32)> = None;
+        for other in scene::find_nodes_by_type::<Actor>() {
+            if other.id == actor.id || other.is_dead() || Factions::get_stance(&actor.factions, &other.factions) != FactionStance::Hostile {
+                continue;
+            }
+            let distance = actor.body.position.distance(other.body.position);
+            // Keep chasing the current target a bit further than we would pick up a new one,
+            // and retaliate against attackers that are outside of the perception radius
+            let max_distance = if self.attacker_id.as_ref() == Some(&other.id) {
+                radius * ActorBehavior::AGGRO_RADIUS_FACTOR
+            } else if self.target_id.as_ref() == Some(&other.id) {
+                radius * ActorBehavior::LOSE_TARGET_FACTOR
+            } else {
+                radius
+            };
+            if distance <= max_distance {
+                if closest.is_none() || distance < closest.as_ref().unwrap().2 {
+                    closest = Some((other.id.clone(), other.body.position, distance));
+                }
+            }
+        }
+        match closest {
+            Some((id, position, _)) => {
+                self.target_id = Some(id);
+                self.target_position = Some(position);
+            }
+            None => {
+                self.target_id = None;
+                self.target_position = None;
+                self.attacker_id = None;
+            }
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.target_position = None;
+        if let Some(target_id) = &self.target_id {
+            if let Some(target) = Actor::find_with_id(target_id) {
+                self.target_position = Some(target.body.position);
+            }
+        }
+        if self.target_position.is_none() {
+            self.target_id = None;
+            self.path.clear();
+            self.path_goal = None;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ActorBehavior {
+    params: ActorBehaviorParams,
+    root: BehaviorNode,
+    blackboard: Blackboard,
+}
+
+impl ActorBehavior {
+    pub const SELECTOR_NODE: &'static str = "selector";
+    pub const SEQUENCE_NODE: &'static str = "sequence";
+    pub const INVERTER_NODE: &'static str = "inverter";
+    pub const FIND_TARGET_NODE: &'static str = "find_target";
+    pub const HAS_TARGET_NODE: &'static str = "has_target";
+    pub const IS_HEALTH_BELOW_NODE: &'static str = "is_health_below";
+    pub const IS_TARGET_IN_RANGE_NODE: &'static str = "is_target_in_range";
+    pub const CHASE_NODE: &'static str = "chase";
+    pub const FLEE_NODE: &'static str = "flee";
+    pub const ATTACK_NODE: &'static str = "attack";
+    pub const IDLE_NODE: &'static str = "idle";
+
+    const PERCEPTION_RADIUS_FACTOR: f32 = 25.0;
+    const LOSE_TARGET_FACTOR: f32 = 1.5;
+    const AGGRO_RADIUS_FACTOR: f32 = 3.0;
+
+    const WAYPOINT_RADIUS: f32 = 4.0;
+    const REPATH_DISTANCE: f32 = 32.0;
+    // Seconds between attempts to find a path to a goal that could not be reached
+    const REPATH_INTERVAL: f32 = 1.0;
+
+    const DEFAULT_HEALTH_THRESHOLD: f32 = 0.25;
+    const DEFAULT_ATTACK_RANGE: f32 = 150.0;
+    const DEFAULT_CHASE_RANGE: f32 = 100.0;
+
+    pub fn new(params: ActorBehaviorParams) -> Self {
+        ActorBehavior {
+            root: BehaviorNode::from(&params),
+            params,
+            blackboard: Default::default(),
+        }
+    }
+
+    pub fn get_target_id(&self) -> Option<String> {
+        self.blackboard.target_id.clone()
+    }
+
+    pub fn on_damage(&mut self, event: &DamageEvent) {
+        if event.attacker_id != event.target_id {
+            self.blackboard.attacker_id = Some(event.attacker_id.clone());
+        }
+    }
+
+    pub fn to_behavior_params(&self) -> ActorBehaviorParams {
+        self.params.clone()
+    }
+
+    pub fn update(&mut self, actor: &mut Actor) -> BehaviorStatus {
+        actor.controller.direction = Vec2::ZERO;
+        actor.controller.primary_target = None;
+        actor.controller.secondary_target = None;
+        actor.controller.ability_bar_target = None;
+        actor.controller.is_sprinting = false;
+        self.blackboard.refresh();
+        self.root.tick(&mut self.blackboard, actor)
+    }
+}
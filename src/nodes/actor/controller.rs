@@ -12,6 +12,8 @@ pub struct ActorController {
     pub kind: ActorControllerKind,
     pub primary_target: Option<Vec2>,
     pub secondary_target: Option<Vec2>,
+    pub ability_bar_target: Option<(usize, Vec2)>,
+    pub quick_use_slot: Option<usize>,
     pub direction: Vec2,
     pub is_interacting: bool,
     pub is_picking_up_items: bool,
@@ -24,6 +26,8 @@ impl ActorController {
             kind,
             primary_target: None,
             secondary_target: None,
+            ability_bar_target: None,
+            quick_use_slot: None,
             direction: Vec2::ZERO,
             is_interacting: false,
             is_picking_up_items: false,
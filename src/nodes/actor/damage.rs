@@ -0,0 +1,65 @@
+use macroquad::prelude::*;
+
+use crate::nodes::actor::ActorStats;
+
+#[derive(Clone)]
+pub struct Damage {
+    pub damage_type: String,
+    pub amount: f32,
+    pub is_critical: bool,
+}
+
+impl Damage {
+    pub const KINETIC_TYPE: &'static str = "kinetic";
+    pub const ENERGY_TYPE: &'static str = "energy";
+    pub const FIRE_TYPE: &'static str = "fire";
+    pub const PSYCHIC_TYPE: &'static str = "psychic";
+
+    const CRITICAL_MULTIPLIER: f32 = 1.5;
+
+    pub fn is_valid_type(damage_type: &str) -> bool {
+        damage_type == Self::KINETIC_TYPE
+            || damage_type == Self::ENERGY_TYPE
+            || damage_type == Self::FIRE_TYPE
+            || damage_type == Self::PSYCHIC_TYPE
+    }
+
+    pub fn new(damage_type: &str, amount: f32) -> Self {
+        Damage {
+            damage_type: damage_type.to_string(),
+            amount,
+            is_critical: false,
+        }
+    }
+
+    /// Rolls for a critical hit, based on the stats of the attacker
+    pub fn roll(damage_type: &str, amount: f32, stats: &ActorStats) -> Self {
+        let is_critical = rand::gen_range(0.0, 1.0) < stats.get_critical_chance();
+        Damage {
+            damage_type: damage_type.to_string(),
+            amount: if is_critical { amount * Self::CRITICAL_MULTIPLIER } else { amount },
+            is_critical,
+        }
+    }
+}
+
+/// Records damage dealt to an actor, after mitigation, so that other systems can react to it
+#[derive(Clone)]
+pub struct DamageEvent {
+    pub attacker_id: String,
+    pub target_id: String,
+    pub damage_type: String,
+    pub amount: f32,
+    pub mitigated: f32,
+    pub is_critical: bool,
+    pub is_fatal: bool,
+}
+
+/// Records that an actor was killed, so that the killer can be rewarded for it
+#[derive(Clone)]
+pub struct KillEvent {
+    pub killer_id: String,
+    pub victim_name: String,
+    pub victim_factions: Vec<String>,
+    pub experience: u32,
+}
@@ -0,0 +1,300 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::{
+    get_global,
+    get_global_mut,
+    Factions,
+    nodes::actor::{
+        Actor,
+        ActorInventoryEntry,
+        ActorStats,
+    },
+    show_notice,
+    Resources,
+};
+
+/// A condition that the player has to meet for a dialogue choice to be available.
+/// Which fields are required depends on the kind:
+/// `has_item` requires `item_id`, `stat_check` requires `attribute` and `value`,
+/// `faction_standing` requires `faction` and `has_quest` and `has_completed_quest` require `quest_id`.
+/// If `reputation` is set, `faction_standing` requires the player to have at least that much reputation with the
+/// faction, otherwise it requires the player to be a member of it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DialogueRequirement {
+    pub kind: String,
+    pub item_id: Option<String>,
+    pub attribute: Option<String>,
+    pub value: Option<u32>,
+    pub faction: Option<String>,
+    pub reputation: Option<i32>,
+    pub quest_id: Option<String>,
+}
+
+impl DialogueRequirement {
+    pub const HAS_ITEM_KIND: &'static str = "has_item";
+    pub const STAT_CHECK_KIND: &'static str = "stat_check";
+    pub const FACTION_STANDING_KIND: &'static str = "faction_standing";
+    pub const HAS_QUEST_KIND: &'static str = "has_quest";
+    pub const HAS_COMPLETED_QUEST_KIND: &'static str = "has_completed_quest";
+
+    /// Checks that the kind is valid and that the fields it requires are set and reference existing data
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        if self.kind == Self::HAS_ITEM_KIND {
+            validate_item_id(&self.item_id, &self.kind, resources)
+        } else if self.kind == Self::STAT_CHECK_KIND {
+            match &self.attribute {
+                Some(attribute) if ActorStats::is_valid_attribute(attribute) => Ok(()),
+                Some(attribute) => Err(format!("Invalid attribute '{}'", attribute)),
+                None => Err(format!("Requirement of kind '{}' has no attribute", self.kind)),
+            }
+        } else if self.kind == Self::FACTION_STANDING_KIND {
+            validate_faction(&self.faction, &self.kind, resources)
+        } else if self.kind == Self::HAS_QUEST_KIND || self.kind == Self::HAS_COMPLETED_QUEST_KIND {
+            validate_quest_id(&self.quest_id, &self.kind, resources)
+        } else {
+            Err(format!("Invalid requirement kind '{}'", self.kind))
+        }
+    }
+
+    /// Requirements are validated when the resources are loaded, so an invalid requirement is never met
+    pub fn is_met(&self, player: &Actor) -> bool {
+        if self.kind == Self::HAS_ITEM_KIND {
+            match &self.item_id {
+                Some(item_id) => player.inventory.has_item(item_id),
+                None => false,
+            }
+        } else if self.kind == Self::STAT_CHECK_KIND {
+            match &self.attribute {
+                Some(attribute) if ActorStats::is_valid_attribute(attribute) => {
+                    player.stats.get_attribute(attribute) >= self.value.unwrap_or(0)
+                }
+                _ => false,
+            }
+        } else if self.kind == Self::FACTION_STANDING_KIND {
+            match (&self.faction, self.reputation) {
+                (Some(faction), Some(reputation)) => get_global::<Factions>().get_reputation(faction) >= reputation,
+                (Some(faction), None) => player.factions.contains(faction),
+                (None, _) => false,
+            }
+        } else if self.kind == Self::HAS_QUEST_KIND {
+            match &self.quest_id {
+                Some(quest_id) => player.has_quest(quest_id),
+                None => false,
+            }
+        } else if self.kind == Self::HAS_COMPLETED_QUEST_KIND {
+            match &self.quest_id {
+                Some(quest_id) => player.has_completed_quest(quest_id),
+                None => false,
+            }
+        } else {
+            false
+        }
+    }
+}
+
+/// An effect of picking a dialogue choice.
+/// `give_item` and `take_item` require `item_id`, `start_quest` requires `quest_id`,
+/// `join_faction` and `leave_faction` require `faction` and `change_reputation` requires `faction` and `amount`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DialogueAction {
+    pub kind: String,
+    pub item_id: Option<String>,
+    pub quest_id: Option<String>,
+    pub faction: Option<String>,
+    pub amount: Option<i32>,
+}
+
+impl DialogueAction {
+    pub const GIVE_ITEM_KIND: &'static str = "give_item";
+    pub const TAKE_ITEM_KIND: &'static str = "take_item";
+    pub const START_QUEST_KIND: &'static str = "start_quest";
+    pub const JOIN_FACTION_KIND: &'static str = "join_faction";
+    pub const LEAVE_FACTION_KIND: &'static str = "leave_faction";
+    pub const CHANGE_REPUTATION_KIND: &'static str = "change_reputation";
+
+    /// Checks that the kind is valid and that the fields it requires are set and reference existing data
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        if self.kind == Self::GIVE_ITEM_KIND || self.kind == Self::TAKE_ITEM_KIND {
+            validate_item_id(&self.item_id, &self.kind, resources)
+        } else if self.kind == Self::START_QUEST_KIND {
+            validate_quest_id(&self.quest_id, &self.kind, resources)
+        } else if self.kind == Self::JOIN_FACTION_KIND
+            || self.kind == Self::LEAVE_FACTION_KIND
+            || self.kind == Self::CHANGE_REPUTATION_KIND {
+            validate_faction(&self.faction, &self.kind, resources)
+        } else {
+            Err(format!("Invalid action kind '{}'", self.kind))
+        }
+    }
+
+    /// Actions are validated when the resources are loaded, so an invalid action is skipped
+    pub fn apply(&self, player: &mut Actor) {
+        if self.kind == Self::GIVE_ITEM_KIND {
+            let params = self.item_id
+                .as_ref()
+                .and_then(|item_id| get_global::<Resources>().try_get_item(item_id).cloned());
+            if let Some(params) = params {
+                player.add_to_inventory(ActorInventoryEntry::new(params));
+            }
+        } else if self.kind == Self::TAKE_ITEM_KIND {
+            if let Some(item_id) = &self.item_id {
+                player.inventory.take_item(item_id);
+            }
+        } else if self.kind == Self::START_QUEST_KIND {
+            if let Some(quest_id) = &self.quest_id {
+                player.start_quest(quest_id);
+            }
+        } else if self.kind == Self::JOIN_FACTION_KIND {
+            if let Some(faction) = &self.faction {
+                if player.factions.contains(faction) == false {
+                    player.factions.push(faction.clone());
+                }
+            }
+        } else if self.kind == Self::LEAVE_FACTION_KIND {
+            if let Some(faction) = &self.faction {
+                player.factions.retain(|other| other != faction);
+            }
+        } else if self.kind == Self::CHANGE_REPUTATION_KIND {
+            if let Some(faction) = &self.faction {
+                get_global_mut::<Factions>().add_reputation(faction, self.amount.unwrap_or(0));
+            }
+        }
+    }
+}
+
+fn validate_item_id(item_id: &Option<String>, kind: &str, resources: &Resources) -> Result<(), String> {
+    match item_id {
+        Some(item_id) if resources.try_get_item(item_id).is_some() => Ok(()),
+        Some(item_id) => Err(format!("Unable to find item '{}'", item_id)),
+        None => Err(format!("Entry of kind '{}' has no item id", kind)),
+    }
+}
+
+fn validate_quest_id(quest_id: &Option<String>, kind: &str, resources: &Resources) -> Result<(), String> {
+    match quest_id {
+        Some(quest_id) if resources.try_get_quest(quest_id).is_some() => Ok(()),
+        Some(quest_id) => Err(format!("Unable to find quest '{}'", quest_id)),
+        None => Err(format!("Entry of kind '{}' has no quest id", kind)),
+    }
+}
+
+fn validate_faction(faction: &Option<String>, kind: &str, resources: &Resources) -> Result<(), String> {
+    match faction {
+        Some(faction) if resources.try_get_faction(faction).is_some() => Ok(()),
+        Some(faction) => Err(format!("Unable to find faction '{}'", faction)),
+        None => Err(format!("Entry of kind '{}' has no faction", kind)),
+    }
+}
+
+/// A player response. If `next` is `None`, picking it ends the dialogue.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    pub requirements: Option<Vec<DialogueRequirement>>,
+    pub actions: Option<Vec<DialogueAction>>,
+    pub next: Option<String>,
+}
+
+impl DialogueChoice {
+    pub fn is_available(&self, player: &Actor) -> bool {
+        self.requirements
+            .as_ref()
+            .map(|requirements| requirements.iter().all(|requirement| requirement.is_met(player)))
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DialogueNode {
+    pub id: String,
+    pub text: String,
+    pub choices: Option<Vec<DialogueChoice>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DialogueParams {
+    pub id: String,
+    pub start: String,
+    pub nodes: Vec<DialogueNode>,
+}
+
+impl DialogueParams {
+    pub fn get_node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Checks the requirements and actions of every choice, and that every node that is referenced exists
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        if self.get_node(&self.start).is_none() {
+            return Err(format!("Unable to find start node '{}'", self.start));
+        }
+        for node in &self.nodes {
+            for choice in node.choices.iter().flatten() {
+                for requirement in choice.requirements.iter().flatten() {
+                    requirement.validate(resources)
+                        .map_err(|err| format!("{} in node '{}'", err, node.id))?;
+                }
+                for action in choice.actions.iter().flatten() {
+                    action.validate(resources)
+                        .map_err(|err| format!("{} in node '{}'", err, node.id))?;
+                }
+                if let Some(next) = &choice.next {
+                    if self.get_node(next).is_none() {
+                        return Err(format!("Unable to find node '{}' in node '{}'", next, node.id));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A conversation in progress between the local player and the actor with the id `actor_id`
+#[derive(Clone)]
+pub struct Dialogue {
+    pub actor_id: String,
+    pub actor_name: String,
+    pub dialogue_id: String,
+    pub node_id: String,
+}
+
+impl Dialogue {
+    /// The dialogue is closed if the player moves further away from the other actor than this
+    pub const MAX_DISTANCE: f32 = 64.0;
+
+    pub fn new(actor: &Actor, dialogue_id: &str) -> Option<Self> {
+        let resources = get_global::<Resources>();
+        match resources.try_get_dialogue(dialogue_id) {
+            Some(params) => Some(Dialogue {
+                actor_id: actor.id.clone(),
+                actor_name: actor.name.clone(),
+                dialogue_id: dialogue_id.to_string(),
+                node_id: params.start.clone(),
+            }),
+            None => {
+                show_notice(&format!("Unable to find dialogue '{}' of actor '{}'", dialogue_id, actor.name));
+                None
+            }
+        }
+    }
+
+    /// Applies the actions of the choice and moves to the node it leads to.
+    /// Returns `false` if the dialogue has ended.
+    pub fn select_choice(&mut self, choice: &DialogueChoice, player: &mut Actor) -> bool {
+        if let Some(actions) = &choice.actions {
+            for action in actions {
+                action.apply(player);
+            }
+        }
+        match &choice.next {
+            Some(next) => {
+                self.node_id = next.clone();
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::{
+    get_global,
+    nodes::{
+        actor::ActorInventoryEntry,
+        Item,
+    },
+    Resources,
+};
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ActorEquipmentParams {
+    pub head: Option<String>,
+    pub body: Option<String>,
+    pub legs: Option<String>,
+    pub main_hand: Option<String>,
+    pub off_hand: Option<String>,
+    pub trinkets: Vec<String>,
+}
+
+impl ActorEquipmentParams {
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        if self.trinkets.len() > ActorEquipment::TRINKET_SLOTS {
+            return Err(format!("Actors can not have more than {} trinkets equipped", ActorEquipment::TRINKET_SLOTS));
+        }
+        let mut equipped = Vec::new();
+        let slots = vec!(
+            (EquipmentSlot::Head, &self.head),
+            (EquipmentSlot::Body, &self.body),
+            (EquipmentSlot::Legs, &self.legs),
+            (EquipmentSlot::MainHand, &self.main_hand),
+            (EquipmentSlot::OffHand, &self.off_hand),
+        );
+        for (slot, item_id) in slots {
+            if let Some(item_id) = item_id {
+                equipped.push((slot, item_id));
+            }
+        }
+        for (i, item_id) in self.trinkets.iter().enumerate() {
+            equipped.push((EquipmentSlot::Trinket(i), item_id));
+        }
+        for (slot, item_id) in equipped {
+            let params = resources.try_get_item(item_id)
+                .ok_or(format!("Unable to find item '{}'", item_id))?;
+            if ActorEquipment::get_valid_slots(&params.kind).contains(&slot) == false {
+                return Err(format!("Item '{}' of kind '{}' can not be equipped in the {} slot", params.id, params.kind, slot.get_label()));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum EquipmentSlot {
+    Head,
+    Body,
+    Legs,
+    MainHand,
+    OffHand,
+    Trinket(usize),
+}
+
+impl EquipmentSlot {
+    pub fn get_label(&self) -> String {
+        match self {
+            EquipmentSlot::Head => "Head".to_string(),
+            EquipmentSlot::Body => "Body".to_string(),
+            EquipmentSlot::Legs => "Legs".to_string(),
+            EquipmentSlot::MainHand => "Main Hand".to_string(),
+            EquipmentSlot::OffHand => "Off Hand".to_string(),
+            EquipmentSlot::Trinket(i) => format!("Trinket {}", i + 1),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ActorEquipment {
+    head: Option<ActorInventoryEntry>,
+    body: Option<ActorInventoryEntry>,
+    legs: Option<ActorInventoryEntry>,
+    main_hand: Option<ActorInventoryEntry>,
+    off_hand: Option<ActorInventoryEntry>,
+    trinkets: [Option<ActorInventoryEntry>; Self::TRINKET_SLOTS],
+}
+
+impl ActorEquipment {
+    pub const TRINKET_SLOTS: usize = 2;
+
+    pub fn new(params: &ActorEquipmentParams) -> Self {
+        let resources = get_global::<Resources>();
+        let to_entry = |item_id: &Option<String>| -> Option<ActorInventoryEntry> {
+            item_id.as_ref().map(|id| ActorInventoryEntry::new(resources.get_item(id).clone()))
+        };
+        let mut equipment = ActorEquipment {
+            head: to_entry(&params.head),
+            body: to_entry(&params.body),
+            legs: to_entry(&params.legs),
+            main_hand: to_entry(&params.main_hand),
+            off_hand: to_entry(&params.off_hand),
+            trinkets: Default::default(),
+        };
+        assert!(params.trinkets.len() <= Self::TRINKET_SLOTS, "Actors can not have more than {} trinkets equipped", Self::TRINKET_SLOTS);
+        for (i, item_id) in params.trinkets.iter().enumerate() {
+            equipment.trinkets[i] = Some(ActorInventoryEntry::new(resources.get_item(item_id).clone()));
+        }
+        for (slot, entry) in equipment.get_all() {
+            assert!(Self::get_valid_slots(&entry.params.kind).contains(&slot),
+                    "Item '{}' of kind '{}' can not be equipped in the {} slot", entry.params.id, entry.params.kind, slot.get_label());
+        }
+        equipment
+    }
+
+    /// Returns the slots that an item of the specified kind can be equipped in, in order of preference
+    pub fn get_valid_slots(kind: &str) -> Vec<EquipmentSlot> {
+        if kind == Item::HEAD_ARMOR_KIND {
+            vec!(EquipmentSlot::Head)
+        } else if kind == Item::BODY_ARMOR_KIND {
+            vec!(EquipmentSlot::Body)
+        } else if kind == Item::LEG_ARMOR_KIND {
+            vec!(EquipmentSlot::Legs)
+        } else if kind == Item::ONE_HANDED_WEAPON_KIND {
+            vec!(EquipmentSlot::MainHand, EquipmentSlot::OffHand)
+        } else if kind == Item::TWO_HANDED_WEAPON_KIND {
+            vec!(EquipmentSlot::MainHand)
+        } else if kind == Item::TRINKET_KIND {
+            (0..Self::TRINKET_SLOTS).map(|i| EquipmentSlot::Trinket(i)).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn is_equippable(kind: &str) -> bool {
+        !Self::get_valid_slots(kind).is_empty()
+    }
+
+    pub fn get(&self, slot: EquipmentSlot) -> Option<&ActorInventoryEntry> {
+        match slot {
+            EquipmentSlot::Head => self.head.as_ref(),
+            EquipmentSlot::Body => self.body.as_ref(),
+            EquipmentSlot::Legs => self.legs.as_ref(),
+            EquipmentSlot::MainHand => self.main_hand.as_ref(),
+            EquipmentSlot::OffHand => self.off_hand.as_ref(),
+            EquipmentSlot::Trinket(i) => self.trinkets.get(i).and_then(|entry| entry.as_ref()),
+        }
+    }
+
+    fn get_mut(&mut self, slot: EquipmentSlot) -> &mut Option<ActorInventoryEntry> {
+        match slot {
+            EquipmentSlot::Head => &mut self.head,
+            EquipmentSlot::Body => &mut self.body,
+            EquipmentSlot::Legs => &mut self.legs,
+            EquipmentSlot::MainHand => &mut self.main_hand,
+            EquipmentSlot::OffHand => &mut self.off_hand,
+            EquipmentSlot::Trinket(i) => &mut self.trinkets[i],
+        }
+    }
+
+    pub fn get_all(&self) -> Vec<(EquipmentSlot, &ActorInventoryEntry)> {
+        let mut slots = vec!(
+            EquipmentSlot::Head,
+            EquipmentSlot::Body,
+            EquipmentSlot::Legs,
+            EquipmentSlot::MainHand,
+            EquipmentSlot::OffHand,
+        );
+        slots.extend((0..Self::TRINKET_SLOTS).map(|i| EquipmentSlot::Trinket(i)));
+        slots.into_iter()
+            .filter_map(|slot| self.get(slot).map(|entry| (slot, entry)))
+            .collect()
+    }
+
+    pub fn is_two_handed(&self) -> bool {
+        match &self.main_hand {
+            Some(entry) => entry.params.kind == Item::TWO_HANDED_WEAPON_KIND,
+            None => false,
+        }
+    }
+
+    /// Picks the first free slot that is valid for the item, or the first valid slot, if all are taken
+    pub fn get_slot_for(&self, entry: &ActorInventoryEntry) -> Option<EquipmentSlot> {
+        let slots = Self::get_valid_slots(&entry.params.kind);
+        for slot in &slots {
+            if self.get(*slot).is_none() && !(*slot == EquipmentSlot::OffHand && self.is_two_handed()) {
+                return Some(*slot);
+            }
+        }
+        slots.first().cloned()
+    }
+
+    /// Equips the entry in the specified slot, returning any entries that were unequipped to make room for it.
+    /// If the item can not be equipped in the slot, the entry is returned as an error.
+    pub fn equip(&mut self, slot: EquipmentSlot, entry: ActorInventoryEntry) -> Result<Vec<ActorInventoryEntry>, ActorInventoryEntry> {
+        if !Self::get_valid_slots(&entry.params.kind).contains(&slot) {
+            return Err(entry);
+        }
+        let mut unequipped = Vec::new();
+        if entry.params.kind == Item::TWO_HANDED_WEAPON_KIND {
+            if let Some(off_hand) = self.off_hand.take() {
+                unequipped.push(off_hand);
+            }
+        } else if slot == EquipmentSlot::OffHand && self.is_two_handed() {
+            if let Some(main_hand) = self.main_hand.take() {
+                unequipped.push(main_hand);
+            }
+        }
+        if let Some(previous) = self.get_mut(slot).replace(entry) {
+            unequipped.push(previous);
+        }
+        Ok(unequipped)
+    }
+
+    pub fn unequip(&mut self, slot: EquipmentSlot) -> Option<ActorInventoryEntry> {
+        self.get_mut(slot).take()
+    }
+
+    pub fn unequip_all(&mut self) -> Vec<ActorInventoryEntry> {
+        let slots: Vec<EquipmentSlot> = self.get_all().into_iter().map(|(slot, _)| slot).collect();
+        slots.into_iter().filter_map(|slot| self.unequip(slot)).collect()
+    }
+
+    pub fn get_total_weight(&self) -> f32 {
+        self.get_all().into_iter().map(|(_, entry)| entry.params.weight).sum()
+    }
+
+    pub fn to_equipment_params(&self) -> ActorEquipmentParams {
+        let to_id = |entry: &Option<ActorInventoryEntry>| entry.as_ref().map(|entry| entry.params.id.clone());
+        ActorEquipmentParams {
+            head: to_id(&self.head),
+            body: to_id(&self.body),
+            legs: to_id(&self.legs),
+            main_hand: to_id(&self.main_hand),
+            off_hand: to_id(&self.off_hand),
+            trinkets: self.trinkets.iter().filter_map(|entry| to_id(entry)).collect(),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::nodes::actor::ActorStatModifiers;
+
+/// Percentage modifiers that a feat applies to abilities.
+/// If a damage type is specified, only abilities that deal that type of damage are modified.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct AbilityModifiers {
+    pub damage_type: Option<String>,
+    pub damage: Option<f32>,
+    pub cooldown: Option<f32>,
+    pub cost: Option<f32>,
+    pub range: Option<f32>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FeatParams {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub stat_modifiers: Option<ActorStatModifiers>,
+    pub ability_modifiers: Option<AbilityModifiers>,
+}
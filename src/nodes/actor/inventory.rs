@@ -2,12 +2,16 @@ use macroquad::{
     experimental::{
         scene::{
             Handle,
-            RefMut,
         },
     },
     prelude::*,
 };
 
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
 use crate::{
     ItemParams,
     Item,
@@ -18,29 +22,52 @@ use crate::{
     Resources,
 };
 
+/// A stack of items, as it is defined in actors and saved with actors, corpses and containers.
+/// `quantity` defaults to one.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActorInventoryEntryParams {
+    pub item_id: String,
+    pub quantity: Option<u32>,
+}
+
+impl ActorInventoryEntryParams {
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        if resources.try_get_item(&self.item_id).is_none() {
+            return Err(format!("Unable to find item '{}'", self.item_id));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct ActorInventoryEntry {
     pub instance_id: String,
     pub params: ItemParams,
+    pub quantity: u32,
     pub sprite: Sprite,
 }
 
 impl ActorInventoryEntry {
     pub fn new(params: ItemParams) -> Self {
-        let id = generate_id();
+        Self::with_quantity(params, 1)
+    }
+
+    pub fn with_quantity(params: ItemParams, quantity: u32) -> Self {
         let sprite = Sprite::new(params.sprite_params.clone());
         ActorInventoryEntry {
-            instance_id: id.clone(),
-            params: ItemParams {
-                id,
-                ..params
-            },
+            instance_id: generate_id(),
+            params,
+            quantity,
             sprite,
         }
     }
 
-    pub fn to_actor_ability(&self) -> ActorAbility {
-        ActorAbility::new(self.params.ability_params.clone())
+    pub fn get_weight(&self) -> f32 {
+        self.params.weight * self.quantity as f32
+    }
+
+    pub fn to_actor_ability(&self) -> Option<ActorAbility> {
+        self.params.ability_params.clone().map(|params| ActorAbility::new(params))
     }
 }
 
@@ -52,14 +79,20 @@ pub struct ActorInventory {
 impl ActorInventory {
     const DROP_ALL_POSITION_VARIANCE: f32 = 15.0;
 
-    pub fn new(items: &[String]) -> Self {
-        ActorInventory {
-            items: items.iter().map(|item_id| {
-                let resources = get_global::<Resources>();
-                let params = resources.get_item(item_id);
-                ActorInventoryEntry::new(params.clone())
-            }).collect(),
+    pub fn new(entries: &[ActorInventoryEntryParams]) -> Self {
+        let mut inventory = ActorInventory {
+            items: Vec::new(),
+        };
+        let resources = get_global::<Resources>();
+        for entry in entries {
+            let params = resources.get_item(&entry.item_id).clone();
+            inventory.add_entry(ActorInventoryEntry::with_quantity(params, entry.quantity.unwrap_or(1)));
         }
+        inventory
+    }
+
+    pub fn get_all(&self) -> Vec<ActorInventoryEntry> {
+        self.items.clone()
     }
 
     pub fn get_all_of_kind(&self, kinds: &[&'static str]) -> Vec<ActorInventoryEntry> {
@@ -73,22 +106,116 @@ impl ActorInventory {
         }).collect()
     }
 
-    pub fn pick_up_item(&mut self, item: RefMut<Item>) {
-        self.items.push(ActorInventoryEntry::new(item.params.clone()));
-        item.delete();
+    pub fn get_entry(&self, instance_id: &str) -> Option<&ActorInventoryEntry> {
+        self.items.iter().find(|entry| entry.instance_id == instance_id)
+    }
+
+    /// Adds the entry to existing stacks of the same item, as far as their max stack size allows,
+    /// and puts the rest in new stacks
+    pub fn add_entry(&mut self, mut entry: ActorInventoryEntry) {
+        let max_stack = entry.params.get_max_stack();
+        for other in &mut self.items {
+            if entry.quantity == 0 {
+                return;
+            }
+            if other.params.id == entry.params.id && other.quantity < max_stack {
+                let amount = entry.quantity.min(max_stack - other.quantity);
+                other.quantity += amount;
+                entry.quantity -= amount;
+            }
+        }
+        while entry.quantity > max_stack {
+            entry.quantity -= max_stack;
+            self.items.push(ActorInventoryEntry::with_quantity(entry.params.clone(), max_stack));
+        }
+        if entry.quantity > 0 {
+            self.items.push(entry);
+        }
+    }
+
+    /// Removes the whole stack
+    pub fn take_entry(&mut self, instance_id: &str) -> Option<ActorInventoryEntry> {
+        let index = self.items.iter().position(|entry| entry.instance_id == instance_id)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Removes up to `quantity` items from the stack and returns them as a new entry.
+    /// The stack is removed if it is emptied.
+    pub fn take_quantity(&mut self, instance_id: &str, quantity: u32) -> Option<ActorInventoryEntry> {
+        let index = self.items.iter().position(|entry| entry.instance_id == instance_id)?;
+        if quantity >= self.items[index].quantity {
+            return Some(self.items.remove(index));
+        }
+        let entry = &mut self.items[index];
+        entry.quantity -= quantity;
+        Some(ActorInventoryEntry::with_quantity(entry.params.clone(), quantity))
+    }
+
+    /// Splits `quantity` items off into a new stack and returns its instance id
+    pub fn split_entry(&mut self, instance_id: &str, quantity: u32) -> Option<String> {
+        let entry = self.get_entry(instance_id)?;
+        if quantity == 0 || quantity >= entry.quantity {
+            return None;
+        }
+        let entry = self.take_quantity(instance_id, quantity)?;
+        let instance_id = entry.instance_id.clone();
+        // This is pushed directly, as adding it would merge it back into the stack it was split from
+        self.items.push(entry);
+        Some(instance_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn take_all(&mut self) -> Vec<ActorInventoryEntry> {
+        self.items.drain(..).collect()
+    }
+
+    pub fn get_first_of_item(&self, item_id: &str) -> Option<&ActorInventoryEntry> {
+        self.items.iter().find(|entry| entry.params.id == item_id)
+    }
+
+    pub fn has_item(&self, item_id: &str) -> bool {
+        self.items.iter().any(|entry| entry.params.id == item_id)
     }
 
+    pub fn count_item(&self, item_id: &str) -> u32 {
+        self.items
+            .iter()
+            .filter(|entry| entry.params.id == item_id)
+            .map(|entry| entry.quantity)
+            .sum()
+    }
+
+    /// Removes one of the item with the specified item id, from the first stack of it
+    pub fn take_item(&mut self, item_id: &str) -> Option<ActorInventoryEntry> {
+        let instance_id = self.items.iter().find(|entry| entry.params.id == item_id)?.instance_id.clone();
+        self.take_quantity(&instance_id, 1)
+    }
+
+    /// Drops the whole stack
     pub fn drop_item(&mut self, item_id: &str, position: Vec2) -> bool {
         let items: Vec<Handle<Item>> = self.items
             .drain_filter(|entry| entry.instance_id == item_id)
-            .map(|entry| Item::add_node(Self::randomize_drop_position(position), entry.params))
+            .map(|entry| Item::add_node(Self::randomize_drop_position(position), entry.params, entry.quantity))
             .collect();
         !items.is_empty()
     }
 
+    pub fn drop_quantity(&mut self, instance_id: &str, quantity: u32, position: Vec2) -> bool {
+        match self.take_quantity(instance_id, quantity) {
+            Some(entry) => {
+                Item::add_node(Self::randomize_drop_position(position), entry.params, entry.quantity);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn drop_all(&mut self, position: Vec2) {
         self.items.drain_filter(|entry| {
-            Item::add_node(Self::randomize_drop_position(position), entry.params.clone());
+            Item::add_node(Self::randomize_drop_position(position), entry.params.clone(), entry.quantity);
            true
         });
     }
@@ -96,7 +223,7 @@ impl ActorInventory {
     pub fn get_total_weight(&self) -> f32 {
         let mut weight = 0.0;
         for item in &self.items {
-            weight += item.params.weight;
+            weight += item.get_weight();
         }
         weight
     }
@@ -105,8 +232,14 @@ impl ActorInventory {
         self.items.iter().map(|entry| entry.params.clone()).collect()
     }
 
-    pub fn to_item_ids(&self) -> Vec<String> {
-        self.items.iter().map(|entry| entry.params.id.clone()).collect()
+    pub fn to_entry_params(&self) -> Vec<ActorInventoryEntryParams> {
+        self.items
+            .iter()
+            .map(|entry| ActorInventoryEntryParams {
+                item_id: entry.params.id.clone(),
+                quantity: if entry.quantity > 1 { Some(entry.quantity) } else { None },
+            })
+            .collect()
     }
 
     fn randomize_drop_position(position: Vec2) -> Vec2 {
@@ -116,3 +249,37 @@ impl ActorInventory {
         )
     }
 }
+
+/// Holds the consumable items that are bound to the quick use keys. Slots refer to items by item id,
+/// so that they use whichever stack of the item is in the inventory.
+#[derive(Clone)]
+pub struct ActorQuickUseBar {
+    slots: Vec<Option<String>>,
+}
+
+impl ActorQuickUseBar {
+    pub const SLOT_COUNT: usize = 4;
+
+    pub fn new(item_ids: &[Option<String>]) -> Self {
+        let mut slots = vec![None; Self::SLOT_COUNT];
+        for (slot, item_id) in item_ids.iter().take(Self::SLOT_COUNT).enumerate() {
+            slots[slot] = item_id.clone();
+        }
+        ActorQuickUseBar {
+            slots,
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&String> {
+        self.slots.get(slot).and_then(|slot| slot.as_ref())
+    }
+
+    pub fn set(&mut self, slot: usize, item_id: Option<String>) {
+        assert!(slot < Self::SLOT_COUNT, "Invalid quick use bar slot '{}'", slot);
+        self.slots[slot] = item_id;
+    }
+
+    pub fn to_item_ids(&self) -> Vec<Option<String>> {
+        self.slots.clone()
+    }
+}
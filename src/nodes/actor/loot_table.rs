@@ -0,0 +1,69 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use macroquad::prelude::*;
+
+use crate::{
+    nodes::actor::ActorInventoryEntryParams,
+    Resources,
+};
+
+/// An entry in a loot table. Entries without an item id are rolls that drop nothing.
+/// `quantity` defaults to one.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LootEntryParams {
+    pub item_id: Option<String>,
+    pub quantity: Option<u32>,
+    pub weight: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LootTableParams {
+    pub id: String,
+    pub rolls: Option<u32>,
+    pub entries: Vec<LootEntryParams>,
+    pub min_gold: Option<u32>,
+    pub max_gold: Option<u32>,
+}
+
+impl LootTableParams {
+    const DEFAULT_ROLLS: u32 = 1;
+
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        for item_id in self.entries.iter().filter_map(|entry| entry.item_id.as_ref()) {
+            if resources.try_get_item(item_id).is_none() {
+                return Err(format!("Unable to find item '{}'", item_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Picks an entry by weight for every roll and returns the dropped items, along with an amount of gold
+    pub fn roll(&self) -> (Vec<ActorInventoryEntryParams>, u32) {
+        let total_weight: u32 = self.entries.iter().map(|entry| entry.weight).sum();
+        let mut items = Vec::new();
+        if total_weight > 0 {
+            for _ in 0..self.rolls.unwrap_or(Self::DEFAULT_ROLLS) {
+                let mut roll = rand::gen_range(0, total_weight);
+                for entry in &self.entries {
+                    if roll < entry.weight {
+                        if let Some(item_id) = &entry.item_id {
+                            items.push(ActorInventoryEntryParams {
+                                item_id: item_id.clone(),
+                                quantity: entry.quantity,
+                            });
+                        }
+                        break;
+                    }
+                    roll -= entry.weight;
+                }
+            }
+        }
+        let min_gold = self.min_gold.unwrap_or(0);
+        let max_gold = self.max_gold.unwrap_or(min_gold).max(min_gold);
+        let gold = rand::gen_range(min_gold, max_gold + 1);
+        (items, gold)
+    }
+}
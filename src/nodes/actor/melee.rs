@@ -0,0 +1,102 @@
+use std::ops::Sub;
+
+use macroquad::prelude::*;
+
+use crate::{
+    nodes::{
+        Actor,
+        actor::{
+            Damage,
+            StatusEffectParams,
+        },
+    },
+    physics::Collider,
+    Factions,
+    FactionStance,
+};
+
+/// A melee swing in progress. The hit is resolved once, when the windup has elapsed,
+/// and the attack is finished when the recovery has elapsed as well
+#[derive(Clone)]
+pub struct MeleeAttack {
+    pub damage: Damage,
+    pub status_effects: Vec<StatusEffectParams>,
+    pub direction: Vec2,
+    pub reach: f32,
+    pub angle: f32,
+    pub knockback: f32,
+    pub windup: f32,
+    pub recovery: f32,
+    pub timer: f32,
+    pub is_resolved: bool,
+}
+
+impl MeleeAttack {
+    pub fn new(
+        damage: Damage,
+        status_effects: &[StatusEffectParams],
+        origin: Vec2,
+        target: Vec2,
+        facing_direction: Vec2,
+        reach: f32,
+        angle: f32,
+        knockback: f32,
+        windup: f32,
+        recovery: f32,
+    ) -> Self {
+        // A swing at the attacker's own position goes in the direction it is facing
+        let direction = match target.sub(origin).normalize_or_zero() {
+            direction if direction == Vec2::ZERO => facing_direction.normalize_or_zero(),
+            direction => direction,
+        };
+        MeleeAttack {
+            damage,
+            status_effects: status_effects.to_vec(),
+            direction,
+            reach,
+            angle: angle.to_radians(),
+            knockback,
+            windup,
+            recovery,
+            timer: 0.0,
+            is_resolved: false,
+        }
+    }
+
+    pub fn should_resolve(&self) -> bool {
+        !self.is_resolved && self.timer >= self.windup
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_resolved && self.timer >= self.windup + self.recovery
+    }
+
+    /// Damages and knocks back all actors whose colliders are within reach, in the swing arc
+    pub fn resolve(&mut self, actor_id: &str, factions: &[String], origin: Vec2) {
+        self.is_resolved = true;
+        let reach_collider = Collider::circle(0.0, 0.0, self.reach).offset(origin);
+        for mut other in scene::find_nodes_by_type::<Actor>() {
+            if other.id == actor_id || other.is_dead() || Factions::get_stance(factions, &other.factions) == FactionStance::Allied {
+                continue;
+            }
+            if let Some(other_collider) = other.body.get_offset_collider() {
+                if reach_collider.overlaps(&other_collider) {
+                    let offset = other_collider.get_position().sub(origin);
+                    // The arc is widened by the angle the collider covers, as seen from the origin,
+                    // so that colliders that reach into the arc are hit, even if their center is outside of it
+                    let distance = offset.length();
+                    let radius = other_collider.get_bounding_radius();
+                    if distance <= radius
+                        || self.direction.angle_between(offset).abs() <= self.angle / 2.0 + (radius / distance).asin() {
+                        other.take_damage(actor_id, self.damage.clone());
+                        for params in &self.status_effects {
+                            other.apply_status_effect(actor_id, params.clone());
+                        }
+                        let direction = offset.normalize_or_zero();
+                        other.apply_knockback(if direction == Vec2::ZERO { self.direction } else { direction } * self.knockback);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use macroquad::prelude::*;
+
+use crate::{
+    get_global,
+    nodes::actor::ActorInventory,
+    factions::ReputationParams,
+    Map,
+    show_notice,
+    Resources,
+};
+
+/// An objective of a quest stage.
+/// `kill_actor` and `talk_to` require `actor_name`, `collect_item` requires `item_id`
+/// and `reach_region` requires `region_id`, which is the name of a region in the map.
+/// `amount` defaults to one and is ignored for `reach_region`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuestObjectiveParams {
+    pub kind: String,
+    pub description: String,
+    pub actor_name: Option<String>,
+    pub item_id: Option<String>,
+    pub region_id: Option<String>,
+    pub amount: Option<u32>,
+}
+
+impl QuestObjectiveParams {
+    pub const KILL_ACTOR_KIND: &'static str = "kill_actor";
+    pub const COLLECT_ITEM_KIND: &'static str = "collect_item";
+    pub const REACH_REGION_KIND: &'static str = "reach_region";
+    pub const TALK_TO_KIND: &'static str = "talk_to";
+
+    /// Checks that the kind is valid and that the fields it requires are set.
+    /// Regions belong to the map, so only item ids are checked against the resources
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        if self.kind == Self::KILL_ACTOR_KIND || self.kind == Self::TALK_TO_KIND {
+            match self.actor_name {
+                Some(_) => Ok(()),
+                None => Err(format!("Objective of kind '{}' has no actor name", self.kind)),
+            }
+        } else if self.kind == Self::COLLECT_ITEM_KIND {
+            match &self.item_id {
+                Some(item_id) if resources.try_get_item(item_id).is_some() => Ok(()),
+                Some(item_id) => Err(format!("Unable to find item '{}'", item_id)),
+                None => Err(format!("Objective of kind '{}' has no item id", self.kind)),
+            }
+        } else if self.kind == Self::REACH_REGION_KIND {
+            match self.region_id {
+                Some(_) => Ok(()),
+                None => Err(format!("Objective of kind '{}' has no region id", self.kind)),
+            }
+        } else {
+            Err(format!("Invalid objective kind '{}'", self.kind))
+        }
+    }
+
+    pub fn get_amount(&self) -> u32 {
+        if self.kind == Self::REACH_REGION_KIND {
+            1
+        } else {
+            self.amount.unwrap_or(1)
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuestStageParams {
+    pub description: String,
+    pub objectives: Vec<QuestObjectiveParams>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct QuestRewardsParams {
+    pub experience: Option<u32>,
+    pub items: Option<Vec<String>>,
+    pub reputation: Option<Vec<ReputationParams>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuestParams {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub stages: Vec<QuestStageParams>,
+    pub rewards: Option<QuestRewardsParams>,
+}
+
+impl QuestParams {
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        for (i, stage) in self.stages.iter().enumerate() {
+            for objective in &stage.objectives {
+                objective.validate(resources).map_err(|err| format!("{} in stage {}", err, i))?;
+            }
+        }
+        if let Some(rewards) = &self.rewards {
+            for item_id in rewards.items.iter().flatten() {
+                if resources.try_get_item(item_id).is_none() {
+                    return Err(format!("Unable to find reward item '{}'", item_id));
+                }
+            }
+            for reputation in rewards.reputation.iter().flatten() {
+                if resources.try_get_faction(&reputation.faction).is_none() {
+                    return Err(format!("Unable to find reward faction '{}'", reputation.faction));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Things that happen to an actor, that can advance the objectives of its quests
+#[derive(Debug, Clone)]
+pub enum QuestEvent {
+    ActorKilled { actor_name: String },
+    TalkedTo { actor_name: String },
+}
+
+/// The progress of a quest, as it is saved with the actor
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuestProgressParams {
+    pub quest_id: String,
+    pub stage: usize,
+    pub progress: Vec<u32>,
+    pub is_completed: bool,
+}
+
+#[derive(Clone)]
+pub struct Quest {
+    pub params: QuestParams,
+    pub stage: usize,
+    /// The progress of each of the objectives of the current stage
+    pub progress: Vec<u32>,
+    pub is_completed: bool,
+}
+
+impl Quest {
+    pub fn new(quest_id: &str) -> Option<Self> {
+        let resources = get_global::<Resources>();
+        let params = match resources.try_get_quest(quest_id) {
+            Some(params) => params.clone(),
+            None => {
+                show_notice(&format!("Unable to find quest '{}'", quest_id));
+                return None;
+            }
+        };
+        let mut quest = Quest {
+            params,
+            stage: 0,
+            progress: Vec::new(),
+            is_completed: false,
+        };
+        quest.reset_progress();
+        Some(quest)
+    }
+
+    pub fn from_progress_params(params: &QuestProgressParams) -> Option<Self> {
+        let mut quest = Self::new(&params.quest_id)?;
+        quest.stage = params.stage;
+        quest.is_completed = params.is_completed;
+        quest.reset_progress();
+        for (progress, saved) in quest.progress.iter_mut().zip(&params.progress) {
+            *progress = *saved;
+        }
+        Some(quest)
+    }
+
+    pub fn to_progress_params(&self) -> QuestProgressParams {
+        QuestProgressParams {
+            quest_id: self.params.id.clone(),
+            stage: self.stage,
+            progress: self.progress.clone(),
+            is_completed: self.is_completed,
+        }
+    }
+
+    pub fn get_current_stage(&self) -> Option<&QuestStageParams> {
+        if self.is_completed {
+            None
+        } else {
+            self.params.stages.get(self.stage)
+        }
+    }
+
+    fn reset_progress(&mut self) {
+        let len = self.get_current_stage()
+            .map(|stage| stage.objectives.len())
+            .unwrap_or(0);
+        self.progress = vec![0; len];
+    }
+
+    pub fn on_event(&mut self, event: &QuestEvent) {
+        let (kind, name) = match event {
+            QuestEvent::ActorKilled { actor_name } => (QuestObjectiveParams::KILL_ACTOR_KIND, actor_name),
+            QuestEvent::TalkedTo { actor_name } => (QuestObjectiveParams::TALK_TO_KIND, actor_name),
+        };
+        let objectives = match self.get_current_stage() {
+            Some(stage) => stage.objectives.clone(),
+            None => return,
+        };
+        for (i, objective) in objectives.iter().enumerate() {
+            if objective.kind == kind && objective.actor_name.as_ref() == Some(name) {
+                self.progress[i] = (self.progress[i] + 1).min(objective.get_amount());
+            }
+        }
+    }
+
+    /// Updates the objectives that depend on the state of the actor, rather than on events, and
+    /// advances the quest if all objectives of the current stage are met.
+    /// Objectives are validated when the resources are loaded, so invalid objectives are skipped.
+    /// Returns `true` if the quest was completed by this update.
+    pub fn update(&mut self, inventory: &ActorInventory, position: Vec2, map: &Map) -> bool {
+        let objectives = match self.get_current_stage() {
+            Some(stage) => stage.objectives.clone(),
+            None => return false,
+        };
+        for (i, objective) in objectives.iter().enumerate() {
+            if objective.kind == QuestObjectiveParams::COLLECT_ITEM_KIND {
+                if let Some(item_id) = &objective.item_id {
+                    self.progress[i] = inventory.count_item(item_id).min(objective.get_amount());
+                }
+            } else if objective.kind == QuestObjectiveParams::REACH_REGION_KIND {
+                if let Some(region) = objective.region_id.as_ref().and_then(|region_id| map.try_get_region(region_id)) {
+                    if region.rect.contains(position) {
+                        self.progress[i] = 1;
+                    }
+                }
+            }
+        }
+        let is_stage_completed = objectives
+            .iter()
+            .zip(&self.progress)
+            .all(|(objective, progress)| *progress >= objective.get_amount());
+        if is_stage_completed {
+            self.stage += 1;
+            self.is_completed = self.stage >= self.params.stages.len();
+            self.reset_progress();
+            return self.is_completed;
+        }
+        false
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::nodes::actor::{
+    ActorAbility,
+    ActorAbilityParams,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpellParams {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub ability_params: ActorAbilityParams,
+}
+
+#[derive(Clone)]
+pub struct AbilityBarSlot {
+    pub spell_id: String,
+    pub name: String,
+    pub ability: ActorAbility,
+}
+
+/// Holds the spells that are bound to the number keys
+#[derive(Clone)]
+pub struct ActorAbilityBar {
+    slots: Vec<Option<AbilityBarSlot>>,
+}
+
+impl ActorAbilityBar {
+    pub const SLOT_COUNT: usize = 6;
+
+    pub fn new() -> Self {
+        ActorAbilityBar {
+            slots: vec![None; Self::SLOT_COUNT],
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&AbilityBarSlot> {
+        self.slots.get(slot).and_then(|slot| slot.as_ref())
+    }
+
+    pub fn get_ability_mut(&mut self, slot: usize) -> Option<&mut ActorAbility> {
+        self.slots.get_mut(slot).and_then(|slot| slot.as_mut()).map(|slot| &mut slot.ability)
+    }
+
+    pub fn set(&mut self, slot: usize, entry: Option<AbilityBarSlot>) {
+        assert!(slot < Self::SLOT_COUNT, "Invalid ability bar slot '{}'", slot);
+        self.slots[slot] = entry;
+    }
+
+    pub fn update(&mut self) {
+        for slot in self.slots.iter_mut().flatten() {
+            slot.ability.update();
+        }
+    }
+
+    pub fn to_spell_ids(&self) -> Vec<Option<String>> {
+        self.slots.iter().map(|slot| slot.as_ref().map(|slot| slot.spell_id.clone())).collect()
+    }
+}
@@ -1,5 +1,77 @@
 use macroquad::prelude::*;
 
+use crate::nodes::actor::Damage;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ActorStatModifiers {
+    pub strength: Option<i32>,
+    pub dexterity: Option<i32>,
+    pub constitution: Option<i32>,
+    pub intelligence: Option<i32>,
+    pub willpower: Option<i32>,
+    pub perception: Option<i32>,
+    pub charisma: Option<i32>,
+    pub max_health: Option<f32>,
+    pub max_stamina: Option<f32>,
+    pub max_energy: Option<f32>,
+    pub health_regen: Option<f32>,
+    pub stamina_regen: Option<f32>,
+    pub energy_regen: Option<f32>,
+    pub carry_capacity: Option<f32>,
+    pub move_speed: Option<f32>,
+    pub armor: Option<f32>,
+    pub kinetic_resistance: Option<f32>,
+    pub energy_resistance: Option<f32>,
+    pub fire_resistance: Option<f32>,
+    pub psychic_resistance: Option<f32>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActorLevelParams {
+    pub level: u32,
+    pub experience: u32,
+    pub attribute_points: u32,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ActorStatModifierKind {
+    Flat,
+    Percentage,
+}
+
+#[derive(Clone)]
+pub struct ActorStatModifier {
+    pub source_id: String,
+    pub kind: ActorStatModifierKind,
+    pub values: ActorStatModifiers,
+    pub time_left: Option<f32>,
+}
+
+impl ActorStatModifier {
+    pub fn flat(source_id: &str, values: ActorStatModifiers, duration: Option<f32>) -> Self {
+        ActorStatModifier {
+            source_id: source_id.to_string(),
+            kind: ActorStatModifierKind::Flat,
+            values,
+            time_left: duration,
+        }
+    }
+
+    pub fn percentage(source_id: &str, values: ActorStatModifiers, duration: Option<f32>) -> Self {
+        ActorStatModifier {
+            source_id: source_id.to_string(),
+            kind: ActorStatModifierKind::Percentage,
+            values,
+            time_left: duration,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ActorStats {
     pub strength: u32,
@@ -9,6 +81,9 @@ pub struct ActorStats {
     pub willpower: u32,
     pub perception: u32,
     pub charisma: u32,
+    pub level: u32,
+    pub experience: u32,
+    pub attribute_points: u32,
     pub current_health: f32,
     pub max_health: f32,
     pub current_stamina: f32,
@@ -20,12 +95,32 @@ pub struct ActorStats {
     pub energy_regen: f32,
     pub carry_capacity: f32,
     pub move_speed: f32,
+    pub armor: f32,
+    pub kinetic_resistance: f32,
+    pub energy_resistance: f32,
+    pub fire_resistance: f32,
+    pub psychic_resistance: f32,
     pub is_static: bool,
+    pub modifiers: Vec<ActorStatModifier>,
 }
 
 impl ActorStats {
+    pub const STRENGTH_ATTRIBUTE: &'static str = "strength";
+    pub const DEXTERITY_ATTRIBUTE: &'static str = "dexterity";
+    pub const CONSTITUTION_ATTRIBUTE: &'static str = "constitution";
+    pub const INTELLIGENCE_ATTRIBUTE: &'static str = "intelligence";
+    pub const WILLPOWER_ATTRIBUTE: &'static str = "willpower";
+    pub const PERCEPTION_ATTRIBUTE: &'static str = "perception";
+    pub const CHARISMA_ATTRIBUTE: &'static str = "charisma";
+
     const VITALS_CAN_OVERFLOW: bool = false;
 
+    const MAX_RESISTANCE: f32 = 75.0;
+    const ARMOR_MITIGATION_CONSTANT: f32 = 100.0;
+
+    const CRITICAL_CHANCE_FACTOR: f32 = 0.005;
+    const MAX_CRITICAL_CHANCE: f32 = 0.5;
+
     pub fn new(
         strength: u32,
         dexterity: u32,
@@ -74,48 +169,245 @@ impl ActorStats {
         }
     }
 
-    pub fn update_derived(&mut self, max_vitals: bool) {
+    /// Adds a stat modifier from a source, like an equipped item or a status effect.
+    /// Modifiers with the same source are stacked and expire individually, if they have a duration.
+    /// Modifiers are only applied to actors that are not static.
+    pub fn add_modifier(&mut self, modifier: ActorStatModifier) {
+        self.modifiers.push(modifier);
+    }
+
+    pub fn remove_modifiers(&mut self, source_id: &str) {
+        self.modifiers.retain(|modifier| modifier.source_id != source_id);
+    }
+
+    fn get_modifier_sum<T: Default + std::ops::Add<Output = T>>(&self, kind: ActorStatModifierKind, f: fn(&ActorStatModifiers) -> Option<T>) -> T {
+        self.modifiers.iter()
+            .filter(|modifier| modifier.kind == kind)
+            .fold(T::default(), |sum, modifier| sum + f(&modifier.values).unwrap_or_default())
+    }
+
+    fn apply_attribute_modifier(&self, value: u32, f: fn(&ActorStatModifiers) -> Option<i32>) -> u32 {
+        let flat = self.get_modifier_sum(ActorStatModifierKind::Flat, f);
+        let percentage = self.get_modifier_sum(ActorStatModifierKind::Percentage, f);
+        ((value as i32 + flat) * (100 + percentage) / 100).max(0) as u32
+    }
+
+    fn apply_derived_modifier(&self, value: f32, f: fn(&ActorStatModifiers) -> Option<f32>) -> f32 {
+        let flat = self.get_modifier_sum(ActorStatModifierKind::Flat, f);
+        let percentage = self.get_modifier_sum(ActorStatModifierKind::Percentage, f);
+        ((value + flat) * (1.0 + percentage / 100.0)).max(0.0)
+    }
+
+    pub fn get_strength(&self) -> u32 {
+        self.apply_attribute_modifier(self.strength, |modifiers| modifiers.strength)
+    }
+
+    pub fn get_dexterity(&self) -> u32 {
+        self.apply_attribute_modifier(self.dexterity, |modifiers| modifiers.dexterity)
+    }
+
+    pub fn get_constitution(&self) -> u32 {
+        self.apply_attribute_modifier(self.constitution, |modifiers| modifiers.constitution)
+    }
+
+    pub fn get_intelligence(&self) -> u32 {
+        self.apply_attribute_modifier(self.intelligence, |modifiers| modifiers.intelligence)
+    }
+
+    pub fn get_willpower(&self) -> u32 {
+        self.apply_attribute_modifier(self.willpower, |modifiers| modifiers.willpower)
+    }
+
+    pub fn get_perception(&self) -> u32 {
+        self.apply_attribute_modifier(self.perception, |modifiers| modifiers.perception)
+    }
+
+    pub fn get_charisma(&self) -> u32 {
+        self.apply_attribute_modifier(self.charisma, |modifiers| modifiers.charisma)
+    }
+
+    pub fn is_valid_attribute(attribute: &str) -> bool {
+        attribute == Self::STRENGTH_ATTRIBUTE
+            || attribute == Self::DEXTERITY_ATTRIBUTE
+            || attribute == Self::CONSTITUTION_ATTRIBUTE
+            || attribute == Self::INTELLIGENCE_ATTRIBUTE
+            || attribute == Self::WILLPOWER_ATTRIBUTE
+            || attribute == Self::PERCEPTION_ATTRIBUTE
+            || attribute == Self::CHARISMA_ATTRIBUTE
+    }
+
+    pub fn get_attribute(&self, attribute: &str) -> u32 {
+        if attribute == Self::STRENGTH_ATTRIBUTE {
+            self.get_strength()
+        } else if attribute == Self::DEXTERITY_ATTRIBUTE {
+            self.get_dexterity()
+        } else if attribute == Self::CONSTITUTION_ATTRIBUTE {
+            self.get_constitution()
+        } else if attribute == Self::INTELLIGENCE_ATTRIBUTE {
+            self.get_intelligence()
+        } else if attribute == Self::WILLPOWER_ATTRIBUTE {
+            self.get_willpower()
+        } else if attribute == Self::PERCEPTION_ATTRIBUTE {
+            self.get_perception()
+        } else if attribute == Self::CHARISMA_ATTRIBUTE {
+            self.get_charisma()
+        } else {
+            assert!(false, "Invalid attribute '{}'", attribute);
+            0
+        }
+    }
+
+    /// Spends an unspent attribute point on the specified attribute and recomputes the derived stats
+    pub fn allocate_attribute_point(&mut self, attribute: &str) -> bool {
+        if self.attribute_points == 0 {
+            return false;
+        }
+        if attribute == Self::STRENGTH_ATTRIBUTE {
+            self.strength += 1;
+        } else if attribute == Self::DEXTERITY_ATTRIBUTE {
+            self.dexterity += 1;
+        } else if attribute == Self::CONSTITUTION_ATTRIBUTE {
+            self.constitution += 1;
+        } else if attribute == Self::INTELLIGENCE_ATTRIBUTE {
+            self.intelligence += 1;
+        } else if attribute == Self::WILLPOWER_ATTRIBUTE {
+            self.willpower += 1;
+        } else if attribute == Self::PERCEPTION_ATTRIBUTE {
+            self.perception += 1;
+        } else if attribute == Self::CHARISMA_ATTRIBUTE {
+            self.charisma += 1;
+        } else {
+            assert!(false, "Invalid attribute '{}'", attribute);
+            return false;
+        }
+        self.attribute_points -= 1;
+        self.recompute_derived(false);
+        true
+    }
+
+    /// Returns the chance of landing a critical hit, between 0.0 and 1.0
+    pub fn get_critical_chance(&self) -> f32 {
+        ((self.get_perception() + self.get_dexterity()) as f32 * Self::CRITICAL_CHANCE_FACTOR)
+            .min(Self::MAX_CRITICAL_CHANCE)
+    }
+
+    /// Returns the fraction of damage of the specified type that is mitigated, between 0.0 and 1.0.
+    /// Armor only mitigates kinetic damage, with diminishing returns. Damage types are validated
+    /// when the resources are loaded, so an unknown type is simply not mitigated.
+    pub fn get_mitigation(&self, damage_type: &str) -> f32 {
+        let resistance = if damage_type == Damage::KINETIC_TYPE {
+            self.kinetic_resistance
+        } else if damage_type == Damage::ENERGY_TYPE {
+            self.energy_resistance
+        } else if damage_type == Damage::FIRE_TYPE {
+            self.fire_resistance
+        } else if damage_type == Damage::PSYCHIC_TYPE {
+            self.psychic_resistance
+        } else {
+            0.0
+        };
+        let mut mitigation = resistance.clamp(0.0, Self::MAX_RESISTANCE) / 100.0;
+        if damage_type == Damage::KINETIC_TYPE && self.armor > 0.0 {
+            let armor_mitigation = self.armor / (self.armor + Self::ARMOR_MITIGATION_CONSTANT);
+            mitigation = 1.0 - (1.0 - mitigation) * (1.0 - armor_mitigation);
+        }
+        mitigation
+    }
+
+    /// Counts down the timed modifiers and removes the ones that have expired.
+    /// This should only be called once per frame, before `update_derived`
+    pub fn update_modifiers(&mut self, dt: f32) {
+        for modifier in &mut self.modifiers {
+            if let Some(time_left) = modifier.time_left.as_mut() {
+                *time_left -= dt;
+            }
+        }
+        self.modifiers.retain(|modifier| modifier.time_left.map(|time_left| time_left > 0.0).unwrap_or(true));
+    }
+
+    /// Recomputes the derived stats from the attributes and modifiers, without regenerating vitals
+    pub fn recompute_derived(&mut self, max_vitals: bool) {
         if !self.is_static {
-            self.max_health = (self.constitution + self.strength / 4 + self.willpower / 4) as f32 * 100.0;
-            self.max_stamina = (self.constitution + self.dexterity / 4 + self.willpower / 4) as f32 * 100.0;
-            self.max_energy = (self.willpower + self.constitution / 2) as f32 * 100.0;
+            let strength = self.get_strength();
+            let dexterity = self.get_dexterity();
+            let constitution = self.get_constitution();
+            let willpower = self.get_willpower();
+            self.max_health = self.apply_derived_modifier(
+                (constitution + strength / 4 + willpower / 4) as f32 * 100.0,
+                |modifiers| modifiers.max_health,
+            );
+            self.max_stamina = self.apply_derived_modifier(
+                (constitution + dexterity / 4 + willpower / 4) as f32 * 100.0,
+                |modifiers| modifiers.max_stamina,
+            );
+            self.max_energy = self.apply_derived_modifier(
+                (willpower + constitution / 2) as f32 * 100.0,
+                |modifiers| modifiers.max_energy,
+            );
             if max_vitals {
                 self.current_health = self.max_health;
                 self.current_stamina = self.max_stamina;
                 self.current_energy = self.max_energy;
             }
-            self.health_regen = (self.constitution + self.strength / 4 + self.willpower / 4) as f32 * 0.1;
-            self.stamina_regen = (self.constitution + self.dexterity / 4 + self.willpower / 4) as f32 * 8.0;
-            self.energy_regen = (self.willpower + self.constitution / 2) as f32 * 0.5;
-            self.move_speed = (self.dexterity + self.strength / 4 + self.willpower / 4) as f32 * 0.1;
-            self.carry_capacity = (self.strength + self.constitution / 4 + self.willpower / 4) as f32 * 50.0;
+            self.health_regen = self.apply_derived_modifier(
+                (constitution + strength / 4 + willpower / 4) as f32 * 0.1,
+                |modifiers| modifiers.health_regen,
+            );
+            self.stamina_regen = self.apply_derived_modifier(
+                (constitution + dexterity / 4 + willpower / 4) as f32 * 8.0,
+                |modifiers| modifiers.stamina_regen,
+            );
+            self.energy_regen = self.apply_derived_modifier(
+                (willpower + constitution / 2) as f32 * 0.5,
+                |modifiers| modifiers.energy_regen,
+            );
+            self.move_speed = self.apply_derived_modifier(
+                (dexterity + strength / 4 + willpower / 4) as f32 * 0.1,
+                |modifiers| modifiers.move_speed,
+            );
+            self.carry_capacity = self.apply_derived_modifier(
+                (strength + constitution / 4 + willpower / 4) as f32 * 50.0,
+                |modifiers| modifiers.carry_capacity,
+            );
+            self.armor = self.apply_derived_modifier(0.0, |modifiers| modifiers.armor);
+            self.kinetic_resistance = self.apply_derived_modifier(0.0, |modifiers| modifiers.kinetic_resistance);
+            self.energy_resistance = self.apply_derived_modifier(0.0, |modifiers| modifiers.energy_resistance);
+            self.fire_resistance = self.apply_derived_modifier(0.0, |modifiers| modifiers.fire_resistance);
+            self.psychic_resistance = self.apply_derived_modifier(
+                willpower as f32 * 1.0,
+                |modifiers| modifiers.psychic_resistance,
+            );
         }
-        let dt = get_frame_time();
-        if self.current_health < self.max_health {
-            self.current_health += self.health_regen * dt;
+        if !Self::VITALS_CAN_OVERFLOW {
             if self.current_health > self.max_health {
                 self.current_health = self.max_health;
             }
-        }
-        if self.current_stamina < self.max_stamina {
-            self.current_stamina += self.stamina_regen * dt;
             if self.current_stamina > self.max_stamina {
                 self.current_stamina = self.max_stamina;
             }
-        }
-        if self.current_energy < self.max_energy {
-            self.current_energy += self.energy_regen * dt;
             if self.current_energy > self.max_energy {
                 self.current_energy = self.max_energy;
             }
         }
-        if !Self::VITALS_CAN_OVERFLOW {
+    }
+
+    pub fn update_derived(&mut self, max_vitals: bool) {
+        self.recompute_derived(max_vitals);
+        let dt = get_frame_time();
+        if self.current_health < self.max_health {
+            self.current_health += self.health_regen * dt;
             if self.current_health > self.max_health {
                 self.current_health = self.max_health;
             }
+        }
+        if self.current_stamina < self.max_stamina {
+            self.current_stamina += self.stamina_regen * dt;
             if self.current_stamina > self.max_stamina {
                 self.current_stamina = self.max_stamina;
             }
+        }
+        if self.current_energy < self.max_energy {
+            self.current_energy += self.energy_regen * dt;
             if self.current_energy > self.max_energy {
                 self.current_energy = self.max_energy;
             }
@@ -133,6 +425,9 @@ impl Default for ActorStats {
             willpower: 0,
             perception: 0,
             charisma: 0,
+            level: 1,
+            experience: 0,
+            attribute_points: 0,
             current_health: 1.0,
             max_health: 1.0,
             current_stamina: 0.0,
@@ -144,7 +439,13 @@ impl Default for ActorStats {
             energy_regen: 0.0,
             carry_capacity: 0.0,
             move_speed: 0.0,
+            armor: 0.0,
+            kinetic_resistance: 0.0,
+            energy_resistance: 0.0,
+            fire_resistance: 0.0,
+            psychic_resistance: 0.0,
             is_static: true,
+            modifiers: Vec::new(),
         }
     }
 }
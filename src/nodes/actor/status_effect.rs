@@ -0,0 +1,126 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::{
+    nodes::actor::{
+        ActorStatModifier,
+        ActorStatModifiers,
+    },
+    generate_id,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StatusEffectParams {
+    pub kind: String,
+    pub duration: Option<f32>,
+    pub magnitude: Option<f32>,
+    pub flat_modifiers: Option<ActorStatModifiers>,
+    pub percentage_modifiers: Option<ActorStatModifiers>,
+    pub is_stackable: Option<bool>,
+}
+
+impl Default for StatusEffectParams {
+    fn default() -> Self {
+        StatusEffectParams {
+            kind: StatusEffect::MODIFIER_KIND.to_string(),
+            duration: None,
+            magnitude: None,
+            flat_modifiers: None,
+            percentage_modifiers: None,
+            is_stackable: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StatusEffect {
+    pub id: String,
+    pub source_id: String,
+    pub kind: String,
+    pub magnitude: f32,
+    pub time_left: Option<f32>,
+    pub is_stackable: bool,
+    flat_modifiers: Option<ActorStatModifiers>,
+    percentage_modifiers: Option<ActorStatModifiers>,
+}
+
+impl StatusEffect {
+    pub const BURNING_KIND: &'static str = "burning";
+    pub const SLOWED_KIND: &'static str = "slowed";
+    pub const STUNNED_KIND: &'static str = "stunned";
+    pub const REGENERATING_KIND: &'static str = "regenerating";
+    pub const MODIFIER_KIND: &'static str = "modifier";
+
+    // burning damage and regenerated health per second, and percentage of move speed lost when slowed
+    const DEFAULT_BURNING_MAGNITUDE: f32 = 10.0;
+    const DEFAULT_SLOWED_MAGNITUDE: f32 = 50.0;
+    const DEFAULT_REGENERATING_MAGNITUDE: f32 = 5.0;
+
+    pub fn new(source_id: &str, params: StatusEffectParams) -> Self {
+        let default_magnitude = if params.kind == Self::BURNING_KIND {
+            Self::DEFAULT_BURNING_MAGNITUDE
+        } else if params.kind == Self::SLOWED_KIND {
+            Self::DEFAULT_SLOWED_MAGNITUDE
+        } else if params.kind == Self::REGENERATING_KIND {
+            Self::DEFAULT_REGENERATING_MAGNITUDE
+        } else if params.kind == Self::STUNNED_KIND || params.kind == Self::MODIFIER_KIND {
+            0.0
+        } else {
+            assert!(false, "Invalid status effect kind '{}'", params.kind);
+            0.0
+        };
+        StatusEffect {
+            id: generate_id(),
+            source_id: source_id.to_string(),
+            kind: params.kind,
+            magnitude: params.magnitude.unwrap_or(default_magnitude),
+            time_left: params.duration,
+            is_stackable: params.is_stackable.unwrap_or_default(),
+            flat_modifiers: params.flat_modifiers,
+            percentage_modifiers: params.percentage_modifiers,
+        }
+    }
+
+    pub fn get_label(&self) -> String {
+        let name = if self.kind == Self::BURNING_KIND {
+            "Burning"
+        } else if self.kind == Self::SLOWED_KIND {
+            "Slowed"
+        } else if self.kind == Self::STUNNED_KIND {
+            "Stunned"
+        } else if self.kind == Self::REGENERATING_KIND {
+            "Regenerating"
+        } else {
+            "Modified"
+        };
+        match self.time_left {
+            Some(time_left) => format!("{} ({:.1}s)", name, time_left),
+            None => name.to_string(),
+        }
+    }
+
+    /// Returns the stat modifiers of the effect, with the effect id as source id, so that they expire with the effect
+    pub fn get_stat_modifiers(&self) -> Vec<ActorStatModifier> {
+        let mut modifiers = Vec::new();
+        if self.kind == Self::SLOWED_KIND {
+            let values = ActorStatModifiers {
+                move_speed: Some(-self.magnitude),
+                ..Default::default()
+            };
+            modifiers.push(ActorStatModifier::percentage(&self.id, values, self.time_left));
+        }
+        if let Some(values) = self.flat_modifiers.clone() {
+            modifiers.push(ActorStatModifier::flat(&self.id, values, self.time_left));
+        }
+        if let Some(values) = self.percentage_modifiers.clone() {
+            modifiers.push(ActorStatModifier::percentage(&self.id, values, self.time_left));
+        }
+        modifiers
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.time_left.map(|time_left| time_left <= 0.0).unwrap_or(false)
+    }
+}
@@ -0,0 +1,328 @@
+use std::ops::Sub;
+
+use macroquad::{
+    experimental::scene::{
+        Node,
+        Handle,
+        RefMut,
+    },
+    prelude::*,
+};
+
+use crate::{
+    nodes::{
+        Actor,
+        actor::{
+            Damage,
+            StatusEffectParams,
+        },
+    },
+    Factions,
+    FactionStance,
+};
+
+pub enum AreaEffectKind {
+    Explosion { falloff: f32 },
+    Zone { tick_interval: f32, next_tick: f32 },
+    Cone { direction: Vec2, angle: f32 },
+    ChainLightning { jumps: u32, falloff: f32, points: Vec<Vec2> },
+}
+
+pub struct AreaEffect {
+    actor_id: String,
+    factions: Vec<String>,
+    kind: AreaEffectKind,
+    damage: Damage,
+    status_effects: Vec<StatusEffectParams>,
+    color: Color,
+    size: f32,
+    position: Vec2,
+    radius: f32,
+    lived: f32,
+    ttl: f32,
+    is_resolved: bool,
+}
+
+pub struct AreaEffects {
+    active: Vec<AreaEffect>,
+}
+
+impl AreaEffects {
+    const EXPLOSION_TTL: f32 = 0.3;
+    const CONE_TTL: f32 = 0.2;
+    const CHAIN_LIGHTNING_TTL: f32 = 0.25;
+
+    const CONE_SEGMENTS: u32 = 12;
+    const ZONE_ALPHA: f32 = 0.3;
+    const CHAIN_LIGHTNING_JITTER: f32 = 4.0;
+
+    pub fn new() -> Self {
+        AreaEffects {
+            active: Vec::new(),
+        }
+    }
+
+    pub fn add_node() -> Handle<Self> {
+        scene::add_node(Self::new())
+    }
+
+    /// Damages all actors within the radius, with the damage falling off linearly to `falloff` times the damage at the edge
+    pub fn spawn_explosion(
+        &mut self,
+        actor_id: &str,
+        factions: &[String],
+        damage: Damage,
+        status_effects: &[StatusEffectParams],
+        color: Color,
+        position: Vec2,
+        radius: f32,
+        falloff: f32,
+    ) {
+        self.spawn(actor_id, factions, AreaEffectKind::Explosion { falloff }, damage, status_effects, color, 0.0, position, radius, Self::EXPLOSION_TTL);
+    }
+
+    /// Damages all actors within the radius every `tick_interval` seconds, for `duration` seconds
+    pub fn spawn_zone(
+        &mut self,
+        actor_id: &str,
+        factions: &[String],
+        damage: Damage,
+        status_effects: &[StatusEffectParams],
+        color: Color,
+        position: Vec2,
+        radius: f32,
+        duration: f32,
+        tick_interval: f32,
+    ) {
+        let kind = AreaEffectKind::Zone { tick_interval, next_tick: 0.0 };
+        self.spawn(actor_id, factions, kind, damage, status_effects, color, 0.0, position, radius, duration);
+    }
+
+    /// Damages all actors within `range` of the origin, that are inside a cone of `angle` degrees, facing the target
+    pub fn spawn_cone(
+        &mut self,
+        actor_id: &str,
+        factions: &[String],
+        damage: Damage,
+        status_effects: &[StatusEffectParams],
+        color: Color,
+        origin: Vec2,
+        target: Vec2,
+        range: f32,
+        angle: f32,
+    ) {
+        let direction = target.sub(origin).normalize_or_zero();
+        let kind = AreaEffectKind::Cone { direction, angle: angle.to_radians() };
+        self.spawn(actor_id, factions, kind, damage, status_effects, color, 0.0, origin, range, Self::CONE_TTL);
+    }
+
+    /// Hits the actor closest to the target, within `radius` of it, and then jumps to the closest actor,
+    /// that has not already been hit, up to `jumps` times, multiplying the damage by `falloff` for every jump
+    pub fn spawn_chain_lightning(
+        &mut self,
+        actor_id: &str,
+        factions: &[String],
+        damage: Damage,
+        status_effects: &[StatusEffectParams],
+        color: Color,
+        size: f32,
+        origin: Vec2,
+        target: Vec2,
+        radius: f32,
+        jumps: u32,
+        falloff: f32,
+    ) {
+        let kind = AreaEffectKind::ChainLightning { jumps, falloff, points: vec!(origin, target) };
+        self.spawn(actor_id, factions, kind, damage, status_effects, color, size, origin, radius, Self::CHAIN_LIGHTNING_TTL);
+    }
+
+    fn spawn(
+        &mut self,
+        actor_id: &str,
+        factions: &[String],
+        kind: AreaEffectKind,
+        damage: Damage,
+        status_effects: &[StatusEffectParams],
+        color: Color,
+        size: f32,
+        position: Vec2,
+        radius: f32,
+        ttl: f32,
+    ) {
+        self.active.push(AreaEffect {
+            actor_id: actor_id.to_string(),
+            factions: factions.to_vec(),
+            kind,
+            damage,
+            status_effects: status_effects.to_vec(),
+            color,
+            size,
+            position,
+            radius,
+            lived: 0.0,
+            ttl,
+            is_resolved: false,
+        });
+    }
+
+    fn is_valid_target(effect: &AreaEffect, other: &Actor) -> bool {
+        other.id != effect.actor_id
+            && other.is_dead() == false
+            && Factions::get_stance(&effect.factions, &other.factions) == FactionStance::Hostile
+    }
+
+    fn get_actor_position(actor: &Actor) -> Vec2 {
+        match actor.body.get_offset_collider() {
+            Some(collider) => collider.get_position(),
+            None => actor.body.position,
+        }
+    }
+
+    fn apply_damage(effect: &AreaEffect, other: &mut Actor, factor: f32) {
+        let mut damage = effect.damage.clone();
+        damage.amount *= factor;
+        other.take_damage(&effect.actor_id, damage);
+        for params in &effect.status_effects {
+            other.apply_status_effect(&effect.actor_id, params.clone());
+        }
+    }
+
+    fn resolve(effect: &mut AreaEffect) {
+        match &mut effect.kind {
+            AreaEffectKind::Explosion { falloff } => {
+                let falloff = *falloff;
+                for mut other in scene::find_nodes_by_type::<Actor>() {
+                    if Self::is_valid_target(effect, &other) {
+                        let distance = effect.position.distance(Self::get_actor_position(&other));
+                        if distance <= effect.radius {
+                            let factor = 1.0 - (1.0 - falloff) * (distance / effect.radius);
+                            Self::apply_damage(effect, &mut other, factor);
+                        }
+                    }
+                }
+            }
+            AreaEffectKind::Zone { .. } => {
+                for mut other in scene::find_nodes_by_type::<Actor>() {
+                    if Self::is_valid_target(effect, &other)
+                        && effect.position.distance(Self::get_actor_position(&other)) <= effect.radius {
+                        Self::apply_damage(effect, &mut other, 1.0);
+                    }
+                }
+            }
+            AreaEffectKind::Cone { direction, angle } => {
+                let (direction, angle) = (*direction, *angle);
+                for mut other in scene::find_nodes_by_type::<Actor>() {
+                    if Self::is_valid_target(effect, &other) {
+                        let offset = Self::get_actor_position(&other).sub(effect.position);
+                        if offset.length() <= effect.radius && direction.angle_between(offset).abs() <= angle / 2.0 {
+                            Self::apply_damage(effect, &mut other, 1.0);
+                        }
+                    }
+                }
+            }
+            AreaEffectKind::ChainLightning { jumps, falloff, points } => {
+                let (jumps, falloff) = (*jumps, *falloff);
+                let mut points = std::mem::take(points);
+                let mut hit_ids = Vec::new();
+                let mut from = points.pop().unwrap();
+                let mut factor = 1.0;
+                for _ in 0..jumps + 1 {
+                    let mut closest: Option<(String, Vec2, f32)> = None;
+                    for other in scene::find_nodes_by_type::<Actor>() {
+                        if Self::is_valid_target(effect, &other) && !hit_ids.contains(&other.id) {
+                            let position = Self::get_actor_position(&other);
+                            let distance = from.distance(position);
+                            if distance <= effect.radius && (closest.is_none() || distance < closest.as_ref().unwrap().2) {
+                                closest = Some((other.id.clone(), position, distance));
+                            }
+                        }
+                    }
+                    match closest {
+                        Some((id, position, _)) => {
+                            if let Some(mut other) = Actor::find_with_id(&id) {
+                                Self::apply_damage(effect, &mut other, factor);
+                            }
+                            points.push(position);
+                            hit_ids.push(id);
+                            from = position;
+                            factor *= falloff;
+                        }
+                        None => break,
+                    }
+                }
+                if hit_ids.is_empty() {
+                    points.push(from);
+                }
+                effect.kind = AreaEffectKind::ChainLightning { jumps, falloff, points };
+            }
+        }
+    }
+}
+
+impl Node for AreaEffects {
+    fn update(mut node: RefMut<Self>) {
+        for effect in &mut node.active {
+            effect.lived += get_frame_time();
+        }
+    }
+
+    fn fixed_update(mut node: RefMut<Self>) {
+        for effect in &mut node.active {
+            if let AreaEffectKind::Zone { tick_interval, next_tick } = &mut effect.kind {
+                if effect.lived >= *next_tick && effect.lived < effect.ttl {
+                    *next_tick += *tick_interval;
+                    effect.is_resolved = false;
+                }
+            }
+            if !effect.is_resolved {
+                Self::resolve(effect);
+                effect.is_resolved = true;
+            }
+        }
+        node.active.retain(|effect| effect.lived < effect.ttl || !effect.is_resolved);
+    }
+
+    fn draw(node: RefMut<Self>) {
+        for effect in &node.active {
+            let progress = (effect.lived / effect.ttl).min(1.0);
+            let mut color = effect.color;
+            match &effect.kind {
+                AreaEffectKind::Explosion { .. } => {
+                    color.a *= 1.0 - progress;
+                    draw_circle(effect.position.x, effect.position.y, effect.radius * progress, color);
+                }
+                AreaEffectKind::Zone { .. } => {
+                    draw_circle_lines(effect.position.x, effect.position.y, effect.radius, 1.0, color);
+                    color.a *= Self::ZONE_ALPHA;
+                    draw_circle(effect.position.x, effect.position.y, effect.radius, color);
+                }
+                AreaEffectKind::Cone { direction, angle } => {
+                    color.a *= 1.0 - progress;
+                    let start_angle = direction.y.atan2(direction.x) - angle / 2.0;
+                    let step = angle / Self::CONE_SEGMENTS as f32;
+                    for i in 0..Self::CONE_SEGMENTS {
+                        let a = start_angle + step * i as f32;
+                        let b = a + step;
+                        draw_triangle(
+                            effect.position,
+                            effect.position + vec2(a.cos(), a.sin()) * effect.radius,
+                            effect.position + vec2(b.cos(), b.sin()) * effect.radius,
+                            color,
+                        );
+                    }
+                }
+                AreaEffectKind::ChainLightning { points, .. } => {
+                    color.a *= 1.0 - progress;
+                    for segment in points.windows(2) {
+                        let middle = segment[0].lerp(segment[1], 0.5) + vec2(
+                            rand::gen_range(-Self::CHAIN_LIGHTNING_JITTER, Self::CHAIN_LIGHTNING_JITTER),
+                            rand::gen_range(-Self::CHAIN_LIGHTNING_JITTER, Self::CHAIN_LIGHTNING_JITTER),
+                        );
+                        draw_line(segment[0].x, segment[0].y, middle.x, middle.y, effect.size, color);
+                        draw_line(middle.x, middle.y, segment[1].x, segment[1].y, effect.size, color);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use macroquad::{
+    audio::{
+        Sound,
+        PlaySoundParams,
+        play_sound,
+        stop_sound,
+        set_sound_volume,
+        load_sound_from_bytes,
+    },
+    file::{
+        FileError,
+        load_file,
+    },
+    experimental::scene::{
+        Node,
+        Handle,
+        RefMut,
+    },
+    prelude::*,
+};
+
+use crate::{
+    get_global,
+    render::Viewport,
+    show_notice,
+    Resources,
+    Settings,
+};
+
+struct MusicTrack {
+    id: String,
+    sound: Sound,
+    volume: f32,
+}
+
+/// A sound effect that is loaded as two sounds, one holding only the left channel and one holding
+/// only the right channel. Macroquad only has a single volume per sound, so a sound effect is panned
+/// by playing both halves at the same time, with different volumes.
+#[derive(Copy, Clone)]
+pub struct SoundEffect {
+    left: Sound,
+    right: Sound,
+}
+
+impl SoundEffect {
+    const WAVE_FORMAT_PCM: u16 = 1;
+    const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+    const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+    /// Loads a sound effect from a PCM WAV file, with one or two channels
+    pub async fn load(path: &str) -> Result<Self, FileError> {
+        let bytes = load_file(path).await?;
+        let (left, right) = match Self::split_channels(&bytes) {
+            Some(channels) => channels,
+            None => {
+                assert!(false, "Sound effect '{}' is not a PCM WAV file with one or two channels", path);
+                unreachable!()
+            }
+        };
+        Ok(SoundEffect {
+            left: load_sound_from_bytes(&left).await?,
+            right: load_sound_from_bytes(&right).await?,
+        })
+    }
+
+    /// Splits a WAV file into two stereo WAV files, where the first only has sound in the left channel
+    /// and the second only has sound in the right channel. Mono files are copied to both channels.
+    fn split_channels(bytes: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return None;
+        }
+        let read_u16 = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let read_u32 = |offset: usize| u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]);
+
+        let mut format = None;
+        let mut samples = None;
+        let mut offset = 12;
+        while offset + 8 <= bytes.len() {
+            let size = read_u32(offset + 4) as usize;
+            let start = offset + 8;
+            let end = start.checked_add(size)?.min(bytes.len());
+            match &bytes[offset..offset + 4] {
+                b"fmt " if size >= 16 && end - start >= 16 => {
+                    let mut format_tag = read_u16(start);
+                    if format_tag == Self::WAVE_FORMAT_EXTENSIBLE && end - start >= 26 {
+                        format_tag = read_u16(start + 24);
+                    }
+                    format = Some((format_tag, read_u16(start + 2), read_u32(start + 4), read_u16(start + 14)));
+                }
+                b"data" => samples = Some(&bytes[start..end]),
+                _ => {}
+            }
+            // Chunks are padded to an even number of bytes
+            offset = start + size + size % 2;
+        }
+
+        let (format_tag, channel_cnt, sample_rate, bits_per_sample) = format?;
+        let samples = samples?;
+        if (format_tag != Self::WAVE_FORMAT_PCM && format_tag != Self::WAVE_FORMAT_IEEE_FLOAT)
+            || (channel_cnt != 1 && channel_cnt != 2)
+            || bits_per_sample == 0
+            || bits_per_sample % 8 != 0 {
+            return None;
+        }
+
+        let sample_size = bits_per_sample as usize / 8;
+        // 8 bit samples are unsigned, so silence is the middle of the range
+        let silence = vec![if bits_per_sample == 8 { 0x80 } else { 0 }; sample_size];
+        let frame_size = sample_size * channel_cnt as usize;
+        let mut left = Vec::with_capacity(samples.len() / frame_size * sample_size * 2);
+        let mut right = Vec::with_capacity(left.capacity());
+        for frame in samples.chunks_exact(frame_size) {
+            let left_sample = &frame[..sample_size];
+            let right_sample = &frame[frame_size - sample_size..];
+            left.extend_from_slice(left_sample);
+            left.extend_from_slice(&silence);
+            right.extend_from_slice(&silence);
+            right.extend_from_slice(right_sample);
+        }
+
+        let to_wav = |samples: Vec<u8>| {
+            let block_align = 2 * sample_size as u16;
+            let mut wav = Vec::with_capacity(44 + samples.len());
+            wav.extend_from_slice(b"RIFF");
+            wav.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+            wav.extend_from_slice(b"WAVE");
+            wav.extend_from_slice(b"fmt ");
+            wav.extend_from_slice(&16u32.to_le_bytes());
+            wav.extend_from_slice(&format_tag.to_le_bytes());
+            wav.extend_from_slice(&2u16.to_le_bytes());
+            wav.extend_from_slice(&sample_rate.to_le_bytes());
+            wav.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+            wav.extend_from_slice(&block_align.to_le_bytes());
+            wav.extend_from_slice(&bits_per_sample.to_le_bytes());
+            wav.extend_from_slice(b"data");
+            wav.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+            wav.extend_from_slice(&samples);
+            wav
+        };
+
+        Some((to_wav(left), to_wav(right)))
+    }
+}
+
+/// Plays sound effects and music, with volumes taken from the `Settings` global.
+/// Positional sound effects are attenuated by their distance to the center of the camera viewport,
+/// and panned by their horizontal offset from it.
+pub struct AudioManager {
+    current_track: Option<MusicTrack>,
+    fading_tracks: Vec<MusicTrack>,
+}
+
+impl AudioManager {
+    const ATTENUATION_MIN_DISTANCE: f32 = 50.0;
+    const ATTENUATION_MAX_DISTANCE: f32 = 400.0;
+
+    const CROSSFADE_DURATION: f32 = 2.0;
+
+    pub fn new() -> Self {
+        AudioManager {
+            current_track: None,
+            fading_tracks: Vec::new(),
+        }
+    }
+
+    pub fn add_node() -> Handle<Self> {
+        scene::add_node(Self::new())
+    }
+
+    /// Plays a sound effect, attenuated by distance to the listener and panned by its horizontal
+    /// offset from the listener, if a position is given
+    pub fn play_sound_effect(&self, id: &str, position: Option<Vec2>) {
+        let resources = get_global::<Resources>();
+        if let Some(sound_effect) = resources.try_get_sound_effect(id) {
+            let (attenuation, pan) = match position {
+                Some(position) => {
+                    let viewport = get_global::<Viewport>();
+                    let listener = vec2(viewport.x + viewport.w / 2.0, viewport.y + viewport.h / 2.0);
+                    let pan = ((position.x - listener.x) / (viewport.w / 2.0)).clamp(-1.0, 1.0);
+                    (Self::get_attenuation(listener, position), pan)
+                }
+                None => (1.0, 0.0),
+            };
+            let volume = get_global::<Settings>().get_sound_effects_volume() * attenuation;
+            if volume > 0.0 {
+                // A centered sound plays both channels at full volume, and the far side is faded out
+                // as the sound moves to one side
+                let left_volume = volume * (1.0 - pan).min(1.0);
+                let right_volume = volume * (1.0 + pan).min(1.0);
+                play_sound(sound_effect.left, PlaySoundParams {
+                    looped: false,
+                    volume: left_volume,
+                });
+                play_sound(sound_effect.right, PlaySoundParams {
+                    looped: false,
+                    volume: right_volume,
+                });
+            }
+        }
+    }
+
+    fn get_attenuation(listener: Vec2, position: Vec2) -> f32 {
+        let distance = listener.distance(position);
+        let range = Self::ATTENUATION_MAX_DISTANCE - Self::ATTENUATION_MIN_DISTANCE;
+        (1.0 - (distance - Self::ATTENUATION_MIN_DISTANCE) / range).clamp(0.0, 1.0)
+    }
+
+    /// Crossfades from the current music track to the one with the given id
+    pub fn play_music(&mut self, id: &str) {
+        if let Some(track) = &self.current_track {
+            if track.id == id {
+                return;
+            }
+        }
+        let resources = get_global::<Resources>();
+        match resources.try_get_music(id) {
+            Some(sound) => {
+                self.fade_out_current_track();
+                self.fading_tracks.retain(|track| {
+                    if track.sound == *sound {
+                        stop_sound(track.sound);
+                        return false;
+                    }
+                    true
+                });
+                play_sound(*sound, PlaySoundParams {
+                    looped: true,
+                    volume: 0.0,
+                });
+                self.current_track = Some(MusicTrack {
+                    id: id.to_string(),
+                    sound: *sound,
+                    volume: 0.0,
+                });
+            }
+            None => show_notice(&format!("Unable to find music track '{}'", id)),
+        }
+    }
+
+    /// Plays the music track assigned to the map with the given path, if any
+    pub fn play_map_music(&mut self, map_path: &str) {
+        let music_id = get_global::<Resources>().try_get_map_music(map_path).cloned();
+        match music_id {
+            Some(id) => self.play_music(&id),
+            None => self.fade_out_current_track(),
+        }
+    }
+
+    pub fn fade_out_current_track(&mut self) {
+        if let Some(track) = self.current_track.take() {
+            self.fading_tracks.push(track);
+        }
+    }
+
+    /// Stops all music immediately. This should be called before the scene is cleared
+    pub fn stop_all(&mut self) {
+        for track in self.current_track.take().into_iter().chain(self.fading_tracks.drain(..)) {
+            stop_sound(track.sound);
+        }
+    }
+}
+
+impl Node for AudioManager {
+    fn update(mut node: RefMut<Self>) {
+        let music_volume = get_global::<Settings>().get_music_volume();
+        let step = get_frame_time() / Self::CROSSFADE_DURATION;
+        if let Some(track) = node.current_track.as_mut() {
+            track.volume = (track.volume + step).min(1.0);
+            set_sound_volume(track.sound, track.volume * music_volume);
+        }
+        node.fading_tracks.retain_mut(|track| {
+            track.volume -= step;
+            if track.volume <= 0.0 {
+                stop_sound(track.sound);
+                return false;
+            }
+            set_sound_volume(track.sound, track.volume * music_volume);
+            true
+        });
+    }
+}
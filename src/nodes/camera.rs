@@ -138,18 +138,20 @@ impl Node for Camera {
     }
 
     fn fixed_update(mut node: RefMut<Self>) {
-        let actor = Actor::find_local_player().unwrap();
-        let viewport = node.get_viewport();
-        let mod_size = vec2(viewport.w * Self::FOLLOW_THRESHOLD, viewport.h * Self::FOLLOW_THRESHOLD);
-        let bounds = Rect::new(
-            viewport.x + (viewport.w - mod_size.x) / 2.0,
-            viewport.y + (viewport.h - mod_size.y) / 2.0,
-            mod_size.x,
-            mod_size.y,
-        );
-        if !bounds.contains(actor.body.position) {
-            let direction = actor.body.position.sub(node.position).normalize_or_zero();
-            node.position += direction * actor.stats.move_speed;
+        // The local player is missing while it is dead, in which case the camera stays where it is
+        if let Some(actor) = Actor::find_local_player() {
+            let viewport = node.get_viewport();
+            let mod_size = vec2(viewport.w * Self::FOLLOW_THRESHOLD, viewport.h * Self::FOLLOW_THRESHOLD);
+            let bounds = Rect::new(
+                viewport.x + (viewport.w - mod_size.x) / 2.0,
+                viewport.y + (viewport.h - mod_size.y) / 2.0,
+                mod_size.x,
+                mod_size.y,
+            );
+            if !bounds.contains(actor.body.position) {
+                let direction = actor.body.position.sub(node.position).normalize_or_zero();
+                node.position += direction * actor.stats.move_speed;
+            }
         }
 
         set_global(node.get_viewport());
@@ -0,0 +1,132 @@
+use macroquad::{
+    experimental::{
+        scene::{
+            Node,
+            Handle,
+            RefMut,
+        },
+    },
+    color,
+    prelude::*,
+};
+
+use crate::{
+    draw_aligned_text,
+    get_global,
+    nodes::actor::{
+        Damage,
+        DamageEvent,
+    },
+    render::{
+        HorizontalAlignment,
+        Viewport,
+    },
+};
+
+struct CombatTextEntry {
+    target_id: String,
+    damage_type: String,
+    amount: f32,
+    is_critical: bool,
+    position: Vec2,
+    lived: f32,
+}
+
+pub struct CombatText {
+    active: Vec<CombatTextEntry>,
+}
+
+impl CombatText {
+    const TTL: f32 = 1.0;
+    const MERGE_TIME: f32 = 0.5;
+    const RISE_SPEED: f32 = 12.0;
+    const OFFSET_Y: f32 = -16.0;
+
+    const FONT_SIZE: u16 = 32;
+    const FONT_SCALE: f32 = 0.25;
+    const CRITICAL_FONT_SCALE: f32 = 0.35;
+
+    pub fn new() -> Self {
+        CombatText {
+            active: Vec::new(),
+        }
+    }
+
+    pub fn add_node() -> Handle<Self> {
+        scene::add_node(Self::new())
+    }
+
+    // Damage over time, like burning and continuous beams, is dealt in small amounts every frame,
+    // so it is added to any recent entry for the same target and damage type
+    pub fn add_damage_event(&mut self, event: &DamageEvent, position: Vec2) {
+        if !event.is_critical {
+            for entry in &mut self.active {
+                if entry.target_id == event.target_id && entry.damage_type == event.damage_type
+                    && !entry.is_critical && entry.lived < Self::MERGE_TIME {
+                    entry.amount += event.amount;
+                    return;
+                }
+            }
+        }
+        self.active.push(CombatTextEntry {
+            target_id: event.target_id.clone(),
+            damage_type: event.damage_type.clone(),
+            amount: event.amount,
+            is_critical: event.is_critical,
+            position,
+            lived: 0.0,
+        });
+    }
+
+    fn get_color(damage_type: &str) -> Color {
+        if damage_type == Damage::ENERGY_TYPE {
+            color::SKYBLUE
+        } else if damage_type == Damage::FIRE_TYPE {
+            color::ORANGE
+        } else if damage_type == Damage::PSYCHIC_TYPE {
+            color::VIOLET
+        } else {
+            color::WHITE
+        }
+    }
+}
+
+impl Node for CombatText {
+    fn update(mut node: RefMut<Self>) {
+        let dt = get_frame_time();
+        for entry in &mut node.active {
+            entry.lived += dt;
+            entry.position.y -= Self::RISE_SPEED * dt;
+        }
+        node.active.retain(|entry| entry.lived < Self::TTL);
+    }
+
+    fn draw(node: RefMut<Self>) {
+        let viewport = get_global::<Viewport>();
+        for entry in &node.active {
+            let amount = entry.amount.round();
+            if amount < 1.0 || !viewport.contains(entry.position) {
+                continue;
+            }
+            let mut color = Self::get_color(&entry.damage_type);
+            color.a = 1.0 - entry.lived / Self::TTL;
+            let (text, font_scale) = if entry.is_critical {
+                (format!("{}!", amount), Self::CRITICAL_FONT_SCALE)
+            } else {
+                (format!("{}", amount), Self::FONT_SCALE)
+            };
+            draw_aligned_text(
+                &text,
+                entry.position.x,
+                entry.position.y + Self::OFFSET_Y,
+                HorizontalAlignment::Center,
+                TextParams {
+                    font_size: Self::FONT_SIZE,
+                    font_scale,
+                    color,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
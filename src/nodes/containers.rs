@@ -0,0 +1,170 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use macroquad::{
+    experimental::{
+        scene::{
+            Node,
+            Handle,
+            RefMut,
+        },
+    },
+    prelude::*,
+};
+
+use crate::{
+    json,
+    map::MapContainer,
+    nodes::actor::{
+        ActorInventory,
+        ActorInventoryEntryParams,
+    },
+    render::{
+        Sprite,
+        SpriteParams,
+    },
+    Resources,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ContainerParams {
+    pub id: String,
+    pub name: String,
+    pub position: json::Vec2,
+    pub size: json::Vec2,
+    pub items: Vec<ActorInventoryEntryParams>,
+    pub sprite_params: Option<SpriteParams>,
+}
+
+/// A crate, locker or similar, placed on the map, that holds items that can be transferred to and from the player
+#[derive(Clone)]
+pub struct Container {
+    pub id: String,
+    pub name: String,
+    pub rect: Rect,
+    pub inventory: ActorInventory,
+    sprite_params: SpriteParams,
+    sprite: Sprite,
+}
+
+impl Container {
+    pub fn new(id: &str, name: &str, rect: Rect, items: &[ActorInventoryEntryParams], sprite_params: SpriteParams) -> Self {
+        Container {
+            id: id.to_string(),
+            name: name.to_string(),
+            rect,
+            inventory: ActorInventory::new(items),
+            sprite: Sprite::new(sprite_params.clone()),
+            sprite_params,
+        }
+    }
+
+    /// The supply crate from the props texture, used for containers placed on the map
+    pub fn default_sprite_params() -> SpriteParams {
+        SpriteParams {
+            texture_id: Resources::PROPS_TEXTURE_ID.to_string(),
+            texture_coords: json::UVec2::new(9, 4),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_map_container(container: &MapContainer) -> Self {
+        Self::new(&container.id, &container.name, container.rect, &container.items, Self::default_sprite_params())
+    }
+
+    pub fn from_container_params(params: &ContainerParams) -> Self {
+        let position = params.position.to_macroquad();
+        let size = params.size.to_macroquad();
+        Self::new(
+            &params.id,
+            &params.name,
+            Rect::new(position.x, position.y, size.x, size.y),
+            &params.items,
+            params.sprite_params.clone().unwrap_or_else(Self::default_sprite_params),
+        )
+    }
+
+    pub fn to_container_params(&self) -> ContainerParams {
+        ContainerParams {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            position: json::Vec2::from(self.rect.point()),
+            size: json::Vec2::from(self.rect.size()),
+            items: self.inventory.to_entry_params(),
+            sprite_params: Some(self.sprite_params.clone()),
+        }
+    }
+
+    pub fn get_position(&self) -> Vec2 {
+        self.rect.center()
+    }
+
+    /// Returns the distance from the position to the closest point of the container
+    pub fn distance_to(&self, position: Vec2) -> f32 {
+        let closest = vec2(
+            position.x.clamp(self.rect.x, self.rect.x + self.rect.w),
+            position.y.clamp(self.rect.y, self.rect.y + self.rect.h),
+        );
+        closest.distance(position)
+    }
+}
+
+/// The container or corpse that the local player has opened, to transfer items to and from
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferTarget {
+    Container { id: String },
+    Corpse { id: String },
+}
+
+impl TransferTarget {
+    // The transfer is closed if the player moves further away than this
+    pub const MAX_DISTANCE: f32 = 64.0;
+}
+
+pub struct Containers {
+    active: Vec<Container>,
+}
+
+impl Containers {
+    pub fn new() -> Self {
+        Containers {
+            active: Vec::new(),
+        }
+    }
+
+    pub fn add_node() -> Handle<Self> {
+        scene::add_node(Self::new())
+    }
+
+    pub fn spawn(&mut self, container: Container) {
+        self.active.push(container);
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut Container> {
+        self.active.iter_mut().find(|container| container.id == id)
+    }
+
+    /// Returns the container closest to the position, if any are within the radius
+    pub fn find_closest(&self, position: Vec2, radius: f32) -> Option<&Container> {
+        self.active
+            .iter()
+            .map(|container| (container, container.distance_to(position)))
+            .filter(|(_, distance)| *distance <= radius)
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(container, _)| container)
+    }
+
+    pub fn to_container_params(&self) -> Vec<ContainerParams> {
+        self.active.iter().map(|container| container.to_container_params()).collect()
+    }
+}
+
+impl Node for Containers {
+    fn draw(node: RefMut<Self>) {
+        for container in &node.active {
+            container.sprite.draw(container.rect.point(), 0.0);
+        }
+    }
+}
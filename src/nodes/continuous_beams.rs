@@ -9,8 +9,16 @@ use macroquad::{
 };
 
 use crate::{
-    nodes::Actor,
+    nodes::{
+        Actor,
+        actor::{
+            Damage,
+            StatusEffectParams,
+        },
+    },
     physics::beam_collision_check,
+    Factions,
+    FactionStance,
 };
 use std::ops::Sub;
 use crate::nodes::GameState;
@@ -18,7 +26,8 @@ use crate::nodes::GameState;
 pub struct ContinuousBeam {
     pub actor_id: String,
     pub factions: Vec<String>,
-    pub damage: f32,
+    pub damage: Damage,
+    pub status_effects: Vec<StatusEffectParams>,
     pub color: Color,
     pub width: f32,
     pub origin: Vec2,
@@ -42,11 +51,12 @@ impl ContinuousBeams {
         scene::add_node(Self::new())
     }
 
-    pub fn spawn(&mut self, actor_id: &str, factions: &[String], damage: f32, color: Color, width: f32, origin: Vec2, end: Vec2) {
+    pub fn spawn(&mut self, actor_id: &str, factions: &[String], damage: Damage, status_effects: &[StatusEffectParams], color: Color, width: f32, origin: Vec2, end: Vec2) {
         let beam = ContinuousBeam {
             actor_id: actor_id.to_string(),
             factions: factions.to_vec(),
             damage,
+            status_effects: status_effects.to_vec(),
             color,
             width,
             origin,
@@ -67,19 +77,20 @@ impl Node for ContinuousBeams {
                 Self::WIDTH_TOLERANCE_FACTOR,
                 true,
             );
-            'outer: for mut other_actor in scene::find_nodes_by_type::<Actor>() {
-                if other_actor.id != beam.actor_id {
-                    for faction in &beam.factions {
-                        if other_actor.factions.contains(&faction) {
-                            continue 'outer;
-                        }
+            for mut other_actor in scene::find_nodes_by_type::<Actor>() {
+                if other_actor.id != beam.actor_id && other_actor.is_dead() == false {
+                    if Factions::get_stance(&beam.factions, &other_actor.factions) != FactionStance::Hostile {
+                        continue;
                     }
                     let position = match other_actor.body.get_offset_collider() {
                         Some(collider) => collider.get_position(),
                         None => other_actor.body.position,
                     };
                     if beam_collision_check(position, beam.origin, beam.end, beam.width,Self::WIDTH_TOLERANCE_FACTOR) {
-                        other_actor.take_damage(&beam.actor_id, beam.damage);
+                        other_actor.take_damage(&beam.actor_id, beam.damage.clone());
+                        for params in &beam.status_effects {
+                            other_actor.refresh_status_effect(&beam.actor_id, params.clone());
+                        }
                         if beam.origin.distance(position) < beam.origin.distance(cutoff) {
                             cutoff = position;
                         }
@@ -0,0 +1,178 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use macroquad::{
+    experimental::{
+        scene::{
+            Node,
+            Handle,
+            RefMut,
+        },
+    },
+    prelude::*,
+};
+
+use crate::{
+    generate_id,
+    get_global,
+    json,
+    nodes::{
+        Actor,
+        actor::{
+            ActorInventory,
+            ActorInventoryEntryParams,
+        },
+    },
+    render::{
+        SpriteAnimationParams,
+        SpriteAnimationPlayer,
+        Viewport,
+    },
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CorpseParams {
+    pub name: String,
+    pub position: json::Vec2,
+    pub sprite_animation_params: SpriteAnimationParams,
+    pub items: Vec<ActorInventoryEntryParams>,
+    pub gold: u32,
+}
+
+/// The remains of a dead actor, holding the items it carried and the loot it dropped
+#[derive(Clone)]
+pub struct Corpse {
+    pub id: String,
+    pub name: String,
+    pub position: Vec2,
+    pub inventory: ActorInventory,
+    pub gold: u32,
+    sprite_animation: SpriteAnimationPlayer,
+    decay_timer: f32,
+}
+
+impl Corpse {
+    pub fn new(name: &str, position: Vec2, sprite_animation: SpriteAnimationPlayer, inventory: ActorInventory, gold: u32) -> Self {
+        Corpse {
+            id: generate_id(),
+            name: name.to_string(),
+            position,
+            inventory,
+            gold,
+            sprite_animation,
+            decay_timer: 0.0,
+        }
+    }
+
+    pub fn from_corpse_params(params: &CorpseParams) -> Self {
+        let mut sprite_animation = SpriteAnimationPlayer::new(params.sprite_animation_params.clone());
+        sprite_animation.set_death_frame();
+        Self::new(
+            &params.name,
+            params.position.to_macroquad(),
+            sprite_animation,
+            ActorInventory::new(&params.items),
+            params.gold,
+        )
+    }
+
+    pub fn to_corpse_params(&self) -> CorpseParams {
+        CorpseParams {
+            name: self.name.clone(),
+            position: json::Vec2::from(self.position),
+            sprite_animation_params: self.sprite_animation.to_sprite_params(),
+            items: self.inventory.to_entry_params(),
+            gold: self.gold,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inventory.is_empty() && self.gold == 0
+    }
+
+    /// Moves all gold, and as many items as the actor can carry, to the actor
+    pub fn loot(&mut self, actor: &mut Actor) {
+        for entry in self.inventory.take_all() {
+            if actor.can_carry(entry.get_weight()) {
+                actor.add_to_inventory(entry);
+            } else {
+                self.inventory.add_entry(entry);
+            }
+        }
+        actor.gold += self.gold;
+        self.gold = 0;
+    }
+}
+
+pub struct Corpses {
+    active: Vec<Corpse>,
+}
+
+impl Corpses {
+    // Corpses that have nothing left to loot are removed after this many seconds
+    const DECAY_TIME: f32 = 60.0;
+
+    pub fn new() -> Self {
+        Corpses {
+            active: Vec::new(),
+        }
+    }
+
+    pub fn add_node() -> Handle<Self> {
+        scene::add_node(Self::new())
+    }
+
+    pub fn spawn(&mut self, corpse: Corpse) {
+        self.active.push(corpse);
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut Corpse> {
+        self.active.iter_mut().find(|corpse| corpse.id == id)
+    }
+
+    /// Returns the corpse closest to the position, if any are within the radius
+    pub fn find_closest(&self, position: Vec2, radius: f32) -> Option<&Corpse> {
+        self.active
+            .iter()
+            .map(|corpse| (corpse, corpse.position.distance(position)))
+            .filter(|(_, distance)| *distance <= radius)
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(corpse, _)| corpse)
+    }
+
+    pub fn find_in_radius(&mut self, position: Vec2, radius: f32) -> Vec<&mut Corpse> {
+        self.active
+            .iter_mut()
+            .filter(|corpse| corpse.position.distance(position) <= radius)
+            .collect()
+    }
+
+    pub fn to_corpse_params(&self) -> Vec<CorpseParams> {
+        self.active.iter().map(|corpse| corpse.to_corpse_params()).collect()
+    }
+}
+
+impl Node for Corpses {
+    fn update(mut node: RefMut<Self>) {
+        let dt = get_frame_time();
+        for corpse in &mut node.active {
+            corpse.sprite_animation.update();
+            if corpse.is_empty() {
+                corpse.decay_timer += dt;
+            }
+        }
+        node.active.retain(|corpse| corpse.decay_timer < Self::DECAY_TIME);
+    }
+
+    fn draw(mut node: RefMut<Self>) {
+        let viewport = get_global::<Viewport>();
+        for corpse in &mut node.active {
+            if viewport.contains(corpse.position) {
+                let position = corpse.position;
+                corpse.sprite_animation.draw(position, 0.0);
+            }
+        }
+    }
+}
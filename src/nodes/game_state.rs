@@ -9,12 +9,35 @@ use macroquad::{
     prelude::*,
 };
 
-use crate::Map;
+use crate::{
+    get_global,
+    globals::LocalPlayer,
+    Map,
+    nodes::{
+        Actor,
+        ActorControllerKind,
+        ActorParams,
+        Camera,
+        TransferTarget,
+        actor::Dialogue,
+    },
+};
 
 pub struct GameState {
     pub map: Map,
     pub show_character_window: bool,
     pub show_inventory_window: bool,
+    pub show_spell_book_window: bool,
+    pub show_save_games_window: bool,
+    pub show_settings_window: bool,
+    pub show_quest_log_window: bool,
+    pub dialogue: Option<Dialogue>,
+    pub transfer_target: Option<TransferTarget>,
+    /// The local player, as it was when it died, until it is respawned
+    pub dead_player: Option<ActorParams>,
+    pub should_respawn: bool,
+    pub should_save_to: Option<String>,
+    pub should_load_from: Option<String>,
     pub should_quit: bool,
 }
 
@@ -24,6 +47,16 @@ impl GameState {
             map,
             show_character_window: false,
             show_inventory_window: false,
+            show_spell_book_window: false,
+            show_save_games_window: false,
+            show_settings_window: false,
+            show_quest_log_window: false,
+            dialogue: None,
+            transfer_target: None,
+            dead_player: None,
+            should_respawn: false,
+            should_save_to: None,
+            should_load_from: None,
             should_quit: false,
         }
     }
@@ -34,6 +67,23 @@ impl GameState {
 }
 
 impl Node for GameState {
+    fn update(mut node: RefMut<Self>) {
+        if node.should_respawn {
+            node.should_respawn = false;
+            if let Some(params) = node.dead_player.take() {
+                let position = node.map.get_spawn_point(Map::PLAYER_SPAWN_POINT_NAME).position;
+                let local_player = get_global::<LocalPlayer>();
+                let controller_kind = ActorControllerKind::Player { id: local_player.id };
+                let mut actor = Actor::new(position, controller_kind, true, params.clone());
+                actor.id = params.id;
+                scene::add_node(actor);
+                if let Some(mut camera) = scene::find_node_by_type::<Camera>() {
+                    camera.position = position;
+                }
+            }
+        }
+    }
+
     fn draw(node: RefMut<Self>) {
         node.map.draw();
     }
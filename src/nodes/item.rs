@@ -22,7 +22,22 @@ use crate::{
 };
 
 pub use draw_buffer::ItemDrawBuffer;
-use crate::nodes::actor::ActorAbilityParams;
+use crate::nodes::actor::{
+    ActorAbilityParams,
+    ActorStatModifiers,
+    StatusEffectParams,
+};
+
+/// The effects of using a consumable item. Vitals are restored instantly, while the status effects
+/// are applied for their duration. An item that teaches a spell can not be used if the spell is already learned.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConsumableParams {
+    pub health: Option<f32>,
+    pub stamina: Option<f32>,
+    pub energy: Option<f32>,
+    pub status_effects: Option<Vec<StatusEffectParams>>,
+    pub spell_id: Option<String>,
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ItemParams {
@@ -31,7 +46,12 @@ pub struct ItemParams {
     pub name: String,
     pub description: String,
     pub weight: f32,
-    pub ability_params: ActorAbilityParams,
+    /// The number of items that can be held in a single inventory stack. Items are not stackable if this is not set
+    pub max_stack: Option<u32>,
+    pub ability_params: Option<ActorAbilityParams>,
+    pub stat_modifiers: Option<ActorStatModifiers>,
+    pub status_effects: Option<Vec<StatusEffectParams>>,
+    pub consumable: Option<ConsumableParams>,
     pub sprite_params: SpriteParams,
 }
 
@@ -43,24 +63,35 @@ impl Default for ItemParams {
             name: "Unnamed Item".to_string(),
             description: "".to_string(),
             weight: 0.1,
-            ability_params: Default::default(),
+            max_stack: None,
+            ability_params: None,
+            stat_modifiers: None,
+            status_effects: None,
+            consumable: None,
             sprite_params: Default::default(),
         }
     }
 }
 
+impl ItemParams {
+    pub fn get_max_stack(&self) -> u32 {
+        self.max_stack.unwrap_or(1).max(1)
+    }
+}
+
 #[derive(Clone)]
 pub struct Item {
     pub instance_id: String,
     pub position: Vec2,
     pub params: ItemParams,
+    pub quantity: u32,
     sprite: Sprite,
 }
 
 impl Item {
     pub const BODY_ARMOR_KIND: &'static str = "body_armor";
-    pub const HEAD_ARMOR_KIND: &'static str = "body_armor";
-    pub const LEG_ARMOR_KIND: &'static str = "body_armor";
+    pub const HEAD_ARMOR_KIND: &'static str = "head_armor";
+    pub const LEG_ARMOR_KIND: &'static str = "leg_armor";
     pub const ARMOR_KINDS: &'static [&'static str] = &[
         Self::BODY_ARMOR_KIND,
         Self::HEAD_ARMOR_KIND,
@@ -74,25 +105,30 @@ impl Item {
         Self::TWO_HANDED_WEAPON_KIND,
     ];
 
+    pub const TRINKET_KIND: &'static str = "trinket";
+
+    /// Consumable items are used from the inventory or the quick use bar, and require `consumable`
+    pub const CONSUMABLE_KIND: &'static str = "consumable";
+
     pub const MISC_KIND: &'static str = "misc";
     pub const QUEST_KIND: &'static str = "quest";
 
-    pub fn new(position: Vec2, params: ItemParams) -> Self {
-        let id = generate_id();
+    /// Currency is added to the gold of the actor that picks it up, rather than to its inventory
+    pub const CURRENCY_KIND: &'static str = "currency";
+
+    pub fn new(position: Vec2, params: ItemParams, quantity: u32) -> Self {
         let sprite = Sprite::new(params.sprite_params.clone());
         Item {
-            instance_id: id.clone(),
+            instance_id: generate_id(),
             position,
-            params: ItemParams {
-                id,
-                ..params
-            },
+            params,
+            quantity,
             sprite,
         }
     }
 
-    pub fn add_node(position: Vec2, params: ItemParams) -> Handle<Self> {
-        scene::add_node(Self::new(position, params))
+    pub fn add_node(position: Vec2, params: ItemParams, quantity: u32) -> Handle<Self> {
+        scene::add_node(Self::new(position, params, quantity))
     }
 
     pub fn draw_item(&mut self) {
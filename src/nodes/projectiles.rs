@@ -13,21 +13,28 @@ use macroquad::{
 
 use crate::{nodes::{
     Actor,
+    AreaEffects,
     GameState,
-}, physics::Collider, get_global, get_mouse_position};
+    actor::{
+        Damage,
+        StatusEffectParams,
+    },
+}, physics::Collider, get_global, get_mouse_position, Factions, FactionStance};
 use crate::render::{Viewport, SpriteAnimationParams, SpriteAnimationPlayer};
 
 pub enum ProjectileKind {
     Bullet,
     Beam,
     EnergySphere,
+    Explosive { radius: f32, falloff: f32 },
 }
 
 pub struct Projectile {
     actor_id: String,
     factions: Vec<String>,
     kind: ProjectileKind,
-    damage: f32,
+    damage: Damage,
+    status_effects: Vec<StatusEffectParams>,
     color: Color,
     size: f32,
     position: Vec2,
@@ -43,7 +50,9 @@ impl Projectile {
         actor_id: &str,
         factions: &[String],
         kind: ProjectileKind,
-        damage: f32, color: Color,
+        damage: Damage,
+        status_effects: &[StatusEffectParams],
+        color: Color,
         size: f32,
         position: Vec2,
         direction: Vec2,
@@ -64,6 +73,7 @@ impl Projectile {
             factions: factions.to_vec(),
             kind,
             damage,
+            status_effects: status_effects.to_vec(),
             color,
             size,
             position,
@@ -74,6 +84,22 @@ impl Projectile {
             sprite_animation,
         }
     }
+
+    fn explode(&self) {
+        if let ProjectileKind::Explosive { radius, falloff } = self.kind {
+            let mut area_effects = scene::find_node_by_type::<AreaEffects>().unwrap();
+            area_effects.spawn_explosion(
+                &self.actor_id,
+                &self.factions,
+                self.damage.clone(),
+                &self.status_effects,
+                self.color,
+                self.position,
+                radius,
+                falloff,
+            );
+        }
+    }
 }
 
 pub struct Projectiles {
@@ -110,7 +136,8 @@ impl Projectiles {
         actor_id: &str,
         factions: &[String],
         kind: ProjectileKind,
-        damage: f32,
+        damage: Damage,
+        status_effects: &[StatusEffectParams],
         color: Color,
         size: f32,
         position: Vec2,
@@ -132,6 +159,7 @@ impl Projectiles {
             factions,
             kind,
             damage,
+            status_effects,
             color,
             size,
             position,
@@ -162,19 +190,30 @@ impl Node for Projectiles {
         node.active.retain(|projectile| {
             // FIXME: This will allow damage from a projectile that has already hit its ttl in last update
             if projectile.lived >= projectile.ttl {
+                projectile.explode();
                 return false;
             }
             let collider = Collider::circle(0.0, 0.0, projectile.size / 2.0).offset(projectile.position);
-            'outer: for mut other_actor in scene::find_nodes_by_type::<Actor>() {
+            for mut other_actor in scene::find_nodes_by_type::<Actor>() {
+                if other_actor.is_dead() {
+                    continue;
+                }
                 if let Some(other_collider) = other_actor.body.get_offset_collider() {
                     if collider.overlaps(&other_collider) {
                         if projectile.actor_id != other_actor.id {
-                            for faction in &projectile.factions {
-                                if other_actor.factions.contains(&faction) {
-                                    continue 'outer;
-                                }
+                            // Projectiles pass through allied and neutral actors, so stray shots do not turn neutrals hostile
+                            if Factions::get_stance(&projectile.factions, &other_actor.factions) != FactionStance::Hostile {
+                                continue;
+                            }
+                            // Explosive projectiles deal all of their damage through the explosion
+                            if let ProjectileKind::Explosive { .. } = projectile.kind {
+                                projectile.explode();
+                                return false;
+                            }
+                            other_actor.take_damage(&projectile.actor_id, projectile.damage.clone());
+                            for params in &projectile.status_effects {
+                                other_actor.apply_status_effect(&projectile.actor_id, params.clone());
                             }
-                            other_actor.take_damage(&projectile.actor_id, projectile.damage);
                             return false;
                         }
                     }
@@ -182,6 +221,7 @@ impl Node for Projectiles {
             }
             let game_state = scene::find_node_by_type::<GameState>().unwrap();
             if game_state.map.solid_at_collider(collider, true) {
+                projectile.explode();
                 return false;
             }
             return true;
@@ -243,7 +283,7 @@ impl Node for Projectiles {
                                 projectile.color,
                             );
                         },
-                        ProjectileKind::EnergySphere => draw_circle(
+                        ProjectileKind::EnergySphere | ProjectileKind::Explosive { .. } => draw_circle(
                             projectile.position.x,
                             projectile.position.y,
                             projectile.size / 2.0,
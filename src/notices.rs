@@ -0,0 +1,51 @@
+use crate::try_get_global_mut;
+
+pub struct Notice {
+    pub text: String,
+    pub time_left: f32,
+}
+
+/// Messages about errors that the game recovers from, like a save game that can not be read,
+/// that are shown to the player for a while, instead of being printed to stdout
+pub struct Notices {
+    entries: Vec<Notice>,
+}
+
+impl Notices {
+    const DURATION: f32 = 8.0;
+    const MAX_COUNT: usize = 5;
+
+    pub fn new() -> Self {
+        Notices {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, text: &str) {
+        if self.entries.len() >= Self::MAX_COUNT {
+            self.entries.remove(0);
+        }
+        self.entries.push(Notice {
+            text: text.to_string(),
+            time_left: Self::DURATION,
+        });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for notice in &mut self.entries {
+            notice.time_left -= dt;
+        }
+        self.entries.retain(|notice| notice.time_left > 0.0);
+    }
+
+    pub fn get_all(&self) -> &[Notice] {
+        &self.entries
+    }
+}
+
+/// Shows a notice to the player, if the `Notices` global has been set
+pub fn show_notice(text: &str) {
+    if let Some(mut notices) = try_get_global_mut::<Notices>() {
+        notices.push(text);
+    }
+}
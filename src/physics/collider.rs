@@ -32,6 +32,14 @@ impl Collider {
         }
     }
 
+    /// Returns the radius of the smallest circle around the center of the collider that contains it
+    pub fn get_bounding_radius(&self) -> f32 {
+        match self {
+            Collider::Rectangle(rect) => vec2(rect.w, rect.h).length() / 2.0,
+            Collider::Circle(circle) => circle.r,
+        }
+    }
+
     pub fn overlaps(&self, other: &Collider) -> bool {
         match self {
             Collider::Rectangle(rect) => match other {
@@ -58,9 +58,12 @@ pub struct SpriteAnimationPlayer {
     tile_size: Vec2,
     animations: Vec<Animation>,
     animated_sprite: AnimatedSprite,
+    play_once_timer: Option<f32>,
 }
 
 impl SpriteAnimationPlayer {
+    pub const DEATH_ANIMATION: &'static str = "death";
+
     pub fn new(params: SpriteAnimationParams) -> Self {
         let animations: Vec<Animation> = params.animations.iter().map(|anim| anim.to_macroquad()).collect();
         let sprite = AnimatedSprite::new(
@@ -79,6 +82,7 @@ impl SpriteAnimationPlayer {
             tile_size: params.tile_size.to_macroquad(),
             animations,
             animated_sprite: sprite,
+            play_once_timer: None,
         }
     }
 
@@ -86,6 +90,10 @@ impl SpriteAnimationPlayer {
         self.animated_sprite.playing
     }
 
+    pub fn get_animation_id(&self, name: &str) -> Option<usize> {
+        self.animations.iter().position(|anim| anim.name == name)
+    }
+
     pub fn set_animation(&mut self, id: usize) {
         self.animated_sprite.set_animation(id);
     }
@@ -109,6 +117,43 @@ impl SpriteAnimationPlayer {
 
     pub fn stop(&mut self) {
         self.animated_sprite.playing = false;
+        self.play_once_timer = None;
+    }
+
+    /// Plays the animation from the start and stops on its last frame
+    pub fn play_once(&mut self, id: usize) {
+        self.start_animation(id);
+        self.restart_animation();
+        let animation = &self.animations[id];
+        self.play_once_timer = Some(animation.frames as f32 / animation.fps as f32);
+    }
+
+    pub fn set_last_frame(&mut self, id: usize) {
+        self.set_animation(id);
+        self.set_frame(self.animations[id].frames - 1);
+        self.stop();
+    }
+
+    /// Plays the death animation, if the sprite has one, or turns the sprite on its side, if it does not
+    pub fn play_death_animation(&mut self) {
+        match self.get_animation_id(Self::DEATH_ANIMATION) {
+            Some(id) => self.play_once(id),
+            None => {
+                self.rotation = std::f32::consts::FRAC_PI_2;
+                self.stop();
+            }
+        }
+    }
+
+    /// Shows the end of the death animation, without playing it
+    pub fn set_death_frame(&mut self) {
+        match self.get_animation_id(Self::DEATH_ANIMATION) {
+            Some(id) => self.set_last_frame(id),
+            None => {
+                self.rotation = std::f32::consts::FRAC_PI_2;
+                self.stop();
+            }
+        }
     }
 
     pub fn to_sprite_params(&self) -> SpriteAnimationParams {
@@ -123,6 +168,15 @@ impl SpriteAnimationPlayer {
 
     pub fn update(&mut self) {
         self.animated_sprite.update();
+        if let Some(timer) = self.play_once_timer {
+            let timer = timer - get_frame_time();
+            if timer <= 0.0 {
+                let id = self.animated_sprite.current_animation();
+                self.set_last_frame(id);
+            } else {
+                self.play_once_timer = Some(timer);
+            }
+        }
     }
 
     pub fn draw(&mut self, position: Vec2, rotation: f32) {
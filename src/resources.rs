@@ -13,9 +13,20 @@ use macroquad::{
 };
 
 use crate::{
+    factions::FactionParams,
     nodes::{
         ItemParams,
         ActorParams,
+        actor::{
+            ActorLevelParams,
+            SpellParams,
+            FeatParams,
+            DialogueParams,
+            QuestParams,
+            LootTableParams,
+            Damage,
+        },
+        audio_manager::SoundEffect,
     },
 };
 use macroquad::audio::{Sound, load_sound};
@@ -33,19 +44,34 @@ struct SoundData {
     pub filename: String,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+struct MapMusicData {
+    pub map_path: String,
+    pub music_id: String,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 struct ResourcesData {
     textures: Vec<TextureData>,
     sound_effects: Vec<SoundData>,
     music: Vec<SoundData>,
+    map_music: Option<Vec<MapMusicData>>,
 }
 
 pub struct Resources {
     textures: HashMap<String, Texture2D>,
-    sound_effects: HashMap<String, Sound>,
+    sound_effects: HashMap<String, SoundEffect>,
     music: HashMap<String, Sound>,
+    map_music: HashMap<String, String>,
     actors: HashMap<String, ActorParams>,
     items: HashMap<String, ItemParams>,
+    levels: HashMap<u32, ActorLevelParams>,
+    spells: HashMap<String, SpellParams>,
+    feats: HashMap<String, FeatParams>,
+    dialogue: HashMap<String, DialogueParams>,
+    quests: HashMap<String, QuestParams>,
+    factions: HashMap<String, FactionParams>,
+    loot_tables: HashMap<String, LootTableParams>,
 }
 
 impl Resources {
@@ -66,6 +92,13 @@ impl Resources {
 
     const ITEMS_FILE_PATH: &'static str = "assets/items.json";
     const ACTORS_FILE_PATH: &'static str = "assets/actors.json";
+    const LEVELS_FILE_PATH: &'static str = "assets/levels.json";
+    const SPELLS_FILE_PATH: &'static str = "assets/spells.json";
+    const FEATS_FILE_PATH: &'static str = "assets/feats.json";
+    const DIALOGUE_FILE_PATH: &'static str = "assets/dialogue.json";
+    const QUESTS_FILE_PATH: &'static str = "assets/quests.json";
+    const FACTIONS_FILE_PATH: &'static str = "assets/factions.json";
+    const LOOT_TABLES_FILE_PATH: &'static str = "assets/loot_tables.json";
 
     pub async fn new() -> Result<Resources, FileError> {
         let mut textures= HashMap::new();
@@ -94,8 +127,8 @@ impl Resources {
         let mut sound_effects = HashMap::new();
 
         for sound_data in &resources.sound_effects {
-            let sound = load_sound(&format!("{}/{}", Self::SOUND_EFFECTS_FOLDER_PATH, sound_data.filename)).await.unwrap();
-            sound_effects.insert(sound_data.id.clone(), sound);
+            let sound_effect = SoundEffect::load(&format!("{}/{}", Self::SOUND_EFFECTS_FOLDER_PATH, sound_data.filename)).await?;
+            sound_effects.insert(sound_data.id.clone(), sound_effect);
         }
 
         let mut music = HashMap::new();
@@ -105,6 +138,12 @@ impl Resources {
             music.insert(music_data.id.clone(), track);
         }
 
+        let mut map_music = HashMap::new();
+
+        for map_music_data in resources.map_music.unwrap_or_default() {
+            map_music.insert(map_music_data.map_path, map_music_data.music_id);
+        }
+
         let mut actors= HashMap::new();
 
         let json = std::fs::read_to_string(Self::ACTORS_FILE_PATH)
@@ -124,16 +163,130 @@ impl Resources {
             .expect(&format!("Error when parsing items file '{}'", Self::ITEMS_FILE_PATH));
 
         for item in items_data {
+            if let Some(damage_type) = item.ability_params.as_ref().and_then(|params| params.damage_type.as_ref()) {
+                assert!(Damage::is_valid_type(damage_type), "Invalid damage type '{}' in item '{}'", damage_type, item.id);
+            }
             items.insert(item.id.clone(), item);
         }
 
-        Ok(Resources {
+        let mut levels = HashMap::new();
+
+        let json = std::fs::read_to_string(Self::LEVELS_FILE_PATH)
+            .expect(&format!("Unable to find levels file '{}'", Self::LEVELS_FILE_PATH));
+        let levels_data: Vec<ActorLevelParams> = serde_json::from_str(&json)
+            .expect(&format!("Error when parsing levels file '{}'", Self::LEVELS_FILE_PATH));
+
+        for level in levels_data {
+            levels.insert(level.level, level);
+        }
+
+        let mut spells = HashMap::new();
+
+        let json = std::fs::read_to_string(Self::SPELLS_FILE_PATH)
+            .expect(&format!("Unable to find spells file '{}'", Self::SPELLS_FILE_PATH));
+        let spells_data: Vec<SpellParams> = serde_json::from_str(&json)
+            .expect(&format!("Error when parsing spells file '{}'", Self::SPELLS_FILE_PATH));
+
+        for spell in spells_data {
+            if let Some(damage_type) = &spell.ability_params.damage_type {
+                assert!(Damage::is_valid_type(damage_type), "Invalid damage type '{}' in spell '{}'", damage_type, spell.id);
+            }
+            spells.insert(spell.id.clone(), spell);
+        }
+
+        let mut feats = HashMap::new();
+
+        let json = std::fs::read_to_string(Self::FEATS_FILE_PATH)
+            .expect(&format!("Unable to find feats file '{}'", Self::FEATS_FILE_PATH));
+        let feats_data: Vec<FeatParams> = serde_json::from_str(&json)
+            .expect(&format!("Error when parsing feats file '{}'", Self::FEATS_FILE_PATH));
+
+        for feat in feats_data {
+            if let Some(damage_type) = feat.ability_modifiers.as_ref().and_then(|modifiers| modifiers.damage_type.as_ref()) {
+                assert!(Damage::is_valid_type(damage_type), "Invalid damage type '{}' in feat '{}'", damage_type, feat.id);
+            }
+            feats.insert(feat.id.clone(), feat);
+        }
+
+        let mut dialogue = HashMap::new();
+
+        let json = std::fs::read_to_string(Self::DIALOGUE_FILE_PATH)
+            .expect(&format!("Unable to find dialogue file '{}'", Self::DIALOGUE_FILE_PATH));
+        let dialogue_data: Vec<DialogueParams> = serde_json::from_str(&json)
+            .expect(&format!("Error when parsing dialogue file '{}'", Self::DIALOGUE_FILE_PATH));
+
+        for params in dialogue_data {
+            dialogue.insert(params.id.clone(), params);
+        }
+
+        let mut quests = HashMap::new();
+
+        let json = std::fs::read_to_string(Self::QUESTS_FILE_PATH)
+            .expect(&format!("Unable to find quests file '{}'", Self::QUESTS_FILE_PATH));
+        let quests_data: Vec<QuestParams> = serde_json::from_str(&json)
+            .expect(&format!("Error when parsing quests file '{}'", Self::QUESTS_FILE_PATH));
+
+        for quest in quests_data {
+            quests.insert(quest.id.clone(), quest);
+        }
+
+        let mut factions = HashMap::new();
+
+        let json = std::fs::read_to_string(Self::FACTIONS_FILE_PATH)
+            .expect(&format!("Unable to find factions file '{}'", Self::FACTIONS_FILE_PATH));
+        let factions_data: Vec<FactionParams> = serde_json::from_str(&json)
+            .expect(&format!("Error when parsing factions file '{}'", Self::FACTIONS_FILE_PATH));
+
+        for faction in factions_data {
+            factions.insert(faction.id.clone(), faction);
+        }
+
+        let mut loot_tables = HashMap::new();
+
+        let json = std::fs::read_to_string(Self::LOOT_TABLES_FILE_PATH)
+            .expect(&format!("Unable to find loot tables file '{}'", Self::LOOT_TABLES_FILE_PATH));
+        let loot_tables_data: Vec<LootTableParams> = serde_json::from_str(&json)
+            .expect(&format!("Error when parsing loot tables file '{}'", Self::LOOT_TABLES_FILE_PATH));
+
+        for loot_table in loot_tables_data {
+            loot_tables.insert(loot_table.id.clone(), loot_table);
+        }
+
+        let resources = Resources {
             textures,
             sound_effects,
             music,
+            map_music,
             actors,
             items,
-        })
+            levels,
+            spells,
+            feats,
+            dialogue,
+            quests,
+            factions,
+            loot_tables,
+        };
+
+        for params in resources.dialogue.values() {
+            if let Err(err) = params.validate(&resources) {
+                assert!(false, "Invalid dialogue '{}' in '{}': {}", params.id, Self::DIALOGUE_FILE_PATH, err);
+            }
+        }
+
+        for params in resources.quests.values() {
+            if let Err(err) = params.validate(&resources) {
+                assert!(false, "Invalid quest '{}' in '{}': {}", params.id, Self::QUESTS_FILE_PATH, err);
+            }
+        }
+
+        for params in resources.loot_tables.values() {
+            if let Err(err) = params.validate(&resources) {
+                assert!(false, "Invalid loot table '{}' in '{}': {}", params.id, Self::LOOT_TABLES_FILE_PATH, err);
+            }
+        }
+
+        Ok(resources)
     }
 
     pub fn get_texture(&self, id: &str) -> &Texture2D {
@@ -144,6 +297,26 @@ impl Resources {
         self.textures.get(id)
     }
 
+    pub fn get_sound_effect(&self, id: &str) -> &SoundEffect {
+        self.sound_effects.get(id).unwrap()
+    }
+
+    pub fn try_get_sound_effect(&self, id: &str) -> Option<&SoundEffect> {
+        self.sound_effects.get(id)
+    }
+
+    pub fn get_music(&self, id: &str) -> &Sound {
+        self.music.get(id).unwrap()
+    }
+
+    pub fn try_get_music(&self, id: &str) -> Option<&Sound> {
+        self.music.get(id)
+    }
+
+    pub fn try_get_map_music(&self, map_path: &str) -> Option<&String> {
+        self.map_music.get(map_path)
+    }
+
     pub fn get_actor(&self, id: &str) -> &ActorParams {
         self.actors.get(id).unwrap()
     }
@@ -159,4 +332,60 @@ impl Resources {
     pub fn try_get_item(&self, id: &str) -> Option<&ItemParams> {
         self.items.get(id)
     }
+
+    pub fn get_spell(&self, id: &str) -> &SpellParams {
+        self.spells.get(id).unwrap()
+    }
+
+    pub fn try_get_spell(&self, id: &str) -> Option<&SpellParams> {
+        self.spells.get(id)
+    }
+
+    pub fn get_feat(&self, id: &str) -> &FeatParams {
+        self.feats.get(id).unwrap()
+    }
+
+    pub fn try_get_feat(&self, id: &str) -> Option<&FeatParams> {
+        self.feats.get(id)
+    }
+
+    pub fn get_dialogue(&self, id: &str) -> &DialogueParams {
+        self.dialogue.get(id).unwrap()
+    }
+
+    pub fn try_get_dialogue(&self, id: &str) -> Option<&DialogueParams> {
+        self.dialogue.get(id)
+    }
+
+    pub fn get_quest(&self, id: &str) -> &QuestParams {
+        self.quests.get(id).unwrap()
+    }
+
+    pub fn try_get_quest(&self, id: &str) -> Option<&QuestParams> {
+        self.quests.get(id)
+    }
+
+    pub fn get_faction(&self, id: &str) -> &FactionParams {
+        self.factions.get(id).unwrap()
+    }
+
+    pub fn try_get_faction(&self, id: &str) -> Option<&FactionParams> {
+        self.factions.get(id)
+    }
+
+    pub fn get_all_factions(&self) -> Vec<&FactionParams> {
+        self.factions.values().collect()
+    }
+
+    pub fn get_loot_table(&self, id: &str) -> &LootTableParams {
+        self.loot_tables.get(id).unwrap()
+    }
+
+    pub fn try_get_loot_table(&self, id: &str) -> Option<&LootTableParams> {
+        self.loot_tables.get(id)
+    }
+
+    pub fn try_get_level(&self, level: u32) -> Option<&ActorLevelParams> {
+        self.levels.get(&level)
+    }
 }
@@ -0,0 +1,234 @@
+use std::fs;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use macroquad::prelude::*;
+
+use crate::{
+    factions::ReputationParams,
+    get_global,
+    set_global,
+    show_notice,
+    Factions,
+    json,
+    Map,
+    Resources,
+    nodes::{
+        Actor,
+        ActorParams,
+        AudioManager,
+        Camera,
+        CombatText,
+        Corpses,
+        Corpse,
+        CorpseParams,
+        Container,
+        ContainerParams,
+        Containers,
+        ContinuousBeams,
+        AreaEffects,
+        GameState,
+        Item,
+        Projectiles,
+        actor::ActorDrawBuffer,
+        item::ItemDrawBuffer,
+    },
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedActor {
+    pub controller_kind: json::ActorControllerKind,
+    pub params: ActorParams,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedItem {
+    pub id: String,
+    pub position: json::Vec2,
+    pub quantity: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub map_path: String,
+    pub tile_size: json::UVec2,
+    pub camera_position: json::Vec2,
+    pub actors: Vec<SavedActor>,
+    pub items: Vec<SavedItem>,
+    pub reputation: Vec<ReputationParams>,
+    pub corpses: Vec<CorpseParams>,
+    pub containers: Vec<ContainerParams>,
+    /// The local player, if the game was saved while it was dead
+    pub dead_player: Option<ActorParams>,
+}
+
+impl SaveGame {
+    pub const VERSION: u32 = 6;
+
+    pub const QUICKSAVE_SLOT: &'static str = "quicksave";
+    pub const SLOT_COUNT: usize = 3;
+
+    const SAVE_GAMES_FOLDER_PATH: &'static str = "saves";
+
+    pub fn get_slot_name(index: usize) -> String {
+        format!("slot_{:02}", index + 1)
+    }
+
+    fn get_path(slot: &str) -> String {
+        format!("{}/{}.json", Self::SAVE_GAMES_FOLDER_PATH, slot)
+    }
+
+    pub fn exists(slot: &str) -> bool {
+        fs::metadata(Self::get_path(slot)).is_ok()
+    }
+
+    /// Takes a snapshot of the current scene
+    pub fn create() -> Self {
+        let game_state = scene::find_node_by_type::<GameState>().unwrap();
+        let camera = scene::find_node_by_type::<Camera>().unwrap();
+        let actors = scene::find_nodes_by_type::<Actor>()
+            .map(|actor| SavedActor {
+                controller_kind: json::ActorControllerKind::from(actor.controller.kind),
+                params: actor.to_actor_params(),
+            })
+            .collect();
+        let items = scene::find_nodes_by_type::<Item>()
+            .map(|item| SavedItem {
+                id: item.params.id.clone(),
+                position: json::Vec2::from(item.position),
+                quantity: item.quantity,
+            })
+            .collect();
+        SaveGame {
+            version: Self::VERSION,
+            map_path: game_state.map.path.clone(),
+            tile_size: json::UVec2::from(game_state.map.tile_size),
+            camera_position: json::Vec2::from(camera.position),
+            actors,
+            items,
+            reputation: get_global::<Factions>().to_reputation_params(),
+            corpses: scene::find_node_by_type::<Corpses>().unwrap().to_corpse_params(),
+            containers: scene::find_node_by_type::<Containers>().unwrap().to_container_params(),
+            dead_player: game_state.dead_player.clone(),
+        }
+    }
+
+    pub fn save(&self, slot: &str) -> std::io::Result<()> {
+        fs::create_dir_all(Self::SAVE_GAMES_FOLDER_PATH)?;
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(Self::get_path(slot), json)
+    }
+
+    pub fn load(slot: &str) -> Result<Self, String> {
+        let path = Self::get_path(slot);
+        let json = fs::read_to_string(&path)
+            .map_err(|err| format!("Unable to read save game '{}': {}", path, err))?;
+        let save_game: SaveGame = serde_json::from_str(&json)
+            .map_err(|err| format!("Error when parsing save game '{}': {}", path, err))?;
+        if save_game.version != Self::VERSION {
+            return Err(format!("Save game '{}' has version {}, expected version {}", path, save_game.version, Self::VERSION));
+        }
+        save_game.validate(&get_global::<Resources>())
+            .map_err(|err| format!("Invalid save game '{}': {}", path, err))?;
+        Ok(save_game)
+    }
+
+    /// Checks everything that would otherwise panic when the save game is restored, like unknown items
+    fn validate(&self, resources: &Resources) -> Result<(), String> {
+        for saved_actor in &self.actors {
+            saved_actor.controller_kind.validate()
+                .and_then(|_| saved_actor.params.validate(resources))
+                .map_err(|err| format!("{} in actor '{}'", err, saved_actor.params.name))?;
+        }
+        if let Some(dead_player) = &self.dead_player {
+            dead_player.validate(resources)
+                .map_err(|err| format!("{} in dead player '{}'", err, dead_player.name))?;
+        }
+        for item in &self.items {
+            if resources.try_get_item(&item.id).is_none() {
+                return Err(format!("Unable to find item '{}'", item.id));
+            }
+        }
+        for corpse in &self.corpses {
+            for entry in &corpse.items {
+                entry.validate(resources).map_err(|err| format!("{} in corpse of '{}'", err, corpse.name))?;
+            }
+        }
+        for container in &self.containers {
+            for entry in &container.items {
+                entry.validate(resources).map_err(|err| format!("{} in container '{}'", err, container.name))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Clears the current scene and rebuilds it from the save game
+    pub async fn restore(&self) {
+        if let Some(mut audio_manager) = scene::find_node_by_type::<AudioManager>() {
+            audio_manager.stop_all();
+        }
+        scene::clear();
+
+        set_global(Factions::from_reputation(&self.reputation));
+
+        let map = Map::new(self.tile_size.to_macroquad(), &self.map_path).await;
+        let game_state = GameState::add_node(map);
+        scene::get_node(game_state).dead_player = self.dead_player.clone();
+
+        Camera::add_node(self.camera_position.to_macroquad());
+
+        ItemDrawBuffer::add_node();
+
+        {
+            let resources = get_global::<Resources>();
+            for item in &self.items {
+                match resources.try_get_item(&item.id) {
+                    Some(params) => {
+                        Item::add_node(item.position.to_macroquad(), params.clone(), item.quantity);
+                    }
+                    None => show_notice(&format!("Save game references unknown item '{}'", item.id)),
+                }
+            }
+        }
+
+        Projectiles::add_node();
+        ContinuousBeams::add_node();
+        AreaEffects::add_node();
+
+        {
+            let corpses = Corpses::add_node();
+            let mut corpses = scene::get_node(corpses);
+            for params in &self.corpses {
+                corpses.spawn(Corpse::from_corpse_params(params));
+            }
+        }
+
+        // The containers of the map are restored from the save game, rather than from the map, with their current contents
+        {
+            let containers = Containers::add_node();
+            let mut containers = scene::get_node(containers);
+            for params in &self.containers {
+                containers.spawn(Container::from_container_params(params));
+            }
+        }
+
+        for saved_actor in &self.actors {
+            let params = saved_actor.params.clone();
+            let position = params.position.map(|position| position.to_macroquad()).unwrap_or_default();
+            let mut actor = Actor::new(position, saved_actor.controller_kind.to_controller_kind(), false, params);
+            actor.id = saved_actor.params.id.clone();
+            scene::add_node(actor);
+        }
+
+        ActorDrawBuffer::add_node();
+
+        CombatText::add_node();
+
+        let audio_manager = AudioManager::add_node();
+        scene::get_node(audio_manager).play_map_music(&self.map_path);
+    }
+}
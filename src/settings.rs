@@ -0,0 +1,57 @@
+use std::fs;
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use crate::show_notice;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub sound_effects_volume: f32,
+    pub music_volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            sound_effects_volume: 1.0,
+            music_volume: 0.5,
+        }
+    }
+}
+
+impl Settings {
+    const SETTINGS_FILE_PATH: &'static str = "settings.json";
+
+    /// Loads the settings file, falling back to the default settings if it is missing or invalid
+    pub fn load() -> Self {
+        let json = match fs::read_to_string(Self::SETTINGS_FILE_PATH) {
+            Ok(json) => json,
+            Err(_) => return Default::default(),
+        };
+        match serde_json::from_str(&json) {
+            Ok(settings) => settings,
+            Err(err) => {
+                show_notice(&format!("Error when parsing settings file '{}': {}", Self::SETTINGS_FILE_PATH, err));
+                Default::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(Self::SETTINGS_FILE_PATH, json)
+    }
+
+    pub fn get_sound_effects_volume(&self) -> f32 {
+        (self.master_volume * self.sound_effects_volume).clamp(0.0, 1.0)
+    }
+
+    pub fn get_music_volume(&self) -> f32 {
+        (self.master_volume * self.music_volume).clamp(0.0, 1.0)
+    }
+}